@@ -1,9 +1,8 @@
 //! Linux I2C Demo
-#[path = "../src/lib.rs"] // Here
-use aht20;
+//!
+//! Pass `--keep-going` to log read errors and carry on instead of exiting on the first one.
 use {
-    // aht20::*,
-
+    aht20::*,
     embedded_hal::blocking::delay::DelayMs,
     linux_embedded_hal as hal,
     std::{env, process},
@@ -11,19 +10,27 @@ use {
 
 fn main() {
     let args: Vec<String> = env::args().collect();
-    if args.len() != 2 {
-        println!("usage: {} /dev/i2c-N", args[0]);
+    let keep_going = args.len() == 3 && args[2] == "--keep-going";
+    if args.len() != 2 && !keep_going {
+        println!("usage: {} /dev/i2c-N [--keep-going]", args[0]);
         process::exit(1);
     }
 
     let i2c = hal::I2cdev::new(&args[1]).unwrap();
 
-    let mut delay = hal::Delay as DelayMs<u16>;
+    let mut dev = Aht20::new(i2c, &mut hal::Delay).unwrap();
 
-    let mut dev = Aht20::new(i2c, delay).unwrap();
+    if keep_going {
+        read_logging_errors(&mut dev);
+    } else {
+        read_until_error(&mut dev);
+    }
+}
 
+/// Prints a reading every second, panicking on the first error.
+fn read_until_error(dev: &mut Aht20<hal::I2cdev>) {
     loop {
-        let (h, t) = dev.read(delay).unwrap();
+        let (h, t) = dev.read(&mut hal::Delay).unwrap();
 
         println!(
             "relative humidity={0}%; temperature={1}C",
@@ -34,3 +41,20 @@ fn main() {
         hal::Delay.delay_ms(1000u16);
     }
 }
+
+/// Prints a reading every second, logging errors and retrying on the next tick.
+fn read_logging_errors(dev: &mut Aht20<hal::I2cdev>) {
+    loop {
+        match dev.read(&mut hal::Delay) {
+            Ok((h, t)) => println!(
+                "relative humidity={0}%; temperature={1}C",
+                h.rh(),
+                t.celsius()
+            ),
+            Err(Error::Bus(e)) => eprintln!("bus error: {}", e),
+            Err(e) => eprintln!("sensor error: {:?}", e),
+        }
+
+        hal::Delay.delay_ms(1000u16);
+    }
+}
@@ -0,0 +1,40 @@
+//! Linux I2C Demo sharing the bus with an EEPROM
+use {
+    aht20::{hal::Borrowed, *},
+    embedded_hal::blocking::{delay::DelayMs, i2c::WriteRead},
+    linux_embedded_hal as hal,
+    std::{env, process},
+};
+
+const EEPROM_ADDRESS: u8 = 0x50;
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    if args.len() != 2 {
+        println!("usage: {} /dev/i2c-N", args[0]);
+        process::exit(1);
+    }
+
+    let mut i2c = hal::I2cdev::new(&args[1]).unwrap();
+
+    loop {
+        // The sensor only borrows the bus while it is in scope
+        let (h, t) = {
+            let mut dev = Aht20Ref::new(Borrowed(&mut i2c), &mut hal::Delay).unwrap();
+            dev.read(&mut hal::Delay).unwrap()
+        };
+
+        // Talk to another device on the same bus
+        let buf = &mut [0u8; 1];
+        i2c.write_read(EEPROM_ADDRESS, &[0u8], buf).unwrap();
+
+        println!(
+            "relative humidity={0}%; temperature={1}C; eeprom[0]={2:#04x}",
+            h.rh(),
+            t.celsius(),
+            buf[0]
+        );
+
+        hal::Delay.delay_ms(1000u16);
+    }
+}
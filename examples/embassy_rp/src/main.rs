@@ -0,0 +1,48 @@
+//! Embassy RP2040 Demo
+//!
+//! Reads the sensor on I2C0 (SDA on GP4, SCL on GP5) once per second. The bus is shared through
+//! `embassy-embedded-hal`, so other tasks can use it between transactions.
+
+#![no_std]
+#![no_main]
+
+use {
+    aht20::Aht20Async,
+    embassy_embedded_hal::shared_bus::asynch::i2c::I2cDevice,
+    embassy_executor::Spawner,
+    embassy_rp::{
+        bind_interrupts,
+        i2c::{self, I2c, InterruptHandler},
+        peripherals::I2C0,
+    },
+    embassy_sync::{blocking_mutex::raw::NoopRawMutex, mutex::Mutex},
+    embassy_time::{Delay, Duration, Timer},
+    panic_halt as _,
+    static_cell::StaticCell,
+};
+
+bind_interrupts!(struct Irqs {
+    I2C0_IRQ => InterruptHandler<I2C0>;
+});
+
+type Bus = Mutex<NoopRawMutex, I2c<'static, I2C0, i2c::Async>>;
+
+#[embassy_executor::main]
+async fn main(_spawner: Spawner) {
+    let p = embassy_rp::init(Default::default());
+
+    static BUS: StaticCell<Bus> = StaticCell::new();
+    let i2c = I2c::new_async(p.I2C0, p.PIN_5, p.PIN_4, Irqs, i2c::Config::default());
+    let bus = BUS.init(Mutex::new(i2c));
+
+    let mut dev = Aht20Async::new_embassy(I2cDevice::new(bus)).await.unwrap();
+
+    loop {
+        if let Ok((h, t)) = dev.read(&mut Delay).await {
+            // Hand the values to a display or radio task here
+            let _ = (h.rh(), t.celsius());
+        }
+
+        Timer::after(Duration::from_secs(1)).await;
+    }
+}
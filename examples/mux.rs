@@ -0,0 +1,42 @@
+//! Linux I2C Demo reading four sensors behind a TCA9548A multiplexer
+use {
+    aht20::*,
+    embedded_hal::blocking::{delay::DelayMs, i2c::Write},
+    linux_embedded_hal as hal,
+    std::{env, process},
+};
+
+const MUX_ADDRESS: u8 = 0x70;
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    if args.len() != 2 {
+        println!("usage: {} /dev/i2c-N", args[0]);
+        process::exit(1);
+    }
+
+    // Each sensor gets its own handle on the bus, selecting its mux channel before every operation
+    let mut sensors: Vec<_> = (0..4u8)
+        .map(|channel| {
+            let i2c = hal::I2cdev::new(&args[1]).unwrap();
+            let select = move |i2c: &mut hal::I2cdev| i2c.write(MUX_ADDRESS, &[1 << channel]);
+            Aht20::with_bus_hook(i2c, select, &mut hal::Delay).unwrap()
+        })
+        .collect();
+
+    loop {
+        for (channel, dev) in sensors.iter_mut().enumerate() {
+            match dev.read(&mut hal::Delay) {
+                Ok((h, t)) => println!(
+                    "channel {0}: relative humidity={1}%; temperature={2}C",
+                    channel,
+                    h.rh(),
+                    t.celsius()
+                ),
+                Err(e) => println!("channel {0}: error {1:?}", channel, e),
+            }
+        }
+
+        hal::Delay.delay_ms(1000u16);
+    }
+}
@@ -0,0 +1,55 @@
+//! Linux I2C Demo sharing an embedded-hal 1.0 bus between two drivers
+use {
+    aht20::{eh1::Eh1, *},
+    core::cell::RefCell,
+    embedded_hal_1::{delay::DelayNs, i2c::I2c},
+    embedded_hal_bus::i2c::RefCellDevice,
+    linux_embedded_hal_1 as hal,
+    std::{env, process},
+};
+
+/// Minimal driver for a 24C02 EEPROM, standing in for any other device on the bus.
+struct Eeprom<I2C> {
+    i2c: I2C,
+}
+
+impl<I2C: I2c> Eeprom<I2C> {
+    const ADDRESS: u8 = 0x50;
+
+    fn read_byte(&mut self, offset: u8) -> Result<u8, I2C::Error> {
+        let buf = &mut [0u8; 1];
+        self.i2c.write_read(Self::ADDRESS, &[offset], buf)?;
+
+        Ok(buf[0])
+    }
+}
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    if args.len() != 2 {
+        println!("usage: {} /dev/i2c-N", args[0]);
+        process::exit(1);
+    }
+
+    let bus = RefCell::new(hal::I2cdev::new(&args[1]).unwrap());
+    let mut delay = hal::Delay;
+
+    let mut dev = Aht20::new(Eh1(RefCellDevice::new(&bus)), &mut Eh1(&mut delay)).unwrap();
+    let mut eeprom = Eeprom {
+        i2c: RefCellDevice::new(&bus),
+    };
+
+    loop {
+        let (h, t) = dev.read(&mut Eh1(&mut delay)).unwrap();
+        let byte = eeprom.read_byte(0).unwrap();
+
+        println!(
+            "relative humidity={0}%; temperature={1}C; eeprom[0]={2:#04x}",
+            h.rh(),
+            t.celsius(),
+            byte
+        );
+
+        delay.delay_ms(1000);
+    }
+}
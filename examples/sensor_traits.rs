@@ -0,0 +1,63 @@
+//! Application code written against the sensor traits, run here with a fake sensor
+use aht20::{hal::Delay, HumiditySensor, Reading, Temperature};
+
+/// Logs a line if the air is humid enough for condensation on a surface at `surface` or below.
+fn check_condensation<S: HumiditySensor>(
+    sensor: &mut S,
+    delay: &mut impl Delay,
+    surface: Temperature,
+) -> Result<bool, S::Error> {
+    // One measurement for both quantities
+    let reading = sensor.reading(delay)?;
+    let margin = reading.temperature - surface;
+    Ok(reading.humidity.rh() > 80.0 && margin.kelvin() < 5.0)
+}
+
+/// Sensor always returning the same reading, standing in for an AHT20, SHT31 or any other.
+struct FakeSensor {
+    reading: Reading,
+    measurements: usize,
+}
+
+impl aht20::TemperatureSensor for FakeSensor {
+    type Error = core::convert::Infallible;
+
+    fn temperature(&mut self, _delay: &mut impl Delay) -> Result<Temperature, Self::Error> {
+        self.measurements += 1;
+        Ok(self.reading.temperature)
+    }
+}
+
+impl HumiditySensor for FakeSensor {
+    fn humidity(&mut self, _delay: &mut impl Delay) -> Result<aht20::Humidity, Self::Error> {
+        self.measurements += 1;
+        Ok(self.reading.humidity)
+    }
+}
+
+/// Delay returning immediately.
+struct NoDelay;
+
+impl Delay for NoDelay {
+    fn delay_ms(&mut self, _ms: u16) {}
+}
+
+fn main() {
+    let mut sensor = FakeSensor {
+        reading: Reading {
+            humidity: aht20::Humidity::from_rh(85.0),
+            temperature: Temperature::from_celsius(20.0),
+            crc_verified: false,
+        },
+        measurements: 0,
+    };
+
+    let humid = check_condensation(&mut sensor, &mut NoDelay, Temperature::from_celsius(17.0));
+    assert_eq!(humid, Ok(true));
+    let dry = check_condensation(&mut sensor, &mut NoDelay, Temperature::from_celsius(10.0));
+    assert_eq!(dry, Ok(false));
+
+    // The default reading takes separate humidity and temperature measurements
+    assert_eq!(sensor.measurements, 4);
+    println!("condensation checks passed");
+}
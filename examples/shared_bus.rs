@@ -0,0 +1,41 @@
+//! Linux I2C Demo using a shared-bus proxy
+use {
+    aht20::*,
+    embedded_hal::blocking::{delay::DelayMs, i2c::WriteRead},
+    linux_embedded_hal as hal,
+    std::{env, process},
+};
+
+const EEPROM_ADDRESS: u8 = 0x50;
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    if args.len() != 2 {
+        println!("usage: {} /dev/i2c-N", args[0]);
+        process::exit(1);
+    }
+
+    let i2c = hal::I2cdev::new(&args[1]).unwrap();
+    let bus = shared_bus::BusManagerSimple::new(i2c);
+
+    let mut dev = Aht20::new(bus.acquire_i2c(), &mut hal::Delay).unwrap();
+    dev.set_bus_retries(3);
+
+    let mut eeprom = bus.acquire_i2c();
+
+    loop {
+        let (h, t) = dev.read(&mut hal::Delay).unwrap();
+
+        let buf = &mut [0u8; 1];
+        eeprom.write_read(EEPROM_ADDRESS, &[0u8], buf).unwrap();
+
+        println!(
+            "relative humidity={0}%; temperature={1}C; eeprom[0]={2:#04x}",
+            h.rh(),
+            t.celsius(),
+            buf[0]
+        );
+
+        hal::Delay.delay_ms(1000u16);
+    }
+}
@@ -0,0 +1,701 @@
+//! Async driver built on `embedded-hal-async`.
+//!
+//! Every bus access is a single transaction, and no transaction is held across a delay, so the
+//! driver works on buses shared between tasks such as `embassy-embedded-hal`'s `I2cDevice`.
+//!
+//! The driver is configured with the same [`Config`] as the blocking one, and follows it the
+//! same way, including the poll strategy, the minimum interval and the recovery settings.
+//! [`PollStrategy::Auto`] polls in frame, as every async bus takes a write and a read in one
+//! transaction.
+
+use {
+    crate::{
+        commands::Command, consts, frame, Config, Error, Humidity, MinIntervalPolicy,
+        PollStrategy, RecoveryError, RecoveryReport, RecoveryStep, StatusFlags, Temperature,
+        Variant,
+    },
+    embedded_hal_async::{
+        delay::DelayNs,
+        i2c::{Error as I2cError, ErrorKind, I2c, NoAcknowledgeSource, SevenBitAddress},
+    },
+};
+
+/// Async AHT20 driver.
+pub struct Aht20Async<I2C> {
+    i2c: I2C,
+    config: Config,
+    pending: Pending,
+    poisoned: bool,
+    clock: Option<fn() -> u32>,
+    last_trigger_ms: Option<u32>,
+    recalibrations: u32,
+}
+
+/// Progress of a measurement, kept across cancelled reads.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Pending {
+    /// No measurement in flight.
+    Idle,
+    /// Trigger command sent, but not known to have completed.
+    Triggering,
+    /// Measurement triggered, result not fetched yet.
+    Measuring,
+}
+
+/// A bus transaction, retried as a whole.
+enum Transfer<'a> {
+    Write(&'a [u8]),
+    WriteRead(&'a [u8], &'a mut [u8]),
+    Read(&'a mut [u8]),
+}
+
+impl<I2C, E> Aht20Async<I2C>
+where
+    I2C: I2c<SevenBitAddress, Error = E>,
+    E: I2cError,
+{
+    /// Creates a new AHT20 device from an I2C peripheral and a Delay.
+    pub async fn new(i2c: I2C, delay: &mut impl DelayNs) -> Result<Self, Error<E>> {
+        Self::new_with_address(i2c, consts::ADDRESS, delay).await
+    }
+
+    /// Creates a new driver for the given sensor model, with the CRC mode suiting it.
+    pub async fn new_with_variant(
+        i2c: I2C,
+        variant: Variant,
+        delay: &mut impl DelayNs,
+    ) -> Result<Self, Error<E>> {
+        let config = Config {
+            variant,
+            crc: variant.default_crc(),
+            ..Config::default()
+        };
+        Self::new_with_config(i2c, config, delay).await
+    }
+
+    /// Creates a new AHT20 device at a non-default I2C address.
+    pub async fn new_with_address(
+        i2c: I2C,
+        address: u8,
+        delay: &mut impl DelayNs,
+    ) -> Result<Self, Error<E>> {
+        let config = Config {
+            address,
+            ..Config::default()
+        };
+        Self::new_with_config(i2c, config, delay).await
+    }
+
+    /// Creates a new AHT20 device with the given configuration.
+    ///
+    /// The sensor is soft reset and calibrated as enabled in `config`, the calibration being
+    /// skipped if the sensor already reports itself as calibrated. An invalid configuration fails
+    /// with [`Error::InvalidConfig`] before any bus traffic.
+    pub async fn new_with_config(
+        i2c: I2C,
+        config: Config,
+        delay: &mut impl DelayNs,
+    ) -> Result<Self, Error<E>> {
+        config.validate().map_err(Error::InvalidConfig)?;
+        let mut dev = Self {
+            i2c,
+            config,
+            pending: Pending::Idle,
+            poisoned: false,
+            clock: None,
+            last_trigger_ms: None,
+            recalibrations: 0,
+        };
+        dev.init_sequence(delay).await?;
+        Ok(dev)
+    }
+
+    /// Waits out the configured power-on delay, soft resets the sensor if enabled in the
+    /// configuration, then resets its registers if needed and calibrates it if enabled, unless
+    /// it already is or the model has no initialization command.
+    ///
+    /// An address NACK on the first transaction fails with [`Error::DeviceNotFound`], later ones
+    /// with [`Error::Bus`], like the blocking driver's initialization.
+    async fn init_sequence(&mut self, delay: &mut impl DelayNs) -> Result<(), Error<E>> {
+        // Give a sensor powered up together with the MCU time to start
+        if self.config.power_on_delay_ms > 0 {
+            delay.delay_ms(self.config.power_on_delay_ms as u32).await;
+        }
+
+        let mut first = true;
+        if self.config.perform_soft_reset {
+            self.reset(delay).await.map_err(not_found_or_bus)?;
+            first = false;
+        }
+        if !self.config.perform_calibration {
+            return Ok(());
+        }
+
+        let mut status = match self.status(delay).await {
+            Ok(status) => status,
+            Err(e) if first => return Err(not_found_or_bus(e)),
+            Err(e) => return Err(Error::Bus(e)),
+        };
+
+        // The datasheet asks for a register reset unless the status reads 0x18 after power-on
+        if self.config.resets_registers(status) {
+            self.reset_registers(delay).await?;
+            status = self.status(delay).await?;
+        }
+        if !status.contains(StatusFlags::CALIBRATION_ENABLE)
+            && self.config.variant.has_init_command()
+        {
+            self.calibrate(delay).await?;
+        }
+
+        // Some models need a pause between the status check and the first trigger
+        let settle_ms = self.config.variant.trigger_settle_ms();
+        if settle_ms > 0 {
+            delay.delay_ms(settle_ms as u32).await;
+        }
+        Ok(())
+    }
+
+    /// Returns the configuration the driver follows.
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    /// Returns how many times [`read`](Self::read) recalibrated a sensor that had lost its
+    /// calibration, see [`Aht20::recalibrations`](crate::Aht20::recalibrations).
+    pub fn recalibrations(&self) -> u32 {
+        self.recalibrations
+    }
+
+    /// Returns whether a fatal error poisoned the driver, see [`Error::is_fatal`].
+    pub fn needs_recovery(&self) -> bool {
+        self.poisoned
+    }
+
+    /// Sets the monotonic millisecond clock enforcing the configured minimum interval between
+    /// measurements.
+    ///
+    /// `now` may wrap. Without a clock the interval is not enforced.
+    pub fn set_clock(&mut self, now: fn() -> u32) {
+        self.clock = Some(now);
+    }
+
+    /// Time left until the next measurement may start, in milliseconds.
+    fn interval_remaining_ms(&self) -> u32 {
+        match (self.clock, self.last_trigger_ms) {
+            (Some(now), Some(last)) => self
+                .config
+                .min_interval_ms
+                .saturating_sub(now().wrapping_sub(last)),
+            _ => 0,
+        }
+    }
+
+    /// Waits out the remainder of the minimum interval if the policy is to block, then fails with
+    /// [`Error::TooSoon`] if it has still not elapsed.
+    ///
+    /// The interval is waited out at most twice, so a stalled clock cannot hang the driver.
+    async fn wait_min_interval(&mut self, delay: &mut impl DelayNs) -> Result<(), Error<E>> {
+        if self.config.min_interval_policy == MinIntervalPolicy::Block {
+            for _ in 0..2 {
+                let ms = self.interval_remaining_ms();
+                if ms == 0 {
+                    break;
+                }
+                delay.delay_ms(ms).await;
+            }
+        }
+
+        match self.interval_remaining_ms() {
+            0 => Ok(()),
+            remaining_ms => Err(Error::TooSoon { remaining_ms }),
+        }
+    }
+
+    /// Whether busy polls fetch the whole measurement frame, as set in the configuration.
+    fn polls_in_frame(&self) -> bool {
+        self.config.poll_strategy != PollStrategy::Status
+    }
+
+    /// Poisons the driver if `result` is a fatal error, see [`Error::is_fatal`].
+    fn poison_if_fatal<T>(&mut self, result: Result<T, Error<E>>) -> Result<T, Error<E>> {
+        if let Err(e) = &result {
+            self.poisoned |= e.is_fatal();
+        }
+        result
+    }
+
+    /// Bytes of `cmd` for the configured [`Variant`].
+    fn command<'a>(&self, cmd: Command<'a>) -> &'a [u8] {
+        cmd.bytes_for(self.config.variant)
+    }
+
+    /// Runs `transfer` on the sensor's address, retrying it while it fails with retryable errors.
+    async fn transfer(
+        &mut self,
+        mut transfer: Transfer<'_>,
+        delay: &mut impl DelayNs,
+    ) -> Result<(), E> {
+        let mut retries = self.config.bus_retries;
+        loop {
+            let address = self.config.address;
+            let result = match &mut transfer {
+                Transfer::Write(bytes) => self.i2c.write(address, bytes).await,
+                Transfer::WriteRead(bytes, buffer) => {
+                    self.i2c.write_read(address, bytes, buffer).await
+                }
+                Transfer::Read(buffer) => self.i2c.read(address, buffer).await,
+            };
+            match result {
+                Err(e) if retries > 0 && is_retryable(&e) => {
+                    retries -= 1;
+                    delay.delay_us(self.config.bus_retry_delay_us).await;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// Writes `bytes` to the sensor, retrying failed transactions.
+    async fn write(&mut self, bytes: &[u8], delay: &mut impl DelayNs) -> Result<(), E> {
+        self.transfer(Transfer::Write(bytes), delay).await
+    }
+
+    /// Gets the sensor status.
+    async fn status(&mut self, delay: &mut impl DelayNs) -> Result<StatusFlags, E> {
+        let buf = &mut [0u8; 1];
+        let transfer = Transfer::WriteRead(self.command(Command::ReadStatus), buf);
+        self.transfer(transfer, delay).await?;
+
+        Ok(StatusFlags { bits: buf[0] })
+    }
+
+    /// Checks whether the sensor is measuring, counting a NACKed address as busy.
+    async fn is_busy(&mut self, delay: &mut impl DelayNs) -> Result<bool, E> {
+        match self.status(delay).await {
+            Ok(status) => Ok(status.contains(StatusFlags::BUSY)),
+            Err(e) if is_address_nack(&e) => Ok(true),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Waits before busy poll number `poll`, following the poll schedule or else for
+    /// `interval_us`.
+    async fn poll_delay(&self, delay: &mut impl DelayNs, poll: u32, interval_us: u32) {
+        delay
+            .delay_us(self.config.poll_wait_us(poll, interval_us))
+            .await;
+    }
+
+    /// Busy polls allowed for a measurement that may have only just started, covering the
+    /// initial wait as well.
+    fn polls_in_flight(&self) -> u32 {
+        let initial_us = self.config.initial_wait_ms as u32 * 1000;
+        self.config.polls_within(initial_us) + self.config.measurement_max_polls
+    }
+
+    /// Polls the sensor until it is idle, for at least as long as a measurement takes.
+    async fn wait_idle(&mut self, delay: &mut impl DelayNs) -> Result<(), Error<E>> {
+        let max_polls = self.polls_in_flight();
+        let mut poll = 0;
+        while self.is_busy(delay).await? {
+            if poll == max_polls {
+                return Err(Error::MaxTriesExceeded);
+            }
+            self.poll_delay(delay, poll, self.config.measurement_poll_interval_us)
+                .await;
+            poll += 1;
+        }
+
+        Ok(())
+    }
+
+    /// Self-calibrate the sensor.
+    ///
+    /// Fails with [`Error::MaxTriesExceeded`] if the sensor stays busy, and with
+    /// [`Error::Uncalibrated`] if it does not report itself as calibrated afterwards. Models
+    /// without an initialization command, see [`Variant`], are only polled, as is a DHT20 already
+    /// reporting itself as calibrated.
+    pub async fn calibrate(&mut self, delay: &mut impl DelayNs) -> Result<(), Error<E>> {
+        // Send calibrate command, if the sensor has one and, for the DHT20, needs it
+        let send = match self.config.variant {
+            Variant::Dht20 => !self
+                .status(delay)
+                .await?
+                .contains(StatusFlags::CALIBRATION_ENABLE),
+            variant => variant.has_init_command(),
+        };
+        if send {
+            self.write(self.command(Command::Initialize), delay).await?;
+        }
+
+        // Let the sensor settle before polling
+        delay
+            .delay_ms(self.config.calibration_poll_interval_ms as u32)
+            .await;
+
+        // Wait until not busy or max tries exceeded
+        let interval_us = self.config.calibration_poll_interval_ms as u32 * 1000;
+        let mut poll = 0;
+        while self.status(delay).await?.contains(StatusFlags::BUSY) {
+            if poll == self.config.calibration_max_polls {
+                return Err(Error::MaxTriesExceeded);
+            }
+            self.poll_delay(delay, poll, interval_us).await;
+            poll += 1;
+        }
+
+        // Confirm sensor is calibrated
+        if !self
+            .status(delay)
+            .await?
+            .contains(StatusFlags::CALIBRATION_ENABLE)
+        {
+            return Err(Error::Uncalibrated);
+        }
+
+        Ok(())
+    }
+
+    /// Resets the sensor's 0x1B, 0x1C and 0x1E registers, following the vendor's power-on
+    /// sequence.
+    ///
+    /// Each register is selected, read back, then written back with its two data bytes.
+    async fn reset_registers(&mut self, delay: &mut impl DelayNs) -> Result<(), E> {
+        for register in consts::RESET_REGISTERS {
+            self.write(&[register, 0x00, 0x00], delay).await?;
+            delay.delay_ms(consts::REGISTER_READ_MS as u32).await;
+
+            let buf = &mut [0u8; 3];
+            self.transfer(Transfer::Read(buf), delay).await?;
+            delay.delay_ms(consts::REGISTER_WRITE_MS as u32).await;
+
+            let bytes = [consts::REGISTER_WRITE | register, buf[1], buf[2]];
+            self.write(&bytes, delay).await?;
+        }
+
+        // Let the registers settle before the sensor is used
+        delay.delay_ms(consts::REGISTER_WRITE_MS as u32).await;
+        Ok(())
+    }
+
+    /// Calibrates the sensor unless it already reports itself as calibrated.
+    ///
+    /// Returns whether a calibration was performed.
+    pub async fn ensure_calibrated(&mut self, delay: &mut impl DelayNs) -> Result<bool, Error<E>> {
+        if self
+            .status(delay)
+            .await?
+            .contains(StatusFlags::CALIBRATION_ENABLE)
+        {
+            return Ok(false);
+        }
+
+        self.calibrate(delay).await?;
+        Ok(true)
+    }
+
+    /// Soft resets the sensor.
+    pub async fn reset(&mut self, delay: &mut impl DelayNs) -> Result<(), E> {
+        // Send soft reset command
+        self.write(self.command(Command::SoftReset), delay).await?;
+        self.pending = Pending::Idle;
+
+        // Wait 20ms as stated in specification
+        delay.delay_ms(consts::SOFT_RESET_MS as u32).await;
+
+        Ok(())
+    }
+
+    /// Soft resets and recalibrates the sensor, recovering it from a glitch.
+    ///
+    /// Can be called at any time, fatal errors included; on success the next reading starts
+    /// afresh.
+    pub async fn reset_and_recalibrate(
+        &mut self,
+        delay: &mut impl DelayNs,
+    ) -> Result<(), Error<E>> {
+        self.reset(delay).await?;
+        self.calibrate(delay).await?;
+        self.poisoned = false;
+
+        Ok(())
+    }
+
+    /// Recovers a misbehaving sensor, including after a fatal error, like the blocking
+    /// [`recover`](crate::Aht20::recover).
+    ///
+    /// The driver is poisoned until the sensor is confirmed calibrated and idle, and a
+    /// measurement is taken and discarded afterwards if [`Config::flush_on_recover`] is set. The
+    /// error notes the step that failed.
+    pub async fn recover(
+        &mut self,
+        delay: &mut impl DelayNs,
+    ) -> Result<RecoveryReport, RecoveryError<E>> {
+        let fail = |step| move |error| RecoveryError { step, error };
+        let mut delay = Timed::new(delay);
+
+        self.poisoned = true;
+        self.reset(&mut delay)
+            .await
+            .map_err(Error::Bus)
+            .map_err(fail(RecoveryStep::Reset))?;
+        let reinitialized = self
+            .ensure_calibrated(&mut delay)
+            .await
+            .map_err(fail(RecoveryStep::Initialize))?;
+        self.confirm_idle(&mut delay)
+            .await
+            .map_err(fail(RecoveryStep::Verify))?;
+        self.poisoned = false;
+
+        let flushed = self.config.flush_on_recover;
+        if flushed {
+            let result = self.measure(&mut delay).await;
+            self.poison_if_fatal(result)
+                .map_err(fail(RecoveryStep::Flush))?;
+        }
+
+        Ok(RecoveryReport {
+            reinitialized,
+            flushed,
+            delay_ms: delay.elapsed_ms(),
+        })
+    }
+
+    /// Checks that the sensor is calibrated and not busy, with a single status read.
+    async fn confirm_idle(&mut self, delay: &mut impl DelayNs) -> Result<(), Error<E>> {
+        let status = self.status(delay).await?;
+        if status.contains(StatusFlags::BUSY) {
+            return Err(Error::StuckBusy);
+        }
+        if !status.contains(StatusFlags::CALIBRATION_ENABLE) {
+            return Err(Error::Uncalibrated);
+        }
+
+        Ok(())
+    }
+
+    /// Reads humidity and temperature.
+    ///
+    /// The sensor is first left alone for the configured initial wait, then polled until it is
+    /// done, as in the blocking [`read`](crate::Aht20::read). The poll strategy, the minimum
+    /// interval, checksum retries, recovery of a sensor stuck busy or uncalibrated and the range
+    /// check follow the configuration too. Fatal errors poison the driver until
+    /// [`recover`](Self::recover) succeeds, see [`Error::is_fatal`].
+    ///
+    /// Every await is a cancellation point. The driver remembers how far a dropped read got, so
+    /// the next read picks up safely:
+    ///
+    /// - dropped while sending the trigger, the sensor may or may not be measuring, so the next
+    ///   read waits until it is idle and triggers a fresh measurement;
+    /// - dropped after the trigger was sent, the next read waits for and consumes the measurement
+    ///   already in flight instead of triggering another one.
+    pub async fn read(
+        &mut self,
+        delay: &mut impl DelayNs,
+    ) -> Result<(Humidity, Temperature), Error<E>> {
+        if self.poisoned {
+            return Err(Error::NeedsRecovery);
+        }
+
+        let result = self.measure_recovering(delay).await;
+        let (humidity, temperature) = self.poison_if_fatal(result)?;
+        if self.config.check_range
+            && (humidity.check_range().is_err() || temperature.check_range().is_err())
+        {
+            return Err(Error::OutOfRange {
+                humidity: humidity.raw(),
+                temperature: temperature.raw(),
+            });
+        }
+
+        Ok((humidity, temperature))
+    }
+
+    /// Measures, retrying and recovering the sensor as set in the configuration.
+    async fn measure_recovering(
+        &mut self,
+        delay: &mut impl DelayNs,
+    ) -> Result<(Humidity, Temperature), Error<E>> {
+        let mut retries = self.config.checksum_retries;
+        let mut recovered = false;
+        let mut recalibrated = false;
+        loop {
+            match self.measure(delay).await {
+                Err(Error::Checksum { .. }) if retries > 0 => retries -= 1,
+                Err(Error::Uncalibrated) if self.config.recover_uncalibrated && !recalibrated => {
+                    recalibrated = true;
+                    let result = self.reset_and_recalibrate(delay).await;
+                    self.poisoned |= result.is_err();
+                    result?;
+                    self.recalibrations = self.recalibrations.wrapping_add(1);
+                }
+                Err(Error::MaxTriesExceeded) if self.config.recover_stuck_busy => {
+                    if recovered {
+                        return Err(Error::StuckBusy);
+                    }
+                    recovered = true;
+                    let result = self.reset_and_recalibrate(delay).await;
+                    self.poisoned |= result.is_err();
+                    result?;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// Triggers a single measurement, or picks up the one in flight, and waits for its result.
+    async fn measure(
+        &mut self,
+        delay: &mut impl DelayNs,
+    ) -> Result<(Humidity, Temperature), Error<E>> {
+        // Let a trigger that may have reached the sensor run its course
+        if self.pending == Pending::Triggering {
+            self.wait_idle(delay).await?;
+            self.pending = Pending::Idle;
+        }
+
+        let max_polls = if self.pending == Pending::Idle {
+            self.wait_min_interval(delay).await?;
+
+            // Send trigger measurement command
+            self.pending = Pending::Triggering;
+            self.write(self.command(Command::TriggerMeasurement), delay)
+                .await?;
+            self.pending = Pending::Measuring;
+            self.last_trigger_ms = self.clock.map(|now| now());
+
+            // Wait for the conversion to be nearly done before polling
+            delay.delay_ms(self.config.initial_wait_ms as u32).await;
+            self.config.measurement_max_polls
+        } else {
+            // The measurement in flight may have only just started
+            self.polls_in_flight()
+        };
+
+        if !self.polls_in_frame() {
+            // Wait until not busy or max tries exceeded
+            let mut poll = 0;
+            while self.is_busy(delay).await? {
+                if poll == max_polls {
+                    return Err(Error::MaxTriesExceeded);
+                }
+                self.poll_delay(delay, poll, self.config.measurement_poll_interval_us)
+                    .await;
+                poll += 1;
+            }
+
+            return self.fetch(delay).await;
+        }
+
+        // Fetch whole frames and check their status byte, so that status and data are read in
+        // the same transaction
+        let mut poll = 0;
+        loop {
+            match self.fetch(delay).await {
+                Err(Error::Busy) if poll < max_polls => {
+                    self.poll_delay(delay, poll, self.config.measurement_poll_interval_us)
+                        .await;
+                    poll += 1;
+                }
+                Err(Error::Busy) => return Err(Error::MaxTriesExceeded),
+                result => return result,
+            }
+        }
+    }
+
+    /// Fetches and decodes a measurement frame, in a single transaction.
+    ///
+    /// Fails with [`Error::Busy`] while the sensor is still measuring, including when it NACKs its
+    /// address.
+    async fn fetch(
+        &mut self,
+        delay: &mut impl DelayNs,
+    ) -> Result<(Humidity, Temperature), Error<E>> {
+        // Read in sensor data, the first byte being the status
+        let buf = &mut [0u8; frame::FRAME_LEN];
+        let len = self.config.frame_len();
+        let transfer = Transfer::WriteRead(self.command(Command::ReadStatus), &mut buf[..len]);
+        match self.transfer(transfer, delay).await {
+            Err(e) if is_address_nack(&e) => return Err(Error::Busy),
+            result => result?,
+        }
+
+        let result = frame::parse(buf, self.config.crc).map(|r| (r.humidity, r.temperature));
+        if !matches!(result, Err(Error::Busy)) {
+            self.pending = Pending::Idle;
+        }
+        result
+    }
+}
+
+#[cfg(feature = "embassy")]
+impl<I2C, E> Aht20Async<I2C>
+where
+    I2C: I2c<SevenBitAddress, Error = E>,
+    E: I2cError,
+{
+    /// Creates a new AHT20 device from an I2C peripheral, waiting on `embassy-time` timers.
+    pub async fn new_embassy(i2c: I2C) -> Result<Self, Error<E>> {
+        Self::new(i2c, &mut embassy_time::Delay).await
+    }
+}
+
+/// Whether `error` means that no device acknowledged its address.
+fn is_address_nack(error: &impl I2cError) -> bool {
+    matches!(
+        error.kind(),
+        ErrorKind::NoAcknowledge(NoAcknowledgeSource::Address | NoAcknowledgeSource::Unknown)
+    )
+}
+
+/// Classifies `error` as [`Error::DeviceNotFound`] if it is an address NACK.
+fn not_found_or_bus<E: I2cError>(error: E) -> Error<E> {
+    if is_address_nack(&error) {
+        Error::DeviceNotFound
+    } else {
+        Error::Bus(error)
+    }
+}
+
+/// Whether `error` is worth retrying, as for `Eh1` buses: arbitration losses
+/// and bus errors, which another master or noise on the lines cause, but not NACKs, overruns or
+/// HAL-specific errors.
+fn is_retryable(error: &impl I2cError) -> bool {
+    matches!(error.kind(), ErrorKind::ArbitrationLoss | ErrorKind::Bus)
+}
+
+/// Delay adding up the time it waits.
+struct Timed<'a, D> {
+    delay: &'a mut D,
+    ns: u64,
+}
+
+impl<'a, D: DelayNs> Timed<'a, D> {
+    fn new(delay: &'a mut D) -> Self {
+        Self { delay, ns: 0 }
+    }
+
+    /// Time waited so far, in whole milliseconds.
+    fn elapsed_ms(&self) -> u32 {
+        (self.ns / 1_000_000).min(u32::MAX as u64) as u32
+    }
+}
+
+impl<D: DelayNs> DelayNs for Timed<'_, D> {
+    async fn delay_ns(&mut self, ns: u32) {
+        self.ns += ns as u64;
+        self.delay.delay_ns(ns).await;
+    }
+
+    async fn delay_us(&mut self, us: u32) {
+        self.ns += us as u64 * 1000;
+        self.delay.delay_us(us).await;
+    }
+
+    async fn delay_ms(&mut self, ms: u32) {
+        self.ns += ms as u64 * 1_000_000;
+        self.delay.delay_ms(ms).await;
+    }
+}
@@ -0,0 +1,283 @@
+//! Builder for configuring the driver before initialization.
+
+use crate::{
+    hal::{Bus, Delay},
+    Aht20, Config, ConfigError, CrcMode, Error, InitError, MinIntervalPolicy, PollStrategy,
+    Uninitialized, Variant, MEASUREMENT_BUDGET_US,
+};
+
+/// Builder for [`Aht20`].
+///
+/// The defaults match [`Aht20::new`].
+#[derive(Debug, Copy, Clone, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Aht20Builder {
+    config: Config,
+    max_retries: Option<u8>,
+    timeout_ms: Option<u32>,
+}
+
+impl Aht20Builder {
+    /// Creates a builder with the default configuration.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the I2C address. Defaults to 0x38.
+    pub fn address(mut self, address: u8) -> Self {
+        self.config.address = address;
+        self
+    }
+
+    /// Sets the sensor model, and the CRC mode to the one suiting it. Defaults to
+    /// [`Variant::Aht20`].
+    ///
+    /// Call [`crc`](Self::crc) afterwards to choose another CRC mode.
+    pub fn variant(mut self, variant: Variant) -> Self {
+        self.config.variant = variant;
+        self.config.crc = variant.default_crc();
+        self
+    }
+
+    /// Sets how the CRC byte of measurement frames is read and checked. Defaults to
+    /// [`CrcMode::Required`].
+    pub fn crc(mut self, mode: CrcMode) -> Self {
+        self.config.crc = mode;
+        self
+    }
+
+    /// Sets the wait after triggering a measurement before the first busy poll. Defaults to 80ms.
+    pub fn initial_wait_ms(mut self, ms: u16) -> Self {
+        self.config.initial_wait_ms = ms;
+        self
+    }
+
+    /// Sets how many busy polls a measurement may take before giving up.
+    ///
+    /// Defaults to as many polls as fit in 50ms at the poll interval, and at least one. Zero is
+    /// rejected when building.
+    pub fn max_retries(mut self, retries: u8) -> Self {
+        self.max_retries = Some(retries);
+        self.timeout_ms = None;
+        self
+    }
+
+    /// Sets how many busy polls a calibration may take before giving up. Defaults to 10.
+    pub fn calibration_max_retries(mut self, retries: u8) -> Self {
+        self.config.calibration_max_polls = retries as u32;
+        self
+    }
+
+    /// Sets the interval between busy polls while calibrating. Defaults to 10ms.
+    pub fn calibration_poll_interval_ms(mut self, ms: u16) -> Self {
+        self.config.calibration_poll_interval_ms = ms;
+        self
+    }
+
+    /// Sets how long a measurement may take before giving up, in milliseconds.
+    ///
+    /// Replaces [`max_retries`](Self::max_retries) with as many polls as fit in the timeout.
+    pub fn timeout_ms(mut self, ms: u32) -> Self {
+        self.timeout_ms = Some(ms);
+        self.max_retries = None;
+        self
+    }
+
+    /// Sets how long a measurement may take before giving up, see
+    /// [`timeout_ms`](Self::timeout_ms).
+    #[cfg(feature = "fugit")]
+    pub fn timeout(self, timeout: fugit::MillisDurationU32) -> Self {
+        self.timeout_ms(timeout.as_ticks())
+    }
+
+    /// Sets the interval between busy polls while waiting for a measurement. Defaults to 10ms.
+    pub fn poll_interval_ms(mut self, ms: u16) -> Self {
+        self.config.measurement_poll_interval_us = ms as u32 * 1000;
+        self
+    }
+
+    /// Sets the interval between busy polls while waiting for a measurement. Defaults to 10ms.
+    #[cfg(feature = "fugit")]
+    pub fn poll_interval(mut self, interval: fugit::MicrosDurationU32) -> Self {
+        self.config.measurement_poll_interval_us = interval.as_ticks();
+        self
+    }
+
+    /// Sets the waits between busy polls, in milliseconds, the last one repeating.
+    ///
+    /// Used by [`Aht20::read`] and [`Aht20::calibrate`] instead of the fixed poll intervals, e.g.
+    /// `&[5, 2, 1]` to poll more and more often once the initial wait is over. Defaults to none.
+    pub fn poll_schedule(mut self, schedule: &'static [u16]) -> Self {
+        self.config.poll_schedule = schedule;
+        self
+    }
+
+    /// Sets the wait before fetching a measurement with
+    /// [`read_fixed_delay`](Aht20::read_fixed_delay). Defaults to 80ms.
+    pub fn fixed_delay_ms(mut self, ms: u16) -> Self {
+        self.config.fixed_delay_ms = ms;
+        self
+    }
+
+    /// Sets how the busy bit is polled while waiting for a measurement. Defaults to
+    /// [`PollStrategy::Auto`].
+    pub fn poll_strategy(mut self, strategy: PollStrategy) -> Self {
+        self.config.poll_strategy = strategy;
+        self
+    }
+
+    /// Sets how many times a failed bus transaction is retried. Defaults to 0.
+    pub fn bus_retries(mut self, retries: u8) -> Self {
+        self.config.bus_retries = retries;
+        self
+    }
+
+    /// Sets the wait before retrying a failed bus transaction, in microseconds. Defaults to 0.
+    pub fn bus_retry_delay_us(mut self, us: u32) -> Self {
+        self.config.bus_retry_delay_us = us;
+        self
+    }
+
+    /// Sets how many times a measurement failing its checksum is triggered again. Defaults to 0.
+    pub fn checksum_retries(mut self, retries: u8) -> Self {
+        self.config.checksum_retries = retries;
+        self
+    }
+
+    /// Sets whether a sensor stuck busy is soft reset and recalibrated, then measured once more.
+    /// Defaults to false.
+    pub fn recover_stuck_busy(mut self, recover: bool) -> Self {
+        self.config.recover_stuck_busy = recover;
+        self
+    }
+
+    /// Sets whether a sensor found uncalibrated is soft reset and recalibrated, then measured
+    /// once more. Defaults to false.
+    pub fn recover_uncalibrated(mut self, recover: bool) -> Self {
+        self.config.recover_uncalibrated = recover;
+        self
+    }
+
+    /// Sets whether [`Aht20::recover`](crate::Aht20::recover) takes and discards a measurement.
+    /// Defaults to false.
+    pub fn flush_on_recover(mut self, flush: bool) -> Self {
+        self.config.flush_on_recover = flush;
+        self
+    }
+
+    /// Sets whether readings outside the sensor's specified output range are rejected. Defaults
+    /// to false.
+    pub fn check_range(mut self, check: bool) -> Self {
+        self.config.check_range = check;
+        self
+    }
+
+    /// Sets how long the power pin is held low when power cycling the sensor. Defaults to 100ms.
+    pub fn power_off_ms(mut self, ms: u16) -> Self {
+        self.config.power_off_ms = ms;
+        self
+    }
+
+    /// Sets the minimum time between the starts of two measurements, limiting self-heating.
+    ///
+    /// The datasheet recommends at most one measurement per second. Only enforced once the
+    /// driver has a clock, see [`Aht20::set_clock`]. Defaults to 0, disabling the check.
+    pub fn min_interval_ms(mut self, ms: u32) -> Self {
+        self.config.min_interval_ms = ms;
+        self
+    }
+
+    /// Sets what to do when a measurement is started too soon. Defaults to
+    /// [`MinIntervalPolicy::Block`].
+    pub fn min_interval_policy(mut self, policy: MinIntervalPolicy) -> Self {
+        self.config.min_interval_policy = policy;
+        self
+    }
+
+    /// Sets the wait before the first transaction of an initialization, in milliseconds.
+    /// Defaults to 0; use [`POWER_ON_MS`](crate::consts::POWER_ON_MS) when the sensor powers up with the MCU.
+    pub fn power_on_delay_ms(mut self, ms: u16) -> Self {
+        self.config.power_on_delay_ms = ms;
+        self
+    }
+
+    /// Skips the calibration during [`build`](Self::build), leaving only the soft reset.
+    pub fn skip_initial_calibration(mut self, skip: bool) -> Self {
+        self.config.perform_calibration = !skip;
+        self
+    }
+
+    /// Skips the register reset the datasheet asks for when the status does not read 0x18 after
+    /// power-on.
+    ///
+    /// The reset is always skipped on buses without a plain read, see
+    /// [`Bus::PLAIN_READ`](crate::hal::Bus::PLAIN_READ).
+    pub fn skip_register_reset(mut self, skip: bool) -> Self {
+        self.config.perform_register_reset = !skip;
+        self
+    }
+
+    /// Returns the configuration the driver will be built with.
+    ///
+    /// Fails if the measurement timeout is out of range for the poll interval, or if no busy polls
+    /// are allowed.
+    pub fn config(&self) -> Result<Config, ConfigError> {
+        let mut config = self.config;
+        config.measurement_poll_interval_us = config.measurement_poll_interval_us.max(1);
+        config.measurement_max_polls = match (self.max_retries, self.timeout_ms) {
+            (Some(retries), _) => retries as u32,
+            (None, Some(ms)) => {
+                let us = ms.checked_mul(1000).ok_or(ConfigError::TimeoutTooLong)?;
+                match config.polls_within(us) {
+                    0 => return Err(ConfigError::TimeoutTooShort),
+                    polls => polls,
+                }
+            }
+            (None, None) => config.polls_within(MEASUREMENT_BUDGET_US).max(1),
+        };
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Creates and initializes the driver.
+    ///
+    /// On failure, including an invalid configuration, the peripheral is handed back in the
+    /// [`InitError`].
+    pub fn build<I2C, E>(
+        self,
+        i2c: I2C,
+        delay: &mut impl Delay,
+    ) -> Result<Aht20<I2C>, InitError<I2C, E>>
+    where
+        I2C: Bus<Error = E>,
+    {
+        match self.config() {
+            Ok(config) => Aht20::new_with_config(i2c, config, delay),
+            Err(e) => Err(InitError {
+                i2c,
+                error: Error::InvalidConfig(e),
+            }),
+        }
+    }
+
+    /// Creates the driver in the [`Uninitialized`] state, without any bus traffic.
+    ///
+    /// [`Aht20::init`] then follows the configuration, including
+    /// [`skip_initial_calibration`](Self::skip_initial_calibration). Fails only on an invalid
+    /// configuration, handing the peripheral back in the [`InitError`].
+    pub fn build_uninitialized<I2C, E>(
+        self,
+        i2c: I2C,
+    ) -> Result<Aht20<I2C, Uninitialized>, InitError<I2C, E>>
+    where
+        I2C: Bus<Error = E>,
+    {
+        match self.config() {
+            Ok(config) => Ok(Aht20::uninitialized_with_config(i2c, config)),
+            Err(e) => Err(InitError {
+                i2c,
+                error: Error::InvalidConfig(e),
+            }),
+        }
+    }
+}
@@ -0,0 +1,41 @@
+//! Low-level sensor commands.
+
+use crate::{consts, Variant};
+
+/// Command sent to the sensor, see [`Aht20::send_command`](crate::Aht20::send_command).
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Command<'a> {
+    /// Triggers a measurement.
+    TriggerMeasurement,
+    /// Initializes the sensor, calibrating it.
+    Initialize,
+    /// Soft resets the sensor.
+    SoftReset,
+    /// Selects the status byte, followed by the measurement data, for the next read.
+    ReadStatus,
+    /// Arbitrary bytes, sent as is.
+    Raw(&'a [u8]),
+}
+
+impl<'a> Command<'a> {
+    /// Returns the bytes written to an AHT20 for this command.
+    pub fn bytes(&self) -> &'a [u8] {
+        self.bytes_for(Variant::Aht20)
+    }
+
+    /// Returns the bytes written to the given sensor model for this command.
+    ///
+    /// Models without an initialization command get the AHT20's.
+    pub fn bytes_for(&self, variant: Variant) -> &'a [u8] {
+        match (self, variant) {
+            (Command::TriggerMeasurement, _) => &consts::TRIGGER_MEASUREMENT,
+            (Command::Initialize, Variant::Aht10) => &consts::AHT10_INIT,
+            (Command::Initialize, _) => &consts::INIT,
+            (Command::SoftReset, _) => &consts::SOFT_RESET,
+            (Command::ReadStatus, Variant::Aht10) => &consts::AHT10_READ_STATUS,
+            (Command::ReadStatus, _) => &consts::READ_STATUS,
+            (Command::Raw(bytes), _) => bytes,
+        }
+    }
+}
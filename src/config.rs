@@ -0,0 +1,332 @@
+//! Driver configuration.
+
+use crate::{consts, frame, Status, StatusFlags, MEASUREMENT_BUDGET_US};
+
+/// Driver configuration, consulted by the driver at runtime.
+///
+/// The default matches [`Aht20::new`](crate::Aht20::new).
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Config {
+    /// I2C address of the sensor.
+    pub address: u8,
+    /// Sensor model, selecting the commands the driver sends.
+    pub variant: Variant,
+    /// Wait after triggering a measurement before the first busy poll, in milliseconds.
+    pub initial_wait_ms: u16,
+    /// How the busy bit is polled while waiting for a measurement.
+    pub poll_strategy: PollStrategy,
+    /// Interval between busy polls while waiting for a measurement, in microseconds.
+    pub measurement_poll_interval_us: u32,
+    /// Busy polls a measurement may take before giving up.
+    pub measurement_max_polls: u32,
+    /// Waits between busy polls in [`read`](crate::Aht20::read) and
+    /// [`calibrate`](crate::Aht20::calibrate), in milliseconds.
+    ///
+    /// The entries are used in turn, the last one repeating until the poll limit. Empty by
+    /// default, polling at the fixed measurement or calibration poll interval instead, which
+    /// follows the datasheet's timing.
+    pub poll_schedule: &'static [u16],
+    /// Wait before fetching a measurement with
+    /// [`read_fixed_delay`](crate::Aht20::read_fixed_delay), in milliseconds.
+    pub fixed_delay_ms: u16,
+    /// Busy polls a calibration may take before giving up.
+    pub calibration_max_polls: u32,
+    /// Interval between busy polls while calibrating, in milliseconds.
+    pub calibration_poll_interval_ms: u16,
+    /// Wait before the first transaction of an initialization, in milliseconds.
+    ///
+    /// Set it to the datasheet's power-on time, [`consts::POWER_ON_MS`], when the sensor is
+    /// powered up together with the MCU, so it accepts commands by the time it is initialized.
+    /// Defaults to 0.
+    pub power_on_delay_ms: u16,
+    /// Whether to soft reset the sensor during initialization.
+    pub perform_soft_reset: bool,
+    /// Whether to calibrate the sensor during initialization.
+    pub perform_calibration: bool,
+    /// Whether initialization resets the sensor's registers, as the datasheet asks when the
+    /// status does not read 0x18 after power-on.
+    ///
+    /// Enabled by default, but the reset reads the registers back with a plain read, so it is
+    /// skipped on buses without one, see [`Bus::PLAIN_READ`](crate::hal::Bus::PLAIN_READ). That
+    /// includes `embedded-hal` 0.2 buses used directly, which the driver only knows as `Write` and
+    /// `WriteRead`: wrap them in [`Split`](crate::hal::Split) to get the reset. It is also only
+    /// checked when calibrating, and never for the AHT10 nor the AHT30. Sensors already reading
+    /// 0x18 are not affected.
+    pub perform_register_reset: bool,
+    /// Times a failed bus transaction is retried before the error is returned.
+    ///
+    /// Only errors the bus deems retryable are retried, see
+    /// [`Bus::is_retryable`](crate::hal::Bus::is_retryable).
+    pub bus_retries: u8,
+    /// Wait before retrying a failed bus transaction, in microseconds.
+    ///
+    /// Methods taking no delay, like [`try_read`](crate::Aht20::try_read), retry at once.
+    pub bus_retry_delay_us: u32,
+    /// How the CRC byte closing each measurement frame is read and checked.
+    ///
+    /// Defaults to [`CrcMode::Required`]; [`Aht20Builder::variant`](crate::Aht20Builder::variant)
+    /// picks [`CrcMode::Optional`] for models that may leave the byte out.
+    pub crc: CrcMode,
+    /// Times a measurement failing its checksum is triggered again before the error is returned.
+    pub checksum_retries: u8,
+    /// Whether [`read`](crate::Aht20::read) soft resets and recalibrates a sensor stuck busy, then
+    /// measures once more.
+    pub recover_stuck_busy: bool,
+    /// Whether [`read`](crate::Aht20::read) soft resets and recalibrates a sensor that reports
+    /// itself as uncalibrated, e.g. after a brown-out, then measures once more.
+    pub recover_uncalibrated: bool,
+    /// Whether [`recover`](crate::Aht20::recover) takes and discards a measurement once the
+    /// sensor is initialized again.
+    pub flush_on_recover: bool,
+    /// Whether [`read`](crate::Aht20::read) rejects readings outside the sensor's specified output
+    /// range with [`Error::OutOfRange`](crate::Error::OutOfRange).
+    pub check_range: bool,
+    /// Time the power pin is held low when power cycling the sensor, in milliseconds.
+    pub power_off_ms: u16,
+    /// Minimum time between the starts of two measurements, in milliseconds, or 0 for none.
+    ///
+    /// Only enforced once the driver has a clock, see [`Aht20::set_clock`](crate::Aht20::set_clock).
+    pub min_interval_ms: u32,
+    /// What to do when a measurement is started before the minimum interval has elapsed.
+    pub min_interval_policy: MinIntervalPolicy,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            address: consts::ADDRESS,
+            variant: Variant::Aht20,
+            initial_wait_ms: consts::MEASUREMENT_MS,
+            poll_strategy: PollStrategy::Auto,
+            measurement_poll_interval_us: consts::POLL_INTERVAL_MS as u32 * 1000,
+            measurement_max_polls: MEASUREMENT_BUDGET_US / (consts::POLL_INTERVAL_MS as u32 * 1000),
+            poll_schedule: &[],
+            fixed_delay_ms: consts::MEASUREMENT_MS,
+            calibration_max_polls: 10,
+            calibration_poll_interval_ms: consts::CALIBRATION_POLL_MS,
+            power_on_delay_ms: 0,
+            perform_soft_reset: true,
+            perform_calibration: true,
+            perform_register_reset: true,
+            bus_retries: 0,
+            bus_retry_delay_us: 0,
+            crc: CrcMode::Required,
+            checksum_retries: 0,
+            recover_stuck_busy: false,
+            recover_uncalibrated: false,
+            flush_on_recover: false,
+            check_range: false,
+            power_off_ms: 100,
+            min_interval_ms: 0,
+            min_interval_policy: MinIntervalPolicy::Block,
+        }
+    }
+}
+
+impl Config {
+    /// Checks that the configuration allows at least one busy poll of a measurement and of a
+    /// calibration.
+    pub(crate) fn validate(&self) -> Result<(), ConfigError> {
+        if self.measurement_max_polls == 0 || self.calibration_max_polls == 0 {
+            return Err(ConfigError::ZeroRetries);
+        }
+        Ok(())
+    }
+
+    /// Whether initialization resets the registers of a sensor reporting `status` after
+    /// power-on, as enabled and not reading 0x18.
+    pub(crate) fn resets_registers(&self, status: StatusFlags) -> bool {
+        self.perform_register_reset
+            && self.variant.resets_registers()
+            && !status.contains(StatusFlags::CRC | StatusFlags::CALIBRATION_ENABLE)
+    }
+
+    /// Busy polls of a measurement that fit in `budget_us`, following the poll schedule.
+    pub(crate) fn polls_within(&self, budget_us: u32) -> u32 {
+        let (head, last_us) = match self.poll_schedule.split_last() {
+            Some((last, head)) => (head, *last as u32 * 1000),
+            None => (&[][..], self.measurement_poll_interval_us),
+        };
+
+        let mut left_us = budget_us;
+        for (polls, &ms) in head.iter().enumerate() {
+            match left_us.checked_sub(ms as u32 * 1000) {
+                Some(us) => left_us = us,
+                None => return polls as u32,
+            }
+        }
+        head.len() as u32 + left_us / last_us.max(1)
+    }
+
+    /// Wait before busy poll number `poll`, following the poll schedule or else `interval_us`, in
+    /// microseconds.
+    pub(crate) fn poll_wait_us(&self, poll: u32, interval_us: u32) -> u32 {
+        match self.poll_schedule {
+            [] => interval_us,
+            schedule => schedule[(poll as usize).min(schedule.len() - 1)] as u32 * 1000,
+        }
+    }
+
+    /// Bytes read for a measurement frame, leaving out the CRC byte when it is disabled.
+    pub(crate) fn frame_len(&self) -> usize {
+        match self.crc {
+            CrcMode::Disabled => frame::FRAME_LEN - 1,
+            _ => frame::FRAME_LEN,
+        }
+    }
+}
+
+/// Sensor model the driver talks to.
+///
+/// The models share the measurement frame and its conversions, but differ in some commands and
+/// in how they are initialized.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Variant {
+    /// The AHT20.
+    #[default]
+    Aht20,
+    /// The older AHT10, initialized with `0xE1` and queried for its status with `0x00` where the
+    /// AHT20 takes `0xBE` and `0x71`.
+    ///
+    /// It has no registers to reset at power-on, and may leave out the CRC byte. It is the only
+    /// model with a FIFO, see [`Aht20::enable_fifo`](crate::Aht20::enable_fifo).
+    Aht10,
+    /// The AHT21, driven exactly like the AHT20.
+    Aht21,
+    /// The AHT25, which has no initialization command and NACKs it.
+    ///
+    /// Initialization only resets its registers when needed.
+    Aht25,
+    /// The AHT30, which has no initialization command and may leave out the CRC byte.
+    Aht30,
+    /// The DHT20, an AHT20 in a larger package.
+    ///
+    /// Its datasheet asks to skip the initialization command when the sensor already reports
+    /// itself as calibrated, and for a 10ms pause between the status check and the first
+    /// measurement, which initialization waits out.
+    Dht20,
+}
+
+impl Variant {
+    /// Whether the vendor's power-on sequence resets registers of this model.
+    pub(crate) fn resets_registers(self) -> bool {
+        matches!(
+            self,
+            Variant::Aht20 | Variant::Aht21 | Variant::Aht25 | Variant::Dht20
+        )
+    }
+
+    /// Whether the model buffers measurements in a FIFO.
+    pub(crate) fn has_fifo(self) -> bool {
+        self == Variant::Aht10
+    }
+
+    /// Wait after initialization, before the first measurement, in milliseconds.
+    pub(crate) fn trigger_settle_ms(self) -> u16 {
+        match self {
+            Variant::Dht20 => consts::DHT20_SETTLE_MS,
+            _ => 0,
+        }
+    }
+
+    /// Whether the model takes the initialization command, calibrating it.
+    pub(crate) fn has_init_command(self) -> bool {
+        !matches!(self, Variant::Aht25 | Variant::Aht30)
+    }
+
+    /// CRC mode suiting the model, [`CrcMode::Optional`] if it may leave out the CRC byte.
+    pub(crate) fn default_crc(self) -> CrcMode {
+        match self {
+            Variant::Aht10 | Variant::Aht30 => CrcMode::Optional,
+            _ => CrcMode::Required,
+        }
+    }
+}
+
+/// How the driver reads and checks the CRC byte closing a measurement frame.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum CrcMode {
+    /// Reads the CRC byte, failing with [`Error::Checksum`](crate::Error::Checksum) on a
+    /// mismatch.
+    #[default]
+    Required,
+    /// Reads the CRC byte and checks it, unless it reads `0xFF`, as it does from parts appending
+    /// no CRC.
+    Optional,
+    /// Reads only the status and data bytes, without checking anything.
+    Disabled,
+    /// Reads the CRC byte, checking it like [`Required`](Self::Required) when the frame's status
+    /// byte has its CRC flag set and skipping the check otherwise.
+    FromStatus,
+}
+
+impl CrcMode {
+    /// Resolves [`FromStatus`](Self::FromStatus) against a status byte, to
+    /// [`Required`](Self::Required) if its CRC flag is set and [`Disabled`](Self::Disabled)
+    /// otherwise. Other modes are returned as is.
+    pub fn for_status(self, status: Status) -> Self {
+        match self {
+            CrcMode::FromStatus if status.has_crc() => CrcMode::Required,
+            CrcMode::FromStatus => CrcMode::Disabled,
+            mode => mode,
+        }
+    }
+}
+
+/// How the driver polls the busy bit while waiting for a measurement.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum PollStrategy {
+    /// [`InFrame`](Self::InFrame) on buses with [`REPEATED_START`](crate::hal::Bus::REPEATED_START),
+    /// [`Status`](Self::Status) otherwise.
+    #[default]
+    Auto,
+    /// Reads the status byte on each poll, then fetches the frame once the sensor is ready.
+    Status,
+    /// Fetches the whole frame on each poll, checking the busy bit of its status byte.
+    ///
+    /// Saves the final fetch and the separate status transactions.
+    InFrame,
+}
+
+/// What the driver does when a measurement is started too soon after the previous one.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum MinIntervalPolicy {
+    /// Waits out the remainder of the interval with the provided delay.
+    ///
+    /// Calls without a delay, such as [`try_read`](crate::Aht20::try_read), return `WouldBlock`
+    /// or [`Error::TooSoon`](crate::Error::TooSoon) instead.
+    #[default]
+    Block,
+    /// Fails with [`Error::TooSoon`](crate::Error::TooSoon).
+    Reject,
+}
+
+/// Invalid driver configuration.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "ufmt", derive(ufmt::derive::uDebug))]
+pub enum ConfigError {
+    /// The measurement timeout is shorter than the first poll interval.
+    TimeoutTooShort,
+    /// The measurement timeout does not fit in 32 bits of microseconds.
+    TimeoutTooLong,
+    /// A measurement or calibration is allowed no busy polls at all.
+    ZeroRetries,
+}
+
+impl core::fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(match self {
+            ConfigError::TimeoutTooShort => "timeout shorter than the first poll interval",
+            ConfigError::TimeoutTooLong => "timeout too long",
+            ConfigError::ZeroRetries => "no busy polls allowed",
+        })
+    }
+}
+
+impl core::error::Error for ConfigError {}
@@ -0,0 +1,126 @@
+//! I2C address, commands, datasheet timings and reading conversions of the sensor.
+
+/// Default I2C address of the sensor.
+pub const ADDRESS: u8 = 0x38;
+
+/// Command reading the status byte, followed by the measurement data once a measurement is done.
+///
+/// This is the datasheet's status query; some clones don't answer other bytes correctly.
+pub const READ_STATUS: [u8; 1] = [0x71];
+
+/// Initialization command, calibrating the sensor.
+///
+/// This is the AHT20's command; the older AHT10 takes `0xE1` instead of `0xBE`, which some AHT20
+/// batches ignore.
+pub const INIT: [u8; 3] = [0xBE, 0x08, 0x00];
+
+/// Command reading the status byte of the AHT10, which does not answer [`READ_STATUS`].
+pub const AHT10_READ_STATUS: [u8; 1] = [0x00];
+
+/// Initialization command of the AHT10.
+pub const AHT10_INIT: [u8; 3] = [0xE1, 0x08, 0x00];
+
+/// Bit or-ed into the second byte of [`AHT10_INIT`] to enable the FIFO, mirroring its status flag.
+pub const INIT_FIFO_ENABLE: u8 = 0x04;
+
+/// Command triggering a measurement.
+pub const TRIGGER_MEASUREMENT: [u8; 3] = [0xAC, 0x33, 0x00];
+
+/// Soft reset command.
+pub const SOFT_RESET: [u8; 1] = [0xBA];
+
+/// Registers reset at power-on unless the status reads 0x18, by the vendor's reference code.
+pub const RESET_REGISTERS: [u8; 3] = [0x1B, 0x1C, 0x1E];
+
+/// Bits or-ed into a register address to write the register back.
+pub const REGISTER_WRITE: u8 = 0xB0;
+
+/// Wait after selecting a register before reading it back, in milliseconds.
+pub const REGISTER_READ_MS: u16 = 5;
+
+/// Wait after reading a register back before writing it, in milliseconds.
+pub const REGISTER_WRITE_MS: u16 = 10;
+
+/// Time the sensor takes to come back after a soft reset, in milliseconds.
+pub const SOFT_RESET_MS: u16 = 20;
+
+/// Wait the DHT20 needs between the status check and the first measurement, in milliseconds.
+pub const DHT20_SETTLE_MS: u16 = 10;
+
+/// Time the sensor takes to start up once powered, in milliseconds.
+pub const POWER_ON_MS: u16 = 100;
+
+/// Typical time a measurement takes, in milliseconds.
+pub const MEASUREMENT_MS: u16 = 80;
+
+/// Interval between busy polls while calibrating, in milliseconds.
+pub const CALIBRATION_POLL_MS: u16 = 10;
+
+/// Default interval between busy polls while measuring, in milliseconds.
+pub const POLL_INTERVAL_MS: u16 = 10;
+
+/// Full scale of the 20-bit raw readings, the denominator of both conversions.
+pub const RAW_SCALE: u32 = 1 << 20;
+
+/// Temperature range covered by the raw reading, in degrees Celsius.
+///
+/// `celsius = raw * TEMPERATURE_SPAN_C / RAW_SCALE + TEMPERATURE_OFFSET_C`.
+pub const TEMPERATURE_SPAN_C: u32 = 200;
+
+/// Temperature at a raw reading of zero, in degrees Celsius.
+pub const TEMPERATURE_OFFSET_C: i32 = -50;
+
+/// Relative humidity range covered by the raw reading, in percent.
+///
+/// `rh = raw * HUMIDITY_SPAN_RH / RAW_SCALE`.
+pub const HUMIDITY_SPAN_RH: u32 = 100;
+
+/// Lowest temperature the sensor is specified for, in thousandths of a degree Celsius.
+pub const TEMPERATURE_MIN_MILLI_C: i32 = -40_000;
+
+/// Highest temperature the sensor is specified for, in thousandths of a degree Celsius.
+pub const TEMPERATURE_MAX_MILLI_C: i32 = 85_000;
+
+/// Margin beyond the specified temperature range still accepted by range checks, in thousandths
+/// of a degree Celsius.
+pub const TEMPERATURE_GUARD_MILLI_C: i32 = 5_000;
+
+// The integer conversions below rely on these reductions to stay within 32 bits
+const _: () = {
+    assert!(TEMPERATURE_SPAN_C * 1000 == 3125 << 6 && RAW_SCALE == 1 << (14 + 6));
+    assert!(HUMIDITY_SPAN_RH * 100 == 625 << 4 && RAW_SCALE == 1 << (16 + 4));
+    assert!(HUMIDITY_SPAN_RH * 1000 == 3125 << 5 && RAW_SCALE == 1 << (15 + 5));
+};
+
+/// Converts a raw temperature reading to thousandths of a degree Celsius, rounded to nearest.
+///
+/// Exact integer arithmetic within 32 bits, also usable in constants.
+pub const fn raw_to_celsius_milli(raw: u32) -> i32 {
+    ((raw * 3125 + (1 << 13)) >> 14) as i32 + TEMPERATURE_OFFSET_C * 1000
+}
+
+/// Converts a raw humidity reading to hundredths of a percent, rounded to nearest.
+///
+/// Exact integer arithmetic within 32 bits, also usable in constants.
+pub const fn raw_to_rh_centi(raw: u32) -> u16 {
+    ((raw * 625 + (1 << 15)) >> 16) as u16
+}
+
+/// Converts a raw humidity reading to thousandths of a percent, rounded to nearest.
+///
+/// Exact integer arithmetic within 32 bits, also usable in constants.
+pub const fn raw_to_rh_milli(raw: u32) -> u32 {
+    (raw * 3125 + (1 << 14)) >> 15
+}
+
+const _: () = {
+    assert!(raw_to_celsius_milli(0) == -50_000);
+    assert!(raw_to_celsius_milli(RAW_SCALE / 4) == 0);
+    assert!(raw_to_celsius_milli(RAW_SCALE / 2) == 50_000);
+    assert!(raw_to_celsius_milli(RAW_SCALE - 1) == 150_000);
+    assert!(raw_to_rh_centi(0) == 0);
+    assert!(raw_to_rh_centi(RAW_SCALE / 2) == 5_000);
+    assert!(raw_to_rh_centi(RAW_SCALE - 1) == 10_000);
+    assert!(raw_to_rh_milli(RAW_SCALE / 2) == 50_000);
+    assert!(raw_to_rh_milli(RAW_SCALE - 1) == 100_000);
+};
@@ -0,0 +1,78 @@
+//! Differences between readings.
+
+use crate::{Humidity, Temperature};
+
+/// Difference between two temperatures, as returned by subtracting them.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct TemperatureDelta {
+    millikelvin: i32,
+}
+
+impl TemperatureDelta {
+    /// Difference in thousandths of a Kelvin, or equally of a degree Celsius.
+    pub fn millikelvin(&self) -> i32 {
+        self.millikelvin
+    }
+
+    /// Difference in Kelvin, or equally in degrees Celsius.
+    pub fn kelvin(&self) -> f32 {
+        self.millikelvin as f32 / 1000.0
+    }
+
+    /// Difference in degrees Fahrenheit.
+    pub fn fahrenheit(&self) -> f32 {
+        self.kelvin() * 1.8
+    }
+}
+
+/// Difference between two humidities, as returned by subtracting them.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct HumidityDelta {
+    centi_rh: i32,
+}
+
+impl HumidityDelta {
+    /// Difference in hundredths of a percent Relative Humidity.
+    pub fn rh_centi(&self) -> i32 {
+        self.centi_rh
+    }
+
+    /// Difference in percent Relative Humidity.
+    pub fn rh(&self) -> f32 {
+        self.centi_rh as f32 / 100.0
+    }
+}
+
+/// Scales the difference of two raw readings by `num / 2^shift`, rounding halves away from zero.
+fn scale_diff(a: u32, b: u32, num: i64, shift: u32) -> i32 {
+    let scaled = (a as i64 - b as i64) * num;
+    let half = 1 << (shift - 1);
+    let rounded = (scaled.abs() + half) >> shift;
+    (rounded * scaled.signum()) as i32
+}
+
+/// Computed from the raw readings, so the difference is rounded only once.
+impl core::ops::Sub for Temperature {
+    type Output = TemperatureDelta;
+
+    fn sub(self, rhs: Self) -> TemperatureDelta {
+        // 200K over 2^20 codes, in millikelvin: 200_000 / 2^20 = 3125 / 2^14
+        TemperatureDelta {
+            millikelvin: scale_diff(self.t, rhs.t, 3125, 14),
+        }
+    }
+}
+
+/// Computed from the raw readings, so the difference is rounded only once.
+impl core::ops::Sub for Humidity {
+    type Output = HumidityDelta;
+
+    fn sub(self, rhs: Self) -> HumidityDelta {
+        // 100% over 2^20 codes, in hundredths: 10_000 / 2^20 = 625 / 2^16
+        HumidityDelta {
+            centi_rh: scale_diff(self.h, rhs.h, 625, 16),
+        }
+    }
+}
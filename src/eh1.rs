@@ -0,0 +1,131 @@
+//! `embedded-hal` 1.0 support.
+//!
+//! Wrap an `embedded-hal` 1.0 I2C bus, delay or power pin in [`Eh1`] to use them with the driver:
+//!
+//! ```ignore
+//! let mut dev = Aht20::new(Eh1(i2c), &mut Eh1(&mut delay))?;
+//! let (h, t) = dev.read(&mut Eh1(&mut delay))?;
+//! ```
+
+use {
+    crate::{
+        hal::{Bus, BusPin, Delay, PowerPin},
+        Error,
+    },
+    embedded_hal_1::{
+        delay::DelayNs,
+        digital::{InputPin, OutputPin},
+        i2c::{Error as _, ErrorKind, I2c, NoAcknowledgeSource, Operation, SevenBitAddress},
+    },
+};
+
+/// Adapter for `embedded-hal` 1.0 buses, delays and pins.
+pub struct Eh1<T>(pub T);
+
+impl<T> Eh1<T> {
+    /// Returns the wrapped peripheral.
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<I2C> Bus for Eh1<I2C>
+where
+    I2C: I2c<SevenBitAddress>,
+{
+    type Error = I2C::Error;
+
+    const REPEATED_START: bool = true;
+    const PLAIN_READ: bool = true;
+
+    fn write(&mut self, address: u8, bytes: &[u8]) -> Result<(), Self::Error> {
+        self.0.write(address, bytes)
+    }
+
+    fn write_read(&mut self, address: u8, bytes: &[u8], buffer: &mut [u8]) -> Result<(), Self::Error> {
+        self.0.transaction(
+            address,
+            &mut [Operation::Write(bytes), Operation::Read(buffer)],
+        )
+    }
+
+    fn read(&mut self, address: u8, buffer: &mut [u8]) -> Result<(), Self::Error> {
+        self.0.read(address, buffer)
+    }
+
+    fn is_address_nack(&self, error: &Self::Error) -> bool {
+        matches!(
+            error.kind(),
+            ErrorKind::NoAcknowledge(NoAcknowledgeSource::Address | NoAcknowledgeSource::Unknown)
+        )
+    }
+
+    /// Retries arbitration losses and bus errors, which another master or noise on the lines
+    /// cause, but not NACKs, overruns or HAL-specific errors.
+    fn is_retryable(&self, error: &Self::Error) -> bool {
+        matches!(error.kind(), ErrorKind::ArbitrationLoss | ErrorKind::Bus)
+    }
+}
+
+impl<E: embedded_hal_1::i2c::Error> Error<E> {
+    /// Returns the `embedded-hal` kind of the bus error, if this is one.
+    ///
+    /// Lets the application tell e.g. a missing device from a noisy bus without knowing the HAL's
+    /// error type. [`Error::DeviceNotFound`] reports an address NACK.
+    pub fn bus_kind(&self) -> Option<ErrorKind> {
+        match self {
+            Error::Bus(e) => Some(e.kind()),
+            Error::DeviceNotFound => Some(ErrorKind::NoAcknowledge(NoAcknowledgeSource::Address)),
+            _ => None,
+        }
+    }
+}
+
+impl<D> Delay for Eh1<D>
+where
+    D: DelayNs,
+{
+    const MICROS: bool = true;
+
+    fn delay_ms(&mut self, ms: u16) {
+        self.0.delay_ms(ms as u32)
+    }
+
+    fn delay_us(&mut self, us: u32) {
+        self.0.delay_us(us)
+    }
+}
+
+impl<P> PowerPin for Eh1<P>
+where
+    P: OutputPin,
+{
+    type Error = P::Error;
+
+    fn set_low(&mut self) -> Result<(), Self::Error> {
+        self.0.set_low()
+    }
+
+    fn set_high(&mut self) -> Result<(), Self::Error> {
+        self.0.set_high()
+    }
+}
+
+impl<P> BusPin for Eh1<P>
+where
+    P: OutputPin + InputPin,
+{
+    type Error = P::Error;
+
+    fn set_low(&mut self) -> Result<(), Self::Error> {
+        OutputPin::set_low(&mut self.0)
+    }
+
+    fn set_high(&mut self) -> Result<(), Self::Error> {
+        OutputPin::set_high(&mut self.0)
+    }
+
+    fn is_high(&mut self) -> Result<bool, Self::Error> {
+        InputPin::is_high(&mut self.0)
+    }
+}
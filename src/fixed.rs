@@ -0,0 +1,163 @@
+//! Fixed-point formatting of readings, without float formatting.
+
+use core::fmt;
+
+/// Most decimals a reading is formatted with, the resolution of the integer conversions.
+pub(crate) const MAX_DECIMALS: u8 = 3;
+
+/// Thousandths of a unit, rounded to a number of decimals and split for printing.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub(crate) struct Fixed {
+    pub(crate) negative: bool,
+    pub(crate) int: u32,
+    pub(crate) frac: u32,
+    pub(crate) decimals: u8,
+}
+
+impl Fixed {
+    /// Rounds `milli` to `decimals` places, at most [`MAX_DECIMALS`], halves away from zero.
+    pub(crate) fn from_milli(milli: i32, decimals: u8) -> Self {
+        let decimals = decimals.min(MAX_DECIMALS);
+        let step = 10u32.pow((MAX_DECIMALS - decimals) as u32);
+        let rounded = (milli.unsigned_abs() + step / 2) / step;
+        let scale = 10u32.pow(decimals as u32);
+
+        Self {
+            // Values rounding to zero print without a sign
+            negative: milli < 0 && rounded != 0,
+            int: rounded / scale,
+            frac: rounded % scale,
+            decimals,
+        }
+    }
+
+    /// Writes the value to `w`, e.g. `-3.05`.
+    pub(crate) fn write_to(&self, w: &mut impl fmt::Write) -> fmt::Result {
+        if self.negative {
+            w.write_char('-')?;
+        }
+        write!(w, "{}", self.int)?;
+        if self.decimals > 0 {
+            write!(w, ".{:01$}", self.frac, self.decimals as usize)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "ufmt")]
+impl Fixed {
+    /// Writes the value followed by `unit` to a `ufmt` writer, e.g. `-3.05 °C`.
+    fn write_ufmt<W: ufmt::uWrite + ?Sized>(
+        &self,
+        f: &mut ufmt::Formatter<'_, W>,
+        unit: &str,
+    ) -> Result<(), W::Error> {
+        if self.negative {
+            f.write_char('-')?;
+        }
+        ufmt::uwrite!(f, "{}", self.int)?;
+        if self.decimals > 0 {
+            // Zero-pad the fraction by hand, ufmt has no width support
+            f.write_char('.')?;
+            let mut scale = 10u32.pow(self.decimals as u32 - 1);
+            while scale > self.frac.max(1) {
+                f.write_char('0')?;
+                scale /= 10;
+            }
+            ufmt::uwrite!(f, "{}", self.frac)?;
+        }
+        f.write_char(' ')?;
+        f.write_str(unit)
+    }
+}
+
+/// Formats as e.g. `45.2 %`, like [`Display`](core::fmt::Display).
+#[cfg(feature = "ufmt")]
+impl ufmt::uDisplay for crate::Humidity {
+    fn fmt<W: ufmt::uWrite + ?Sized>(
+        &self,
+        f: &mut ufmt::Formatter<'_, W>,
+    ) -> Result<(), W::Error> {
+        Fixed::from_milli(self.rh_milli() as i32, 1).write_ufmt(f, "%")
+    }
+}
+
+/// Formats as e.g. `23.4 °C`, like [`Display`](core::fmt::Display).
+#[cfg(feature = "ufmt")]
+impl ufmt::uDisplay for crate::Temperature {
+    fn fmt<W: ufmt::uWrite + ?Sized>(
+        &self,
+        f: &mut ufmt::Formatter<'_, W>,
+    ) -> Result<(), W::Error> {
+        Fixed::from_milli(self.celsius_milli(), 1).write_ufmt(f, "°C")
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl Fixed {
+    /// Writes the value followed by `unit` to a `defmt` formatter, e.g. `-3.05 °C`.
+    fn format_defmt(&self, f: defmt::Formatter<'_>, unit: &str) {
+        let sign = if self.negative { "-" } else { "" };
+        defmt::write!(f, "{=str}{=u32}", sign, self.int);
+        match self.decimals {
+            0 => {}
+            1 => defmt::write!(f, ".{=u32:01}", self.frac),
+            2 => defmt::write!(f, ".{=u32:02}", self.frac),
+            _ => defmt::write!(f, ".{=u32:03}", self.frac),
+        }
+        defmt::write!(f, " {=str}", unit);
+    }
+}
+
+/// Formats in hundredths of a percent, e.g. `45.20 %`.
+#[cfg(feature = "defmt")]
+impl defmt::Format for crate::Humidity {
+    fn format(&self, f: defmt::Formatter<'_>) {
+        Fixed::from_milli(self.rh_milli() as i32, 2).format_defmt(f, "%")
+    }
+}
+
+/// Formats in hundredths of a degree Celsius, e.g. `23.45 °C`.
+#[cfg(feature = "defmt")]
+impl defmt::Format for crate::Temperature {
+    fn format(&self, f: defmt::Formatter<'_>) {
+        Fixed::from_milli(self.celsius_milli(), 2).format_defmt(f, "°C")
+    }
+}
+
+/// Fails to compile if a public type lacks a `defmt::Format` implementation.
+#[cfg(feature = "defmt")]
+const _: () = {
+    use crate::*;
+
+    const fn format<T: defmt::Format + ?Sized>() {}
+
+    format::<Humidity>();
+    format::<Temperature>();
+    format::<Reading>();
+    format::<HumidityDelta>();
+    format::<TemperatureDelta>();
+    format::<Error<u8>>();
+    format::<ErrorKind>();
+    format::<PowerError<u8, u8>>();
+    format::<RangeError>();
+    format::<ParseError>();
+    format::<Config>();
+    format::<ConfigError>();
+    format::<PollStrategy>();
+    format::<MinIntervalPolicy>();
+    format::<Variant>();
+    format::<CrcMode>();
+    format::<State>();
+    format::<Status>();
+    format::<FifoStatus>();
+    format::<Aht20Builder>();
+    format::<BusClear>();
+    format::<BusClearError<u8>>();
+    format::<RecoveryReport>();
+    format::<RecoveryStep>();
+    format::<RecoveryError<u8>>();
+    format::<commands::Command<'static>>();
+    #[cfg(any(feature = "libm", feature = "micromath"))]
+    format::<metrics::CondensationRisk>();
+};
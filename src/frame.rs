@@ -0,0 +1,175 @@
+//! Measurement frame decoding shared by the blocking and async drivers.
+
+use {
+    crate::{CrcMode, Error, Reading, Status, StatusFlags},
+    core::convert::TryInto,
+};
+
+/// Length of a measurement frame: status, 5 data bytes and CRC.
+pub(crate) const FRAME_LEN: usize = 7;
+
+/// Computes the CRC-8 of `data` (polynomial 0x31, initial value 0xFF).
+pub(crate) fn crc8(data: &[u8]) -> u8 {
+    let mut crc = 0xFFu8;
+    for byte in data {
+        crc ^= byte;
+        for _ in 0..8 {
+            crc = if crc & 0x80 != 0 { (crc << 1) ^ 0x31 } else { crc << 1 };
+        }
+    }
+    crc
+}
+
+/// Measurement frame parsing error, see [`Reading`]'s `TryFrom` implementations.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ParseError {
+    /// The frame is not exactly 7 bytes long.
+    InvalidLength,
+    /// The busy bit is set, so the data is stale.
+    Busy,
+    /// Checksum mismatch.
+    Checksum {
+        /// CRC byte in the frame.
+        expected: u8,
+        /// CRC computed over the status and data bytes.
+        computed: u8,
+    },
+    /// The calibration bit is clear.
+    Uncalibrated,
+    /// The data bytes are all zeros or all ones, as if the conversion was skipped.
+    ///
+    /// Either would read 0% or 100% relative humidity at -50°C or 150°C, outside the sensor's
+    /// range, so no actual measurement produces them.
+    InvalidData,
+}
+
+/// Checks and decodes a measurement frame: status, 5 data bytes and CRC.
+impl core::convert::TryFrom<[u8; FRAME_LEN]> for Reading {
+    type Error = ParseError;
+
+    fn try_from(buf: [u8; FRAME_LEN]) -> Result<Self, ParseError> {
+        decode(buf, CrcMode::Required)
+    }
+}
+
+/// Checks and decodes a measurement frame, checking its CRC byte as `crc` asks.
+fn decode(buf: [u8; FRAME_LEN], crc: CrcMode) -> Result<Reading, ParseError> {
+    // Reject stale data from a measurement still in progress
+    let status = StatusFlags { bits: buf[0] };
+    if status.contains(StatusFlags::BUSY) {
+        return Err(ParseError::Busy);
+    }
+
+    // Check for CRC mismatch, unless the sensor sent no CRC
+    let computed = crc8(&buf[..=5]);
+    let crc_verified = match crc.for_status(Status::from_bits(buf[0])) {
+        CrcMode::Disabled => false,
+        _ if computed == buf[6] => true,
+        CrcMode::Optional if buf[6] == 0xFF => false,
+        _ => {
+            return Err(ParseError::Checksum {
+                expected: buf[6],
+                computed,
+            })
+        }
+    };
+
+    // Check calibration
+    if !status.contains(StatusFlags::CALIBRATION_ENABLE) {
+        return Err(ParseError::Uncalibrated);
+    }
+
+    // Reject data with no measurement in it
+    let data = &buf[1..=5];
+    if data.iter().all(|&b| b == 0x00) || data.iter().all(|&b| b == 0xFF) {
+        return Err(ParseError::InvalidData);
+    }
+
+    // Extract humitidy and temperature values from data
+    Ok(Reading {
+        crc_verified,
+        ..Reading::unpack([buf[1], buf[2], buf[3], buf[4], buf[5]])
+    })
+}
+
+/// Checks and decodes a measurement frame, which must be exactly 7 bytes long.
+impl core::convert::TryFrom<&[u8]> for Reading {
+    type Error = ParseError;
+
+    fn try_from(buf: &[u8]) -> Result<Self, ParseError> {
+        let buf: [u8; FRAME_LEN] = buf.try_into().map_err(|_| ParseError::InvalidLength)?;
+        Reading::try_from(buf)
+    }
+}
+
+/// Checks and decodes a measurement frame, see [`decode`].
+pub(crate) fn parse<E>(buf: &[u8; FRAME_LEN], crc: CrcMode) -> Result<Reading, Error<E>> {
+    match decode(*buf, crc) {
+        Ok(reading) => Ok(reading),
+        Err(ParseError::Busy) => Err(Error::Busy),
+        Err(ParseError::Uncalibrated) => Err(Error::Uncalibrated),
+        Err(ParseError::Checksum { expected, computed }) => Err(Error::Checksum {
+            expected,
+            computed,
+            frame: *buf,
+        }),
+        Err(ParseError::InvalidLength | ParseError::InvalidData) => Err(Error::InvalidData),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Frame of an idle, calibrated sensor at 50 %RH and 25 °C.
+    fn frame() -> [u8; FRAME_LEN] {
+        let mut buf = [0x1C, 0x80, 0x00, 0x06, 0x00, 0x00, 0x00];
+        buf[6] = crc8(&buf[..6]);
+        buf
+    }
+
+    #[test]
+    fn parses_frames_for_both_drivers() {
+        let reading = parse::<()>(&frame(), CrcMode::Required).unwrap();
+        assert_eq!(
+            (reading.humidity.raw(), reading.temperature.raw()),
+            (0x80000, 0x60000)
+        );
+        assert!(reading.crc_verified);
+    }
+
+    #[test]
+    fn maps_parse_errors_to_driver_errors() {
+        let mut busy = frame();
+        busy[0] |= 0x80;
+        busy[6] = crc8(&busy[..6]);
+        assert_eq!(parse::<()>(&busy, CrcMode::Required), Err(Error::Busy));
+
+        let mut uncalibrated = frame();
+        uncalibrated[0] &= !0x08;
+        uncalibrated[6] = crc8(&uncalibrated[..6]);
+        assert_eq!(
+            parse::<()>(&uncalibrated, CrcMode::Required),
+            Err(Error::Uncalibrated)
+        );
+
+        let mut corrupted = frame();
+        corrupted[2] ^= 0x01;
+        assert_eq!(
+            parse::<()>(&corrupted, CrcMode::Required),
+            Err(Error::Checksum {
+                expected: frame()[6],
+                computed: crc8(&corrupted[..6]),
+                frame: corrupted,
+            })
+        );
+
+        let mut empty = [0x1C, 0, 0, 0, 0, 0, 0];
+        empty[6] = crc8(&empty[..6]);
+        assert_eq!(
+            parse::<()>(&empty, CrcMode::Required),
+            Err(Error::InvalidData)
+        );
+    }
+}
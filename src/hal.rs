@@ -0,0 +1,495 @@
+//! Bus, delay and pin abstractions used by the driver.
+//!
+//! The driver is written once against [`Bus`], [`Delay`], [`PowerPin`] and [`BusPin`].
+//! Implementations are provided for the `embedded-hal` 0.2 traits (`eh02` feature) and, through
+//! `Eh1`, for the `embedded-hal` 1.0 traits (`eh1` feature).
+
+#[cfg(feature = "eh02")]
+use embedded_hal::{
+    blocking::{
+        delay::{DelayMs, DelayUs},
+        i2c::{Read, Write, WriteRead},
+    },
+    digital::v2::{InputPin, OutputPin},
+};
+
+/// Blocking I2C bus used by the driver.
+pub trait Bus {
+    /// Underlying bus error.
+    type Error;
+
+    /// Whether [`write_read`](Self::write_read) is a single transaction with a repeated start.
+    ///
+    /// When set, the driver polls the busy bit of the measurement frame itself instead of the
+    /// status register, so no other bus master can get in between the status check and the data.
+    const REPEATED_START: bool = false;
+
+    /// Writes `bytes` to the device at `address`.
+    fn write(&mut self, address: u8, bytes: &[u8]) -> Result<(), Self::Error>;
+
+    /// Writes `bytes` to the device at `address`, then reads enough bytes to fill `buffer`.
+    fn write_read(&mut self, address: u8, bytes: &[u8], buffer: &mut [u8]) -> Result<(), Self::Error>;
+
+    /// Whether [`read`](Self::read) is a plain read transaction.
+    ///
+    /// Unset for buses relying on the default [`read`](Self::read), an empty
+    /// [`write_read`](Self::write_read) that some HALs reject. Initialization then skips the
+    /// register reset, which reads the registers back; wrap `embedded-hal` 0.2 buses
+    /// implementing `Read` in [`Split`] to have it.
+    const PLAIN_READ: bool = false;
+
+    /// Reads enough bytes from the device at `address` to fill `buffer`.
+    ///
+    /// Defaults to a [`write_read`](Self::write_read) writing no bytes.
+    fn read(&mut self, address: u8, buffer: &mut [u8]) -> Result<(), Self::Error> {
+        self.write_read(address, &[], buffer)
+    }
+
+    /// Whether `error` means that no device acknowledged its address.
+    ///
+    /// Buses with opaque errors, like `embedded-hal` 0.2 ones, never classify errors as such
+    /// unless wrapped in [`NackAware`].
+    fn is_address_nack(&self, _error: &Self::Error) -> bool {
+        false
+    }
+
+    /// Whether a transaction failing with `error` is worth retrying, see
+    /// [`Config::bus_retries`](crate::Config::bus_retries).
+    ///
+    /// Defaults to every error but address NACKs, as a missing device won't show up on retry.
+    /// Wrap the bus in [`RetryIf`] to decide otherwise.
+    fn is_retryable(&self, error: &Self::Error) -> bool {
+        !self.is_address_nack(error)
+    }
+
+    /// Prepares the bus for a driver operation, before its first transaction.
+    ///
+    /// The driver calls it once per operation: each command sent, each status or frame read,
+    /// and the register reset of an initialization as a whole. Bus retries and the transactions
+    /// an operation is made of don't call it again. Does nothing by default; [`Hooked`] runs its
+    /// hook here.
+    fn begin(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+/// Blocking delay used by the driver.
+pub trait Delay {
+    /// Pauses execution for `ms` milliseconds.
+    fn delay_ms(&mut self, ms: u16);
+
+    /// Whether [`delay_us`](Self::delay_us) waits with microsecond resolution.
+    ///
+    /// Unset for delays relying on the default [`delay_us`](Self::delay_us), so the driver
+    /// accounts for the whole milliseconds they really wait.
+    const MICROS: bool = false;
+
+    /// Pauses execution for `us` microseconds.
+    ///
+    /// Delays without microsecond resolution round up to the next millisecond.
+    fn delay_us(&mut self, us: u32) {
+        let mut ms = us.div_ceil(1000);
+        while ms > 0 {
+            let chunk = ms.min(u16::MAX as u32) as u16;
+            self.delay_ms(chunk);
+            ms -= chunk as u32;
+        }
+    }
+}
+
+/// Delay returning immediately, for retrying where the caller supplied no delay.
+pub(crate) struct NoDelay;
+
+impl Delay for NoDelay {
+    const MICROS: bool = true;
+
+    fn delay_ms(&mut self, _ms: u16) {}
+
+    fn delay_us(&mut self, _us: u32) {}
+}
+
+/// Output pin switching the sensor's supply, for hard power cycling.
+pub trait PowerPin {
+    /// Underlying pin error.
+    type Error;
+
+    /// Whether a pin is connected at all, cleared for [`NoPin`].
+    const CONNECTED: bool = true;
+
+    /// Drives the pin low, powering the sensor off.
+    fn set_low(&mut self) -> Result<(), Self::Error>;
+
+    /// Drives the pin high, powering the sensor on.
+    fn set_high(&mut self) -> Result<(), Self::Error>;
+}
+
+/// Placeholder for a driver without a power pin.
+#[derive(Debug, Copy, Clone, Default)]
+pub struct NoPin;
+
+impl PowerPin for NoPin {
+    type Error = core::convert::Infallible;
+
+    const CONNECTED: bool = false;
+
+    fn set_low(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    fn set_high(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+/// Open-drain pin driving an I2C line directly, for clearing a stuck bus.
+pub trait BusPin {
+    /// Underlying pin error.
+    type Error;
+
+    /// Pulls the line low.
+    fn set_low(&mut self) -> Result<(), Self::Error>;
+
+    /// Releases the line, letting the pull-up take it high.
+    fn set_high(&mut self) -> Result<(), Self::Error>;
+
+    /// Whether the line reads high.
+    fn is_high(&mut self) -> Result<bool, Self::Error>;
+}
+
+#[cfg(feature = "eh02")]
+impl<I2C, E> Bus for I2C
+where
+    I2C: WriteRead<Error = E> + Write<Error = E>,
+{
+    type Error = E;
+
+    fn write(&mut self, address: u8, bytes: &[u8]) -> Result<(), E> {
+        Write::write(self, address, bytes)
+    }
+
+    fn write_read(&mut self, address: u8, bytes: &[u8], buffer: &mut [u8]) -> Result<(), E> {
+        WriteRead::write_read(self, address, bytes, buffer)
+    }
+}
+
+#[cfg(feature = "eh02")]
+impl<P> PowerPin for P
+where
+    P: OutputPin,
+{
+    type Error = P::Error;
+
+    fn set_low(&mut self) -> Result<(), Self::Error> {
+        OutputPin::set_low(self)
+    }
+
+    fn set_high(&mut self) -> Result<(), Self::Error> {
+        OutputPin::set_high(self)
+    }
+}
+
+#[cfg(feature = "eh02")]
+impl<P, E> BusPin for P
+where
+    P: OutputPin<Error = E> + InputPin<Error = E>,
+{
+    type Error = E;
+
+    fn set_low(&mut self) -> Result<(), E> {
+        OutputPin::set_low(self)
+    }
+
+    fn set_high(&mut self) -> Result<(), E> {
+        OutputPin::set_high(self)
+    }
+
+    fn is_high(&mut self) -> Result<bool, E> {
+        InputPin::is_high(self)
+    }
+}
+
+/// Bus running a hook before each driver operation, e.g. to select an I2C multiplexer channel.
+///
+/// The hook runs in [`Bus::begin`], so once per operation rather than per transaction, see there.
+pub struct Hooked<B, H> {
+    bus: B,
+    hook: H,
+}
+
+impl<B, H> Hooked<B, H>
+where
+    B: Bus,
+    H: FnMut(&mut B) -> Result<(), B::Error>,
+{
+    /// Wraps `bus`, calling `hook` on it before each driver operation.
+    pub fn new(bus: B, hook: H) -> Self {
+        Self { bus, hook }
+    }
+
+    /// Returns the wrapped bus and the hook.
+    pub fn into_inner(self) -> (B, H) {
+        (self.bus, self.hook)
+    }
+}
+
+impl<B, H> Bus for Hooked<B, H>
+where
+    B: Bus,
+    H: FnMut(&mut B) -> Result<(), B::Error>,
+{
+    type Error = B::Error;
+
+    const REPEATED_START: bool = B::REPEATED_START;
+    const PLAIN_READ: bool = B::PLAIN_READ;
+
+    fn write(&mut self, address: u8, bytes: &[u8]) -> Result<(), Self::Error> {
+        self.bus.write(address, bytes)
+    }
+
+    fn write_read(&mut self, address: u8, bytes: &[u8], buffer: &mut [u8]) -> Result<(), Self::Error> {
+        self.bus.write_read(address, bytes, buffer)
+    }
+
+    fn read(&mut self, address: u8, buffer: &mut [u8]) -> Result<(), Self::Error> {
+        self.bus.read(address, buffer)
+    }
+
+    fn is_address_nack(&self, error: &Self::Error) -> bool {
+        self.bus.is_address_nack(error)
+    }
+
+    fn is_retryable(&self, error: &Self::Error) -> bool {
+        self.bus.is_retryable(error)
+    }
+
+    fn begin(&mut self) -> Result<(), Self::Error> {
+        (self.hook)(&mut self.bus)?;
+        self.bus.begin()
+    }
+}
+
+/// Bus classifying address NACKs with a user-supplied predicate, for buses with opaque errors.
+///
+/// Sensors NACK their address while measuring, which the driver then treats as busy.
+pub struct NackAware<B: Bus> {
+    bus: B,
+    is_nack: fn(&B::Error) -> bool,
+}
+
+impl<B: Bus> NackAware<B> {
+    /// Wraps `bus`, classifying its errors with `is_nack`.
+    pub fn new(bus: B, is_nack: fn(&B::Error) -> bool) -> Self {
+        Self { bus, is_nack }
+    }
+
+    /// Returns the wrapped bus.
+    pub fn into_inner(self) -> B {
+        self.bus
+    }
+}
+
+impl<B: Bus> Bus for NackAware<B> {
+    type Error = B::Error;
+
+    const REPEATED_START: bool = B::REPEATED_START;
+    const PLAIN_READ: bool = B::PLAIN_READ;
+
+    fn write(&mut self, address: u8, bytes: &[u8]) -> Result<(), Self::Error> {
+        self.bus.write(address, bytes)
+    }
+
+    fn write_read(&mut self, address: u8, bytes: &[u8], buffer: &mut [u8]) -> Result<(), Self::Error> {
+        self.bus.write_read(address, bytes, buffer)
+    }
+
+    fn read(&mut self, address: u8, buffer: &mut [u8]) -> Result<(), Self::Error> {
+        self.bus.read(address, buffer)
+    }
+
+    fn is_address_nack(&self, error: &Self::Error) -> bool {
+        (self.is_nack)(error)
+    }
+
+    fn begin(&mut self) -> Result<(), Self::Error> {
+        self.bus.begin()
+    }
+}
+
+/// Bus deciding which failed transactions are retried with a user-supplied predicate.
+///
+/// Useful on noisy buses, to retry arbitration losses but give up at once on anything else.
+pub struct RetryIf<B: Bus> {
+    bus: B,
+    is_retryable: fn(&B::Error) -> bool,
+}
+
+impl<B: Bus> RetryIf<B> {
+    /// Wraps `bus`, retrying only the errors for which `is_retryable` returns true.
+    pub fn new(bus: B, is_retryable: fn(&B::Error) -> bool) -> Self {
+        Self { bus, is_retryable }
+    }
+
+    /// Returns the wrapped bus.
+    pub fn into_inner(self) -> B {
+        self.bus
+    }
+}
+
+impl<B: Bus> Bus for RetryIf<B> {
+    type Error = B::Error;
+
+    const REPEATED_START: bool = B::REPEATED_START;
+    const PLAIN_READ: bool = B::PLAIN_READ;
+
+    fn write(&mut self, address: u8, bytes: &[u8]) -> Result<(), Self::Error> {
+        self.bus.write(address, bytes)
+    }
+
+    fn write_read(&mut self, address: u8, bytes: &[u8], buffer: &mut [u8]) -> Result<(), Self::Error> {
+        self.bus.write_read(address, bytes, buffer)
+    }
+
+    fn read(&mut self, address: u8, buffer: &mut [u8]) -> Result<(), Self::Error> {
+        self.bus.read(address, buffer)
+    }
+
+    fn is_address_nack(&self, error: &Self::Error) -> bool {
+        self.bus.is_address_nack(error)
+    }
+
+    fn is_retryable(&self, error: &Self::Error) -> bool {
+        (self.is_retryable)(error)
+    }
+
+    fn begin(&mut self) -> Result<(), Self::Error> {
+        self.bus.begin()
+    }
+}
+
+/// Adapter for `embedded-hal` 0.2 buses implementing `Write` and `Read` but not `WriteRead`.
+///
+/// Combined transactions are issued as a write followed by a separate read, with a stop
+/// condition in between.
+#[cfg(feature = "eh02")]
+pub struct Split<I2C>(pub I2C);
+
+#[cfg(feature = "eh02")]
+impl<I2C, E> Bus for Split<I2C>
+where
+    I2C: Read<Error = E> + Write<Error = E>,
+{
+    type Error = E;
+
+    const PLAIN_READ: bool = true;
+
+    fn write(&mut self, address: u8, bytes: &[u8]) -> Result<(), E> {
+        self.0.write(address, bytes)
+    }
+
+    fn write_read(&mut self, address: u8, bytes: &[u8], buffer: &mut [u8]) -> Result<(), E> {
+        self.0.write(address, bytes)?;
+        self.0.read(address, buffer)
+    }
+
+    fn read(&mut self, address: u8, buffer: &mut [u8]) -> Result<(), E> {
+        self.0.read(address, buffer)
+    }
+}
+
+#[cfg(feature = "eh02")]
+impl<D> Delay for D
+where
+    D: DelayMs<u16>,
+{
+    fn delay_ms(&mut self, ms: u16) {
+        DelayMs::delay_ms(self, ms)
+    }
+}
+
+/// Adapter for `embedded-hal` 0.2 delays implementing `DelayMs<u8>` but not `DelayMs<u16>`.
+///
+/// Longer delays are issued in chunks of at most 255ms.
+#[cfg(feature = "eh02")]
+pub struct MsU8<D>(pub D);
+
+#[cfg(feature = "eh02")]
+impl<D> Delay for MsU8<D>
+where
+    D: DelayMs<u8>,
+{
+    fn delay_ms(&mut self, mut ms: u16) {
+        while ms > 0 {
+            let chunk = ms.min(u8::MAX as u16) as u8;
+            self.0.delay_ms(chunk);
+            ms -= chunk as u16;
+        }
+    }
+}
+
+/// Adapter for `embedded-hal` 0.2 delays implementing `DelayMs<u32>` but not `DelayMs<u16>`.
+#[cfg(feature = "eh02")]
+pub struct MsU32<D>(pub D);
+
+#[cfg(feature = "eh02")]
+impl<D> Delay for MsU32<D>
+where
+    D: DelayMs<u32>,
+{
+    fn delay_ms(&mut self, ms: u16) {
+        self.0.delay_ms(ms as u32)
+    }
+}
+
+/// Adapter for `embedded-hal` 0.2 delays with microsecond resolution.
+#[cfg(feature = "eh02")]
+pub struct Micros<D>(pub D);
+
+#[cfg(feature = "eh02")]
+impl<D> Delay for Micros<D>
+where
+    D: DelayUs<u32>,
+{
+    const MICROS: bool = true;
+
+    fn delay_ms(&mut self, ms: u16) {
+        self.0.delay_us(ms as u32 * 1000)
+    }
+
+    fn delay_us(&mut self, us: u32) {
+        self.0.delay_us(us)
+    }
+}
+
+/// Mutable borrow of a bus, so the peripheral stays available to other drivers.
+pub struct Borrowed<'a, B: ?Sized>(pub &'a mut B);
+
+impl<B: Bus + ?Sized> Bus for Borrowed<'_, B> {
+    type Error = B::Error;
+
+    const REPEATED_START: bool = B::REPEATED_START;
+    const PLAIN_READ: bool = B::PLAIN_READ;
+
+    fn write(&mut self, address: u8, bytes: &[u8]) -> Result<(), Self::Error> {
+        self.0.write(address, bytes)
+    }
+
+    fn write_read(&mut self, address: u8, bytes: &[u8], buffer: &mut [u8]) -> Result<(), Self::Error> {
+        self.0.write_read(address, bytes, buffer)
+    }
+
+    fn read(&mut self, address: u8, buffer: &mut [u8]) -> Result<(), Self::Error> {
+        self.0.read(address, buffer)
+    }
+
+    fn is_address_nack(&self, error: &Self::Error) -> bool {
+        self.0.is_address_nack(error)
+    }
+
+    fn is_retryable(&self, error: &Self::Error) -> bool {
+        self.0.is_retryable(error)
+    }
+
+    fn begin(&mut self) -> Result<(), Self::Error> {
+        self.0.begin()
+    }
+}
@@ -2,28 +2,109 @@
 //!
 //! This driver was built using [`embedded-hal`] traits and is a fork of Anthony Romano's [AHT10 crate].
 //!
+//! The older AHT10, the newer AHT21, AHT25 and AHT30, and the DHT20, an AHT20 in a larger
+//! package, are supported as well, see [`Variant`].
+//!
 //! [`embedded-hal`]: https://docs.rs/embedded-hal/~0.2
 //! [AHT10 crate]: https://github.com/heyitsanthony/aht10
-
-
+//!
+//! ## Features
+//!
+//! - `eh02` (default): implements the driver's [`Bus`] and [`Delay`] traits for `embedded-hal` 0.2.
+//!   Delays implementing `DelayMs<u16>` are used directly; wrap `DelayMs<u8>` or `DelayMs<u32>`
+//!   only implementations in [`hal::MsU8`] or [`hal::MsU32`].
+//! - `eh1`: adds the `Eh1` adapter for `embedded-hal` 1.0 buses and delays.
+//! - `async`: adds `Aht20Async`, built on `embedded-hal-async`.
+//! - `embassy`: adds `Aht20Async::new_embassy`, using `embassy-time` for delays. See
+//!   `examples/embassy_rp` for an RP2040 application.
+//! - `fugit`: adds [`Aht20Builder`] setters taking `fugit` durations.
+//! - `libm` or `micromath`: adds the `metrics` and `psychrometrics` modules, deriving quantities
+//!   such as the dew point from readings. `libm` is more accurate, `micromath` smaller.
+//! - `defmt`: implements `defmt::Format` for readings, errors and configuration types.
+//! - `serde`: implements `Serialize` and `Deserialize` for readings, as their raw values. Wrap
+//!   them in [`HumanReadable`] to serialize them in percent and Celsius instead.
+//! - `ufmt`: implements `uDisplay` and `uDebug` for readings and errors, formatting readings with
+//!   integer arithmetic only.
+//! - `uom`: converts readings, and the dew point and absolute humidity from `metrics`, to `uom`
+//!   quantities.
+//! - `measurements`: converts readings to `measurements` quantities, in double precision.
+//!
+//! ## Shared buses
+//!
+//! The driver makes no assumption about being the only user of the bus: any wrapper implementing
+//! the bus traits works, such as `embedded-hal-bus`'s `RefCellDevice` or `CriticalSectionDevice`
+//! wrapped in `Eh1`, or `shared-bus` proxies with `embedded-hal` 0.2. Each status check and data
+//! fetch is a single transaction, and [`Aht20::set_bus_retries`] retries transactions that fail
+//! while another user holds the bus. See `examples/refcell_bus.rs` and `examples/shared_bus.rs`.
+//!
+//! ## Non-blocking reads
+//!
+//! [`Aht20::start_measurement`] triggers a conversion and [`Aht20::try_read`] polls for the
+//! result, returning [`nb::Error::WouldBlock`] until the sensor is done.
 
 #![deny(missing_docs)]
 #![no_std]
 
+mod builder;
+pub mod commands;
+mod config;
+pub mod consts;
+mod delta;
+mod fixed;
+mod frame;
+pub mod hal;
+mod readings;
+mod recovery;
+mod sensor;
+#[cfg(feature = "serde")]
+mod serialize;
+mod state;
+mod status;
+mod with_delay;
+
+#[cfg(feature = "async")]
+mod asynch;
+#[cfg(feature = "eh1")]
+pub mod eh1;
+#[cfg(any(feature = "libm", feature = "micromath"))]
+mod math;
+#[cfg(any(feature = "libm", feature = "micromath"))]
+pub mod metrics;
+#[cfg(any(feature = "libm", feature = "micromath"))]
+pub mod psychrometrics;
+#[cfg(any(feature = "uom", feature = "measurements"))]
+mod quantities;
+
+pub use {
+    builder::Aht20Builder,
+    config::{Config, ConfigError, CrcMode, MinIntervalPolicy, PollStrategy, Variant},
+    delta::{HumidityDelta, TemperatureDelta},
+    frame::ParseError,
+    readings::Readings,
+    recovery::{bus_clear, BusClear, BusClearError, RecoveryError, RecoveryReport, RecoveryStep},
+    sensor::{HumiditySensor, TemperatureSensor},
+    state::State,
+    status::{FifoStatus, Status},
+    with_delay::Aht20WithDelay,
+};
+
+#[cfg(feature = "async")]
+pub use asynch::Aht20Async;
+#[cfg(feature = "serde")]
+pub use serialize::HumanReadable;
+
 use {
     bitflags::bitflags,
-    crc_all::CrcAlgo,
-    embedded_hal::blocking::{
-        delay::DelayMs,
-        i2c::{Write, WriteRead},
-    },
-    lazy_static::lazy_static,
+    commands::Command,
+    core::marker::PhantomData,
+    hal::{Borrowed, Bus, Delay, Hooked, NoDelay, NoPin, PowerPin},
 };
 
-const I2C_ADDRESS: u8 = 0x38;
+/// Time budget for a measurement to complete, in microseconds.
+const MEASUREMENT_BUDGET_US: u32 = 50_000;
 
 bitflags! {
-    struct StatusFlags: u8 {
+    pub(crate) struct StatusFlags: u8 {
         const UNKOWN = 0;
         const BUSY = (1 << 7);
         const MODE = ((1 << 6) | (1 << 5));
@@ -36,17 +117,162 @@ bitflags! {
 }
 
 /// AHT20 Error.
-#[derive(Debug, Copy, Clone)]
+///
+/// The enum is `#[non_exhaustive]`, so new failure modes can be added without a breaking
+/// release. When upgrading from 0.2, add a wildcard arm to matches that listed every variant,
+/// e.g. mapping unknown errors to a generic sensor failure.
+///
+/// A sensor staying busy fails differently depending on how the wait is bounded. Waits bounded
+/// by a number of polls, as in [`Aht20::read`] and [`Aht20::calibrate`], still fail with
+/// [`MaxTriesExceeded`](Error::MaxTriesExceeded) as in 0.2, while waits bounded by time, as in
+/// [`Aht20::read_with_timeout`], [`Aht20::read_until`] and [`Aht20::calibrate_until`], fail with
+/// [`Timeout`](Error::Timeout). Both are fatal, see [`is_fatal`](Error::is_fatal), so match on
+/// that to handle either.
+///
+/// Errors compare equal when the bus error does, payloads included.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "ufmt", derive(ufmt::derive::uDebug))]
+#[non_exhaustive]
 pub enum Error<E> {
     /// Device is not calibrated.
     Uncalibrated,
     /// Underlying bus error.
     Bus(E),
     /// Checksum mismatch.
-    Checksum,
+    Checksum {
+        /// CRC byte sent by the sensor.
+        expected: u8,
+        /// CRC computed over the status and data bytes received.
+        computed: u8,
+        /// The whole frame received: status, 5 data bytes and CRC.
+        frame: [u8; 7],
+    },
 
-    /// Max Tries Exceeded.
+    /// Device stayed busy for as many polls as allowed, see [`Config::measurement_max_polls`] and
+    /// [`Config::calibration_max_polls`].
     MaxTriesExceeded,
+    /// Device has not been initialized, see [`Aht20::new_uninit`].
+    NotInitialized,
+    /// Invalid driver configuration.
+    InvalidConfig(ConfigError),
+    /// Device is still busy measuring.
+    Busy,
+    /// Device did not complete a measurement or calibration within the time budget given, as
+    /// opposed to a number of polls, see [`MaxTriesExceeded`](Error::MaxTriesExceeded).
+    Timeout {
+        /// Time spent waiting, in milliseconds.
+        waited_ms: u16,
+    },
+    /// Device stayed busy even after a soft reset and recalibration.
+    StuckBusy,
+    /// Reading outside the sensor's specified output range, see [`Config::check_range`].
+    OutOfRange {
+        /// Raw humidity reading.
+        humidity: u32,
+        /// Raw temperature reading.
+        temperature: u32,
+    },
+    /// Measurement started before the configured minimum interval elapsed.
+    TooSoon {
+        /// Time left until the next measurement may start, in milliseconds.
+        remaining_ms: u32,
+    },
+    /// Frame passed the checksum but holds no measurement, see [`ParseError::InvalidData`].
+    InvalidData,
+    /// Driver refuses to measure after a fatal error, until [`Aht20::recover`] is called.
+    NeedsRecovery,
+    /// No device acknowledged the first transaction of the initialization, as classified by
+    /// [`Bus::is_address_nack`].
+    DeviceNotFound,
+    /// The configured [`Variant`] does not support the operation.
+    Unsupported,
+}
+
+impl<E> Error<E> {
+    /// Converts the bus error with `f`, keeping every other variant as is.
+    ///
+    /// Handy for wrapping the error in a higher-level error type, e.g. with
+    /// `error.map_bus(MyBusError::from)`.
+    pub fn map_bus<F>(self, f: impl FnOnce(E) -> F) -> Error<F> {
+        match self {
+            Error::Uncalibrated => Error::Uncalibrated,
+            Error::Bus(e) => Error::Bus(f(e)),
+            Error::Checksum {
+                expected,
+                computed,
+                frame,
+            } => Error::Checksum {
+                expected,
+                computed,
+                frame,
+            },
+            Error::MaxTriesExceeded => Error::MaxTriesExceeded,
+            Error::NotInitialized => Error::NotInitialized,
+            Error::InvalidConfig(e) => Error::InvalidConfig(e),
+            Error::Busy => Error::Busy,
+            Error::Timeout { waited_ms } => Error::Timeout { waited_ms },
+            Error::StuckBusy => Error::StuckBusy,
+            Error::OutOfRange {
+                humidity,
+                temperature,
+            } => Error::OutOfRange {
+                humidity,
+                temperature,
+            },
+            Error::TooSoon { remaining_ms } => Error::TooSoon { remaining_ms },
+            Error::InvalidData => Error::InvalidData,
+            Error::NeedsRecovery => Error::NeedsRecovery,
+            Error::DeviceNotFound => Error::DeviceNotFound,
+            Error::Unsupported => Error::Unsupported,
+        }
+    }
+
+    /// Returns which kind of error this is, without its payload.
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            Error::Uncalibrated => ErrorKind::Uncalibrated,
+            Error::Bus(_) => ErrorKind::Bus,
+            Error::Checksum { .. } => ErrorKind::Checksum,
+            Error::MaxTriesExceeded => ErrorKind::MaxTriesExceeded,
+            Error::NotInitialized => ErrorKind::NotInitialized,
+            Error::InvalidConfig(_) => ErrorKind::InvalidConfig,
+            Error::Busy => ErrorKind::Busy,
+            Error::Timeout { .. } => ErrorKind::Timeout,
+            Error::StuckBusy => ErrorKind::StuckBusy,
+            Error::OutOfRange { .. } => ErrorKind::OutOfRange,
+            Error::TooSoon { .. } => ErrorKind::TooSoon,
+            Error::InvalidData => ErrorKind::InvalidData,
+            Error::NeedsRecovery => ErrorKind::NeedsRecovery,
+            Error::DeviceNotFound => ErrorKind::DeviceNotFound,
+            Error::Unsupported => ErrorKind::Unsupported,
+        }
+    }
+
+    /// Whether the error leaves the sensor in an unknown state, poisoning the driver.
+    ///
+    /// These are [`MaxTriesExceeded`](Error::MaxTriesExceeded), [`Timeout`](Error::Timeout) and
+    /// [`StuckBusy`](Error::StuckBusy), after which a measurement may still be in flight, plus
+    /// failures of the recovery [`read`](Aht20::read) attempts by itself. Further measurements then
+    /// fail with [`NeedsRecovery`](Error::NeedsRecovery) until [`Aht20::recover`] succeeds. Other
+    /// errors, like a checksum mismatch, leave the driver usable.
+    pub fn is_fatal(&self) -> bool {
+        matches!(
+            self,
+            Error::MaxTriesExceeded | Error::Timeout { .. } | Error::StuckBusy
+        )
+    }
+
+    /// Turns [`MaxTriesExceeded`](Error::MaxTriesExceeded) into [`Timeout`](Error::Timeout), for
+    /// waits bounded by a clock rather than by a number of polls.
+    fn timed_out(self, waited_ms: u32) -> Self {
+        match self {
+            Error::MaxTriesExceeded => Error::Timeout {
+                waited_ms: waited_ms.min(u16::MAX as u32) as u16,
+            },
+            error => error,
+        }
+    }
 }
 
 impl<E> core::convert::From<E> for Error<E> {
@@ -55,15 +281,331 @@ impl<E> core::convert::From<E> for Error<E> {
     }
 }
 
+/// Kind of an [`Error`], one per variant, without the bus error or other payload.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "ufmt", derive(ufmt::derive::uDebug))]
+#[non_exhaustive]
+pub enum ErrorKind {
+    /// See [`Error::Uncalibrated`].
+    Uncalibrated,
+    /// See [`Error::Bus`].
+    Bus,
+    /// See [`Error::Checksum`].
+    Checksum,
+    /// See [`Error::MaxTriesExceeded`].
+    MaxTriesExceeded,
+    /// See [`Error::NotInitialized`].
+    NotInitialized,
+    /// See [`Error::InvalidConfig`].
+    InvalidConfig,
+    /// See [`Error::Busy`].
+    Busy,
+    /// See [`Error::Timeout`].
+    Timeout,
+    /// See [`Error::StuckBusy`].
+    StuckBusy,
+    /// See [`Error::OutOfRange`].
+    OutOfRange,
+    /// See [`Error::TooSoon`].
+    TooSoon,
+    /// See [`Error::InvalidData`].
+    InvalidData,
+    /// See [`Error::NeedsRecovery`].
+    NeedsRecovery,
+    /// See [`Error::DeviceNotFound`].
+    DeviceNotFound,
+    /// See [`Error::Unsupported`].
+    Unsupported,
+}
+
+impl<E: core::fmt::Debug> core::fmt::Display for Error<E> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Error::Uncalibrated => f.write_str("sensor not calibrated"),
+            Error::Bus(e) => write!(f, "bus error: {:?}", e),
+            Error::Checksum {
+                expected,
+                computed,
+                frame,
+            } => write!(
+                f,
+                "checksum mismatch (expected {:#04x}, computed {:#04x}, frame {:02x?})",
+                expected, computed, frame
+            ),
+            Error::MaxTriesExceeded => {
+                f.write_str("sensor still busy after the maximum number of polls")
+            }
+            Error::NotInitialized => f.write_str("driver not initialized"),
+            Error::InvalidConfig(e) => write!(f, "invalid configuration: {}", e),
+            Error::Busy => f.write_str("sensor busy measuring"),
+            Error::Timeout { waited_ms } => {
+                write!(f, "measurement timed out after {} ms", waited_ms)
+            }
+            Error::StuckBusy => f.write_str("sensor stuck busy after a reset"),
+            Error::OutOfRange {
+                humidity,
+                temperature,
+            } => write!(
+                f,
+                "reading out of range (raw humidity {}, raw temperature {})",
+                humidity, temperature
+            ),
+            Error::TooSoon { remaining_ms } => {
+                write!(f, "measurement started {} ms too soon", remaining_ms)
+            }
+            Error::InvalidData => f.write_str("invalid measurement data"),
+            Error::NeedsRecovery => f.write_str("driver needs recovery after a fatal error"),
+            Error::DeviceNotFound => f.write_str("no sensor found at the address"),
+            Error::Unsupported => f.write_str("not supported by the sensor model"),
+        }
+    }
+}
+
+/// The bus error and the configuration error are reported as sources.
+impl<E: core::error::Error + 'static> core::error::Error for Error<E> {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            Error::Bus(e) => Some(e),
+            Error::InvalidConfig(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(feature = "ufmt")]
+impl<E: ufmt::uDebug> ufmt::uDisplay for Error<E> {
+    fn fmt<W: ufmt::uWrite + ?Sized>(
+        &self,
+        f: &mut ufmt::Formatter<'_, W>,
+    ) -> Result<(), W::Error> {
+        use ufmt::uwrite;
+
+        match self {
+            Error::Uncalibrated => f.write_str("sensor not calibrated"),
+            Error::Bus(e) => uwrite!(f, "bus error: {:?}", e),
+            Error::Checksum {
+                expected,
+                computed,
+                frame,
+            } => uwrite!(
+                f,
+                "checksum mismatch (expected {}, computed {}, frame {:?})",
+                expected,
+                computed,
+                frame
+            ),
+            Error::MaxTriesExceeded => {
+                f.write_str("sensor still busy after the maximum number of polls")
+            }
+            Error::NotInitialized => f.write_str("driver not initialized"),
+            Error::InvalidConfig(e) => uwrite!(f, "invalid configuration: {:?}", e),
+            Error::Busy => f.write_str("sensor busy measuring"),
+            Error::Timeout { waited_ms } => {
+                uwrite!(f, "measurement timed out after {} ms", waited_ms)
+            }
+            Error::StuckBusy => f.write_str("sensor stuck busy after a reset"),
+            Error::OutOfRange {
+                humidity,
+                temperature,
+            } => uwrite!(
+                f,
+                "reading out of range (raw humidity {}, raw temperature {})",
+                humidity,
+                temperature
+            ),
+            Error::TooSoon { remaining_ms } => {
+                uwrite!(f, "measurement started {} ms too soon", remaining_ms)
+            }
+            Error::InvalidData => f.write_str("invalid measurement data"),
+            Error::NeedsRecovery => f.write_str("driver needs recovery after a fatal error"),
+            Error::DeviceNotFound => f.write_str("no sensor found at the address"),
+            Error::Unsupported => f.write_str("not supported by the sensor model"),
+        }
+    }
+}
+
+/// Initialization error, handing back the I2C peripheral.
+///
+/// Converts into [`Error`] with `?` for callers that don't need the peripheral back.
+pub struct InitError<I2C, E> {
+    /// The I2C peripheral the driver was created with.
+    pub i2c: I2C,
+    /// The error that caused initialization to fail.
+    pub error: Error<E>,
+}
+
+impl<I2C, E: core::fmt::Debug> core::fmt::Debug for InitError<I2C, E> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("InitError")
+            .field("error", &self.error)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<I2C, E: core::fmt::Debug> core::fmt::Display for InitError<I2C, E> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "initialization failed: {}", self.error)
+    }
+}
+
+impl<I2C, E: core::error::Error + 'static> core::error::Error for InitError<I2C, E> {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+impl<I2C, E> core::convert::From<InitError<I2C, E>> for Error<E> {
+    fn from(e: InitError<I2C, E>) -> Self {
+        e.error
+    }
+}
+
+/// FIFO drain error, noting how many readings were drained before it.
+///
+/// The first [`read`](Self::read) readings of the output are valid. Converts into [`Error`] with
+/// `?` for callers that don't need the count.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct FifoError<E> {
+    /// Readings drained into the output before the failure.
+    pub read: usize,
+    /// The error that stopped the drain.
+    pub error: Error<E>,
+}
+
+impl<E: core::fmt::Debug> core::fmt::Display for FifoError<E> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "FIFO drain failed after {} readings: {}",
+            self.read, self.error
+        )
+    }
+}
+
+impl<E: core::error::Error + 'static> core::error::Error for FifoError<E> {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+impl<E> core::convert::From<FifoError<E>> for Error<E> {
+    fn from(e: FifoError<E>) -> Self {
+        e.error
+    }
+}
+
+/// Power cycling error.
+#[derive(Debug, Copy, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum PowerError<E, PE> {
+    /// The driver has no power pin.
+    Unsupported,
+    /// Underlying pin error.
+    Pin(PE),
+    /// Error initializing the sensor after powering it up.
+    Sensor(Error<E>),
+}
+
+impl<E, PE> core::convert::From<Error<E>> for PowerError<E, PE> {
+    fn from(e: Error<E>) -> Self {
+        PowerError::Sensor(e)
+    }
+}
+
+/// Reading outside the sensor's specified output range.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum RangeError {
+    /// Below the lower limit.
+    TooLow,
+    /// Above the upper limit.
+    TooHigh,
+}
+
 /// Humidity reading from AHT20.
+///
+/// Readings are ordered by their raw value, which is monotonic in the physical quantity.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "ufmt", derive(ufmt::derive::uDebug))]
 pub struct Humidity {
     h: u32,
 }
 
 impl Humidity {
+    /// Creates a humidity from a raw 20-bit reading, e.g. one logged earlier.
+    ///
+    /// Bits above the lowest 20 are masked off, as the sensor has no room for them.
+    pub const fn from_raw(raw: u32) -> Self {
+        Self {
+            h: raw & (consts::RAW_SCALE - 1),
+        }
+    }
+
+    /// Averages `humidities` on their raw values, or `None` if the slice is empty.
+    pub fn average(humidities: &[Humidity]) -> Option<Self> {
+        average_raw(humidities.iter().map(|h| h.h)).map(|h| Self { h })
+    }
+
+    /// Creates the humidity the sensor reports as `rh` percent Relative Humidity, e.g. for a
+    /// threshold to compare readings against.
+    ///
+    /// Rounds to the nearest raw value, saturating outside the 0 to 100% range.
+    pub fn from_rh(rh: f32) -> Self {
+        Self {
+            h: raw_from_scaled(rh / consts::HUMIDITY_SPAN_RH as f32),
+        }
+    }
+
     /// Humidity converted to Relative Humidity %.
     pub fn rh(&self) -> f32 {
-        100.0 * (self.h as f32) / ((1 << 20) as f32)
+        consts::HUMIDITY_SPAN_RH as f32 * (self.h as f32) / (consts::RAW_SCALE as f32)
+    }
+
+    /// Humidity converted to Relative Humidity %, in double precision.
+    pub fn rh_f64(&self) -> f64 {
+        consts::HUMIDITY_SPAN_RH as f64 * (self.h as f64) / (consts::RAW_SCALE as f64)
+    }
+
+    /// Humidity converted to hundredths of a percent Relative Humidity, rounded to nearest.
+    ///
+    /// Uses integer arithmetic only, for targets without an FPU.
+    pub fn rh_centi(&self) -> u16 {
+        consts::raw_to_rh_centi(self.h)
+    }
+
+    /// Humidity converted to thousandths of a percent Relative Humidity, rounded to nearest.
+    ///
+    /// Uses integer arithmetic only, for targets without an FPU.
+    pub fn rh_milli(&self) -> u32 {
+        consts::raw_to_rh_milli(self.h)
+    }
+
+    /// Humidity converted to Relative Humidity %, rejecting values above 100%.
+    pub fn rh_checked(&self) -> Result<f32, RangeError> {
+        self.check_range().map(|()| self.rh())
+    }
+
+    /// Humidity converted to Relative Humidity %, clamped to at most 100%.
+    pub fn rh_clamped(&self) -> f32 {
+        self.rh().min(100.0)
+    }
+
+    /// Checks that the humidity is within the sensor's 0 to 100% output range.
+    fn check_range(&self) -> Result<(), RangeError> {
+        if self.rh_milli() > 100_000 {
+            return Err(RangeError::TooHigh);
+        }
+        Ok(())
+    }
+
+    /// Writes the humidity in percent Relative Humidity with `decimals` places, at most 3.
+    ///
+    /// Formats from [`rh_milli`](Self::rh_milli), without float formatting. Halves round away
+    /// from zero.
+    pub fn format_fixed(&self, f: &mut impl core::fmt::Write, decimals: u8) -> core::fmt::Result {
+        fixed::Fixed::from_milli(self.rh_milli() as i32, decimals).write_to(f)
     }
 
     /// Raw humidity reading.
@@ -72,15 +614,112 @@ impl Humidity {
     }
 }
 
+/// Formats as e.g. `45.2 %`, with one decimal unless a precision such as `{:.2}` is given.
+impl core::fmt::Display for Humidity {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        self.format_fixed(f, f.precision().map_or(1, |p| p.min(3) as u8))?;
+        f.write_str(" %")
+    }
+}
+
 /// Temperature reading from AHT20.
+///
+/// Readings are ordered by their raw value, which is monotonic in the physical quantity.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "ufmt", derive(ufmt::derive::uDebug))]
 pub struct Temperature {
     t: u32,
 }
 
 impl Temperature {
+    /// Creates a temperature from a raw 20-bit reading, e.g. one logged earlier.
+    ///
+    /// Bits above the lowest 20 are masked off, as the sensor has no room for them.
+    pub const fn from_raw(raw: u32) -> Self {
+        Self {
+            t: raw & (consts::RAW_SCALE - 1),
+        }
+    }
+
+    /// Averages `temperatures` on their raw values, or `None` if the slice is empty.
+    pub fn average(temperatures: &[Temperature]) -> Option<Self> {
+        average_raw(temperatures.iter().map(|t| t.t)).map(|t| Self { t })
+    }
+
+    /// Creates the temperature the sensor reports as `celsius`, e.g. for a threshold to compare
+    /// readings against.
+    ///
+    /// Rounds to the nearest raw value, saturating outside the -50°C to 150°C range.
+    pub fn from_celsius(celsius: f32) -> Self {
+        let offset = celsius - consts::TEMPERATURE_OFFSET_C as f32;
+        Self {
+            t: raw_from_scaled(offset / consts::TEMPERATURE_SPAN_C as f32),
+        }
+    }
+
     /// Temperature converted to Celsius.
     pub fn celsius(&self) -> f32 {
-        (200.0 * (self.t as f32) / ((1 << 20) as f32)) - 50.0
+        (consts::TEMPERATURE_SPAN_C as f32 * (self.t as f32) / (consts::RAW_SCALE as f32))
+            + consts::TEMPERATURE_OFFSET_C as f32
+    }
+
+    /// Temperature converted to Celsius, in double precision.
+    pub fn celsius_f64(&self) -> f64 {
+        (consts::TEMPERATURE_SPAN_C as f64 * (self.t as f64) / (consts::RAW_SCALE as f64))
+            + consts::TEMPERATURE_OFFSET_C as f64
+    }
+
+    /// Temperature converted to thousandths of a degree Celsius, rounded to nearest.
+    ///
+    /// Uses integer arithmetic only, for targets without an FPU.
+    pub fn celsius_milli(&self) -> i32 {
+        consts::raw_to_celsius_milli(self.t)
+    }
+
+    /// Temperature converted to Celsius, rejecting values outside the sensor's -40°C to 85°C
+    /// output range by more than the guard band of [`consts::TEMPERATURE_GUARD_MILLI_C`].
+    pub fn celsius_checked(&self) -> Result<f32, RangeError> {
+        self.check_range().map(|()| self.celsius())
+    }
+
+    /// Temperature converted to Celsius, clamped to the sensor's -40°C to 85°C output range.
+    pub fn celsius_clamped(&self) -> f32 {
+        let min = consts::TEMPERATURE_MIN_MILLI_C as f32 / 1000.0;
+        let max = consts::TEMPERATURE_MAX_MILLI_C as f32 / 1000.0;
+        self.celsius().clamp(min, max)
+    }
+
+    /// Checks that the temperature is within the sensor's output range and guard band.
+    fn check_range(&self) -> Result<(), RangeError> {
+        let milli = self.celsius_milli();
+        if milli < consts::TEMPERATURE_MIN_MILLI_C - consts::TEMPERATURE_GUARD_MILLI_C {
+            return Err(RangeError::TooLow);
+        }
+        if milli > consts::TEMPERATURE_MAX_MILLI_C + consts::TEMPERATURE_GUARD_MILLI_C {
+            return Err(RangeError::TooHigh);
+        }
+        Ok(())
+    }
+
+    /// Temperature converted to Fahrenheit.
+    pub fn fahrenheit(&self) -> f32 {
+        let span = consts::TEMPERATURE_SPAN_C * 9 / 5;
+        let offset = consts::TEMPERATURE_OFFSET_C * 9 / 5 + 32;
+        (span as f32 * (self.t as f32) / (consts::RAW_SCALE as f32)) + offset as f32
+    }
+
+    /// Temperature converted to Kelvin.
+    pub fn kelvin(&self) -> f32 {
+        (consts::TEMPERATURE_SPAN_C as f32 * (self.t as f32) / (consts::RAW_SCALE as f32))
+            + (consts::TEMPERATURE_OFFSET_C as f32 + 273.15)
+    }
+
+    /// Writes the temperature in Celsius with `decimals` places, at most 3.
+    ///
+    /// Formats from [`celsius_milli`](Self::celsius_milli), without float formatting. Halves
+    /// round away from zero.
+    pub fn format_fixed(&self, f: &mut impl core::fmt::Write, decimals: u8) -> core::fmt::Result {
+        fixed::Fixed::from_milli(self.celsius_milli(), decimals).write_to(f)
     }
 
     /// Raw temperature reading.
@@ -89,112 +728,1467 @@ impl Temperature {
     }
 }
 
+/// Formats as e.g. `23.4 °C`, with one decimal unless a precision such as `{:.2}` is given.
+impl core::fmt::Display for Temperature {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        self.format_fixed(f, f.precision().map_or(1, |p| p.min(3) as u8))?;
+        f.write_str(" °C")
+    }
+}
+
+/// Averages raw readings, rounding halves up, or `None` if there are none.
+///
+/// Sums into a `u64`, which can't overflow for fewer than 2^44 readings.
+fn average_raw(raws: impl ExactSizeIterator<Item = u32>) -> Option<u32> {
+    let len = raws.len() as u64;
+    if len == 0 {
+        return None;
+    }
+    let sum: u64 = raws.map(u64::from).sum();
+    Some(((sum + len / 2) / len) as u32)
+}
+
+/// Converts a fraction of the full scale to the nearest raw value, saturating to 20 bits.
+fn raw_from_scaled(fraction: f32) -> u32 {
+    // Casting saturates, taking negative values and NaN to 0
+    ((fraction * consts::RAW_SCALE as f32 + 0.5) as u32).min(consts::RAW_SCALE - 1)
+}
+
+/// Humidity and temperature from a single measurement.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Reading {
+    /// Measured humidity.
+    pub humidity: Humidity,
+    /// Measured temperature.
+    pub temperature: Temperature,
+    /// Whether the measurement frame's CRC was checked and matched, see [`Config::crc`].
+    ///
+    /// Not serialized, and false for readings not decoded from a frame.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub crc_verified: bool,
+}
+
+impl Reading {
+    /// Creates a reading from raw 20-bit humidity and temperature readings.
+    ///
+    /// See [`Humidity::from_raw`] and [`Temperature::from_raw`].
+    pub const fn from_raw(humidity: u32, temperature: u32) -> Self {
+        Self {
+            humidity: Humidity::from_raw(humidity),
+            temperature: Temperature::from_raw(temperature),
+            crc_verified: false,
+        }
+    }
+
+    /// Averages `readings` on their raw values, or `None` if the slice is empty.
+    ///
+    /// Halves round up. Slices of up to 2^44 readings, 128 TiB, are supported without overflow.
+    /// The average counts as CRC verified if all readings are.
+    pub fn average(readings: &[Reading]) -> Option<Self> {
+        Some(Self {
+            humidity: Humidity {
+                h: average_raw(readings.iter().map(|r| r.humidity.h))?,
+            },
+            temperature: Temperature {
+                t: average_raw(readings.iter().map(|r| r.temperature.t))?,
+            },
+            crc_verified: readings.iter().all(|r| r.crc_verified),
+        })
+    }
+
+    /// Packs the raw readings into 5 bytes, laid out like the data bytes of a measurement frame.
+    ///
+    /// The bytes are a big-endian 40-bit integer with the humidity in the high 20 bits and the
+    /// temperature in the low 20 bits:
+    ///
+    /// | Byte | Bits 7 to 4          | Bits 3 to 0          |
+    /// |------|----------------------|----------------------|
+    /// | 0    | humidity 19 to 16    | humidity 15 to 12    |
+    /// | 1    | humidity 11 to 8     | humidity 7 to 4      |
+    /// | 2    | humidity 3 to 0      | temperature 19 to 16 |
+    /// | 3    | temperature 15 to 12 | temperature 11 to 8  |
+    /// | 4    | temperature 7 to 4   | temperature 3 to 0   |
+    pub const fn pack(&self) -> [u8; 5] {
+        let (h, t) = (self.humidity.h, self.temperature.t);
+        [
+            (h >> 12) as u8,
+            (h >> 4) as u8,
+            ((h << 4) as u8) | ((t >> 16) as u8 & 0x0f),
+            (t >> 8) as u8,
+            t as u8,
+        ]
+    }
+
+    /// Unpacks a reading packed with [`pack`](Self::pack), or the data bytes of a measurement
+    /// frame.
+    pub const fn unpack(bytes: [u8; 5]) -> Self {
+        let h = ((bytes[0] as u32) << 12) | ((bytes[1] as u32) << 4) | ((bytes[2] as u32) >> 4);
+        let t = (((bytes[2] as u32) & 0x0f) << 16) | ((bytes[3] as u32) << 8) | (bytes[4] as u32);
+        Self::from_raw(h, t)
+    }
+}
+
+impl core::convert::From<(Humidity, Temperature)> for Reading {
+    fn from((humidity, temperature): (Humidity, Temperature)) -> Self {
+        Self {
+            humidity,
+            temperature,
+            crc_verified: false,
+        }
+    }
+}
+
 /// AHT20 driver.
-pub struct Aht20<I2C> {
+///
+/// `S` is the initialization state, either [`Ready`] or [`Uninitialized`]. Readings can only be
+/// taken in the `Ready` state, which the usual constructors such as [`Aht20::new`] produce.
+///
+/// `P` is the pin switching the sensor's supply, see [`with_power_pin`](Aht20::with_power_pin).
+pub struct Aht20<I2C, S = Ready, P = NoPin> {
     i2c: I2C,
+    config: Config,
+    initialized: bool,
+    calibrated: bool,
+    measuring: bool,
+    poisoned: bool,
+    clock: Option<fn() -> u32>,
+    last_trigger_ms: Option<u32>,
+    recalibrations: u32,
+    crc_verified: bool,
+    power_pin: P,
+    _state: PhantomData<S>,
 }
 
+/// Initialization state of a driver that has been initialized and can take readings.
+///
+/// Also the state of drivers from [`Aht20::new_uninit`], which are only initialized at runtime:
+/// their readings fail with [`Error::NotInitialized`] until [`Aht20::init`] is called. Use
+/// [`Uninitialized`] to have that checked at compile time instead.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Ready;
+
+/// Initialization state of a driver that needs [`Aht20::init`] before it can take readings.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Uninitialized;
+
+// The driver is `Send`/`Sync` whenever its bus is, so it can live in shared resources.
+const _: () = {
+    const fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<Aht20<()>>();
+};
+
+/// AHT20 driver borrowing its I2C peripheral.
+///
+/// The bus is only borrowed for the lifetime of the driver, so other devices can use it once the
+/// driver is dropped. Construct it with `Aht20::new(Borrowed(&mut i2c), &mut delay)`, see
+/// [`Borrowed`].
+pub type Aht20Ref<'a, I2C> = Aht20<Borrowed<'a, I2C>>;
+
 impl<I2C, E> Aht20<I2C>
 where
-    I2C: WriteRead<Error = E> + Write<Error = E>,
+    I2C: Bus<Error = E>,
 {
     /// Creates a new AHT20 device from an I2C peripheral and a Delay.
-    pub fn new(i2c: I2C, delay: &mut impl DelayMs<u16>) -> Result<Self, Error<E>> {
-        let mut dev = Self {
-            i2c: i2c
+    ///
+    /// On failure the peripheral is handed back in the [`InitError`]. A missing sensor fails with
+    /// [`Error::DeviceNotFound`] on buses classifying address NACKs, such as [`hal::NackAware`].
+    ///
+    /// The delay is borrowed, as in `Aht20::new(i2c, &mut Delay)`. It cannot be passed by value
+    /// as well: implementing [`Delay`] for `&mut D` would overlap with the blanket implementation
+    /// for `embedded-hal` 0.2 delays, which another crate may implement for references. Use
+    /// [`Aht20WithDelay::new`] to hand the driver its delay instead.
+    pub fn new(i2c: I2C, delay: &mut impl Delay) -> Result<Self, InitError<I2C, E>> {
+        Self::new_with_address(i2c, consts::ADDRESS, delay)
+    }
+
+    /// Creates a new driver for the older AHT10, see [`Variant::Aht10`].
+    pub fn new_aht10(i2c: I2C, delay: &mut impl Delay) -> Result<Self, InitError<I2C, E>> {
+        Self::new_with_variant(i2c, Variant::Aht10, delay)
+    }
+
+    /// Creates a new driver for the AHT25, see [`Variant::Aht25`].
+    pub fn new_aht25(i2c: I2C, delay: &mut impl Delay) -> Result<Self, InitError<I2C, E>> {
+        Self::new_with_variant(i2c, Variant::Aht25, delay)
+    }
+
+    /// Creates a new driver for the DHT20, see [`Variant::Dht20`].
+    pub fn new_dht20(i2c: I2C, delay: &mut impl Delay) -> Result<Self, InitError<I2C, E>> {
+        Self::new_with_variant(i2c, Variant::Dht20, delay)
+    }
+
+    /// Creates a new driver for the given sensor model.
+    pub fn new_with_variant(
+        i2c: I2C,
+        variant: Variant,
+        delay: &mut impl Delay,
+    ) -> Result<Self, InitError<I2C, E>> {
+        Aht20Builder::new().variant(variant).build(i2c, delay)
+    }
+
+    /// Creates a new AHT20 device at a non-default I2C address.
+    pub fn new_with_address(
+        i2c: I2C,
+        address: u8,
+        delay: &mut impl Delay,
+    ) -> Result<Self, InitError<I2C, E>> {
+        Aht20Builder::new().address(address).build(i2c, delay)
+    }
+
+    /// Creates a new AHT20 device with the given configuration.
+    ///
+    /// The sensor is soft reset and calibrated as enabled in `config`. An invalid configuration
+    /// fails with [`Error::InvalidConfig`] before any bus traffic.
+    pub fn new_with_config(
+        i2c: I2C,
+        config: Config,
+        delay: &mut impl Delay,
+    ) -> Result<Self, InitError<I2C, E>> {
+        if let Err(e) = config.validate() {
+            return Err(InitError {
+                i2c,
+                error: Error::InvalidConfig(e),
+            });
+        }
+
+        let mut dev = Self::new_uninit(i2c);
+        dev.config = config;
+        match dev.init(delay) {
+            Ok(()) => Ok(dev),
+            Err(error) => Err(InitError {
+                i2c: dev.free(),
+                error,
+            }),
+        }
+    }
+
+    /// Creates a new AHT20 device without any bus traffic.
+    ///
+    /// Readings fail with [`Error::NotInitialized`] until [`init`](Self::init) is called, so the
+    /// driver can be created before a delay provider is available. See
+    /// [`uninitialized`](Aht20::uninitialized) for the same check at compile time.
+    pub fn new_uninit(i2c: I2C) -> Self {
+        let mut dev = Self::attach(i2c);
+        dev.initialized = false;
+        dev
+    }
+
+    /// Wraps an already initialized sensor without any bus traffic.
+    ///
+    /// Use this when the sensor stayed powered, e.g. across an MCU deep sleep, and check it with
+    /// [`verify`](Self::verify) if in doubt.
+    pub fn attach(i2c: I2C) -> Self {
+        Self {
+            i2c,
+            config: Config::default(),
+            initialized: true,
+            calibrated: false,
+            measuring: false,
+            poisoned: false,
+            clock: None,
+            last_trigger_ms: None,
+            recalibrations: 0,
+            crc_verified: false,
+            power_pin: NoPin,
+            _state: PhantomData,
+        }
+    }
+
+    /// Rebuilds a driver from an I2C peripheral and the state of a previous driver, without any
+    /// bus traffic.
+    ///
+    /// The driver behaves exactly as the one `state` was taken from with
+    /// [`into_parts`](Self::into_parts), provided the sensor stayed powered in between. The clock
+    /// is not part of the state and has to be set again, see [`set_clock`](Self::set_clock).
+    pub fn from_parts(i2c: I2C, state: State) -> Self {
+        Self {
+            i2c,
+            config: state.config,
+            initialized: state.initialized,
+            calibrated: state.calibrated,
+            measuring: state.measuring,
+            poisoned: state.poisoned,
+            clock: None,
+            last_trigger_ms: state.last_trigger_ms,
+            recalibrations: state.recalibrations,
+            crc_verified: false,
+            power_pin: NoPin,
+            _state: PhantomData,
+        }
+    }
+
+    /// Checks whether a sensor is present at the default address, with a single status read.
+    ///
+    /// Returns `Ok(false)` when no device acknowledges, as classified by
+    /// [`Bus::is_address_nack`], or when the status byte reads as all ones. Other bus errors are
+    /// returned as is.
+    pub fn probe(i2c: &mut I2C) -> Result<bool, E> {
+        let buf = &mut [0u8; 1];
+        i2c.begin()?;
+        match i2c.write_read(consts::ADDRESS, Command::ReadStatus.bytes(), buf) {
+            Ok(()) => Ok(buf[0] != 0xFF),
+            Err(e) if i2c.is_address_nack(&e) => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Like [`probe`](Self::probe), with `is_nack` classifying address NACKs.
+    ///
+    /// Needed with `embedded-hal` 0.2 buses, whose errors are opaque to the driver, unless they
+    /// are wrapped in [`hal::NackAware`].
+    pub fn probe_with(i2c: &mut I2C, is_nack: impl Fn(&E) -> bool) -> Result<bool, E> {
+        let buf = &mut [0u8; 1];
+        i2c.begin()?;
+        match i2c.write_read(consts::ADDRESS, Command::ReadStatus.bytes(), buf) {
+            Ok(()) => Ok(buf[0] != 0xFF),
+            Err(e) if is_nack(&e) => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Returns a builder for configuring the driver.
+    pub fn builder() -> Aht20Builder {
+        Aht20Builder::new()
+    }
+
+    /// Creates a new AHT20 device calling `hook` on the bus before each bus operation.
+    ///
+    /// Each status check, trigger, data fetch, reset and calibration command is a single
+    /// operation, as is the register reset of the initialization, so the hook can select the
+    /// sensor's channel on an I2C multiplexer such as the TCA9548A. Bus retries don't call it
+    /// again, see [`Bus::begin`]. Hook errors are returned as [`Error::Bus`].
+    #[allow(clippy::type_complexity)]
+    pub fn with_bus_hook<H>(
+        i2c: I2C,
+        hook: H,
+        delay: &mut impl Delay,
+    ) -> Result<Aht20<Hooked<I2C, H>>, InitError<Hooked<I2C, H>, E>>
+    where
+        H: FnMut(&mut I2C) -> Result<(), E>,
+    {
+        Aht20::new(Hooked::new(i2c, hook), delay)
+    }
+}
+
+impl<I2C, E> Aht20<I2C, Uninitialized>
+where
+    I2C: Bus<Error = E>,
+{
+    /// Creates an uninitialized driver with the default configuration, without any bus traffic.
+    ///
+    /// Call [`init`](Self::init) before taking readings. See
+    /// [`Aht20Builder::build_uninitialized`] for other configurations.
+    pub fn uninitialized(i2c: I2C) -> Self {
+        Self::uninitialized_with_config(i2c, Config::default())
+    }
+
+    /// Creates an uninitialized driver with the given configuration, without any bus traffic.
+    pub(crate) fn uninitialized_with_config(i2c: I2C, config: Config) -> Self {
+        Self {
+            i2c,
+            config,
+            initialized: false,
+            calibrated: false,
+            measuring: false,
+            poisoned: false,
+            clock: None,
+            last_trigger_ms: None,
+            recalibrations: 0,
+            crc_verified: false,
+            power_pin: NoPin,
+            _state: PhantomData,
+        }
+    }
+}
+
+impl<I2C, E, P> Aht20<I2C, Uninitialized, P>
+where
+    I2C: Bus<Error = E>,
+{
+    /// Initializes the sensor, soft resetting and calibrating it as enabled in the configuration.
+    ///
+    /// The calibration is skipped if the sensor already reports itself as calibrated. On failure
+    /// the driver is handed back, still uninitialized, along with the error.
+    pub fn init(
+        mut self,
+        delay: &mut impl Delay,
+    ) -> Result<Aht20<I2C, Ready, P>, (Self, Error<E>)> {
+        self.measuring = false;
+        if let Err(e) = self.init_sequence(delay) {
+            return Err((self, e));
+        }
+
+        Ok(Aht20 {
+            i2c: self.i2c,
+            config: self.config,
+            initialized: true,
+            calibrated: self.calibrated,
+            measuring: false,
+            poisoned: false,
+            clock: self.clock,
+            last_trigger_ms: self.last_trigger_ms,
+            recalibrations: self.recalibrations,
+            crc_verified: self.crc_verified,
+            power_pin: self.power_pin,
+            _state: PhantomData,
+        })
+    }
+}
+
+impl<I2C, E, S, P> Aht20<I2C, S, P>
+where
+    I2C: Bus<Error = E>,
+{
+    /// Destroys the driver, returning the I2C peripheral.
+    ///
+    /// The driver keeps no bus state, so the peripheral can be handed to [`attach`](Self::attach)
+    /// or [`new`](Self::new) again later.
+    pub fn free(self) -> I2C {
+        self.i2c
+    }
+
+    /// Adds a pin switching the sensor's supply, enabling [`power_cycle`](Aht20::power_cycle).
+    ///
+    /// The pin is expected to power the sensor when high.
+    pub fn with_power_pin<Q: PowerPin>(self, pin: Q) -> Aht20<I2C, S, Q> {
+        Aht20 {
+            i2c: self.i2c,
+            config: self.config,
+            initialized: self.initialized,
+            calibrated: self.calibrated,
+            measuring: self.measuring,
+            poisoned: self.poisoned,
+            clock: self.clock,
+            last_trigger_ms: self.last_trigger_ms,
+            recalibrations: self.recalibrations,
+            crc_verified: self.crc_verified,
+            power_pin: pin,
+            _state: PhantomData,
+        }
+    }
+
+    /// Removes the power pin from the driver, returning it.
+    pub fn release_power_pin(self) -> (Aht20<I2C, S>, P) {
+        let dev = Aht20 {
+            i2c: self.i2c,
+            config: self.config,
+            initialized: self.initialized,
+            calibrated: self.calibrated,
+            measuring: self.measuring,
+            poisoned: self.poisoned,
+            clock: self.clock,
+            last_trigger_ms: self.last_trigger_ms,
+            recalibrations: self.recalibrations,
+            crc_verified: self.crc_verified,
+            power_pin: NoPin,
+            _state: PhantomData,
         };
-        dev.reset(delay)?;
-        dev.calibrate(delay)?;
-        Ok(dev)
+        (dev, self.power_pin)
+    }
+
+    /// Destroys the driver, returning the I2C peripheral and the driver state.
+    ///
+    /// Use [`from_parts`](Self::from_parts) to rebuild the driver, possibly with a new peripheral.
+    pub fn into_parts(self) -> (I2C, State) {
+        let state = State {
+            config: self.config,
+            initialized: self.initialized,
+            calibrated: self.calibrated,
+            measuring: self.measuring,
+            poisoned: self.poisoned,
+            last_trigger_ms: self.last_trigger_ms,
+            recalibrations: self.recalibrations,
+        };
+        (self.i2c, state)
+    }
+
+    /// Returns the driver configuration.
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    /// Returns how many times [`read`](Aht20::read) recalibrated a sensor that had lost its
+    /// calibration, typically after a brown-out.
+    ///
+    /// Only counts while [`Config::recover_uncalibrated`] is set; wraps on overflow.
+    pub fn recalibrations(&self) -> u32 {
+        self.recalibrations
+    }
+
+    /// Returns whether a fatal error poisoned the driver, see [`Error::is_fatal`].
+    pub fn needs_recovery(&self) -> bool {
+        self.poisoned
+    }
+
+    /// Sets how many times a failed bus transaction is retried before the error is returned.
+    ///
+    /// Useful when the bus is shared through a proxy and another user may hold it. Defaults to 0.
+    pub fn set_bus_retries(&mut self, retries: u8) {
+        self.config.bus_retries = retries;
+    }
+
+    /// Sets the wait before retrying a failed bus transaction, in microseconds. Defaults to 0.
+    pub fn set_bus_retry_delay_us(&mut self, us: u32) {
+        self.config.bus_retry_delay_us = us;
+    }
+
+    /// Sets the interval between busy polls while waiting for a measurement, in microseconds.
+    ///
+    /// The poll limit is rescaled to keep the time it allowed, and at least one poll, so shorter
+    /// intervals poll more often and a limit raised in the configuration is kept. Use a delay
+    /// with microsecond resolution, such as [`hal::Micros`], for intervals that are not whole
+    /// milliseconds. Defaults to 10ms; zero is treated as 1µs.
+    pub fn set_poll_interval_us(&mut self, us: u32) {
+        let budget_us = self
+            .config
+            .measurement_max_polls
+            .saturating_mul(self.config.measurement_poll_interval_us);
+        self.config.measurement_poll_interval_us = us.max(1);
+        self.config.measurement_max_polls = (budget_us / us.max(1)).max(1);
+    }
+
+    /// Sets the monotonic millisecond clock enforcing the configured minimum interval between
+    /// measurements.
+    ///
+    /// `now` may wrap. Without a clock the interval is not enforced.
+    pub fn set_clock(&mut self, now: fn() -> u32) {
+        self.clock = Some(now);
+    }
+
+    /// Time left until the next measurement may start, in milliseconds.
+    fn interval_remaining_ms(&self) -> u32 {
+        match (self.clock, self.last_trigger_ms) {
+            (Some(now), Some(last)) => self
+                .config
+                .min_interval_ms
+                .saturating_sub(now().wrapping_sub(last)),
+            _ => 0,
+        }
+    }
+
+    /// Runs the bus transaction `op` on the sensor's address, retrying it while it fails with
+    /// retryable errors.
+    fn retrying(
+        &mut self,
+        delay: &mut impl Delay,
+        mut op: impl FnMut(&mut I2C, u8) -> Result<(), E>,
+    ) -> Result<(), E> {
+        let mut retries = self.config.bus_retries;
+        loop {
+            match op(&mut self.i2c, self.config.address) {
+                Err(e) if retries > 0 && self.i2c.is_retryable(&e) => {
+                    retries -= 1;
+                    delay.delay_us(self.config.bus_retry_delay_us);
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// Writes `bytes` to the sensor as an operation of its own, see [`Bus::begin`], retrying
+    /// failed transactions.
+    fn write(&mut self, bytes: &[u8], delay: &mut impl Delay) -> Result<(), E> {
+        self.i2c.begin()?;
+        self.retrying(delay, |i2c, address| i2c.write(address, bytes))
+    }
+
+    /// Writes `bytes` to the sensor and reads its response into `buffer`, as an operation of its
+    /// own, retrying failed transactions.
+    fn write_read(
+        &mut self,
+        bytes: &[u8],
+        buffer: &mut [u8],
+        delay: &mut impl Delay,
+    ) -> Result<(), E> {
+        self.i2c.begin()?;
+        self.retrying(delay, |i2c, address| i2c.write_read(address, bytes, buffer))
+    }
+
+    /// Bytes of `cmd` for the configured [`Variant`].
+    fn command<'a>(&self, cmd: Command<'a>) -> &'a [u8] {
+        cmd.bytes_for(self.config.variant)
+    }
+
+    /// Sends a command to the sensor, in the form its configured [`Variant`] takes.
+    pub fn send_command(&mut self, cmd: Command) -> Result<(), Error<E>> {
+        self.write(self.command(cmd), &mut NoDelay)?;
+
+        Ok(())
+    }
+
+    /// Decodes a measurement frame as configured, remembering whether its CRC was verified.
+    fn decode(
+        &mut self,
+        buf: &[u8; frame::FRAME_LEN],
+    ) -> Result<(Humidity, Temperature), Error<E>> {
+        let reading = frame::parse(buf, self.config.crc)?;
+        self.crc_verified = reading.crc_verified;
+        Ok((reading.humidity, reading.temperature))
+    }
+
+    /// Reads a measurement frame into `buf`, leaving out the CRC byte when it is disabled.
+    fn read_frame(
+        &mut self,
+        buf: &mut [u8; frame::FRAME_LEN],
+        delay: &mut impl Delay,
+    ) -> Result<(), E> {
+        let len = self.config.frame_len();
+        self.write_read(self.command(Command::ReadStatus), &mut buf[..len], delay)
+    }
+
+    /// Enables the sensor's FIFO, buffering measurements to be drained with
+    /// [`read_fifo`](Aht20::read_fifo).
+    ///
+    /// Only the AHT10 has a FIFO, enabled through its initialization command; other models fail
+    /// with [`Error::Unsupported`].
+    pub fn enable_fifo(&mut self) -> Result<(), Error<E>> {
+        self.set_fifo(true)
+    }
+
+    /// Disables the sensor's FIFO, see [`enable_fifo`](Self::enable_fifo).
+    pub fn disable_fifo(&mut self) -> Result<(), Error<E>> {
+        self.set_fifo(false)
+    }
+
+    /// Reads the FIFO flags of the sensor's status byte, with a single transaction.
+    ///
+    /// Fails with [`Error::Unsupported`] on models without a FIFO.
+    pub fn fifo_status(&mut self) -> Result<FifoStatus, Error<E>> {
+        self.check_fifo()?;
+        Ok(self.read_status()?.fifo())
+    }
+
+    /// Sends the initialization command with the FIFO bit set or cleared.
+    fn set_fifo(&mut self, enable: bool) -> Result<(), Error<E>> {
+        self.check_fifo()?;
+
+        let mut command = consts::AHT10_INIT;
+        if enable {
+            command[1] |= consts::INIT_FIFO_ENABLE;
+        }
+        self.write(&command, &mut NoDelay)?;
+
+        Ok(())
+    }
+
+    /// Fails with [`Error::Unsupported`] unless the configured model has a FIFO.
+    fn check_fifo(&self) -> Result<(), Error<E>> {
+        if !self.config.variant.has_fifo() {
+            return Err(Error::Unsupported);
+        }
+        Ok(())
+    }
+
+    /// Whether busy polls fetch the whole measurement frame, as set in the configuration.
+    fn polls_in_frame(&self) -> bool {
+        match self.config.poll_strategy {
+            PollStrategy::Auto => I2C::REPEATED_START,
+            PollStrategy::Status => false,
+            PollStrategy::InFrame => true,
+        }
+    }
+
+    /// Waits before busy poll number `poll`, following the poll schedule or else for
+    /// `interval_us`.
+    fn poll_delay(&self, delay: &mut impl Delay, poll: u32, interval_us: u32) {
+        delay.delay_us(self.config.poll_wait_us(poll, interval_us));
+    }
+
+    /// Reads the sensor's status byte, with a single transaction.
+    ///
+    /// Useful to check that the hardware responds, in any state of the driver.
+    pub fn read_status(&mut self) -> Result<Status, Error<E>> {
+        let status = self.status(&mut NoDelay)?;
+        Ok(Status::from_bits(status.bits))
+    }
+
+    /// Returns the CRC mode in effect for the sensor, resolving [`CrcMode::FromStatus`] against a
+    /// fresh status read, see [`CrcMode::for_status`].
+    pub fn effective_crc_mode(&mut self) -> Result<CrcMode, Error<E>> {
+        let status = self.read_status()?;
+        Ok(self.config.crc.for_status(status))
     }
 
     /// Gets the sensor status.
-    fn status(&mut self) -> Result<StatusFlags, E> {
+    fn status(&mut self, delay: &mut impl Delay) -> Result<StatusFlags, E> {
         let buf = &mut [0u8; 1];
-        self.i2c.write_read(I2C_ADDRESS, &[0u8], buf)?;
+        self.write_read(self.command(Command::ReadStatus), buf, delay)?;
 
         Ok(StatusFlags { bits: buf[0] })
     }
 
+    /// Checks that the sensor reports itself as calibrated, with a single status read.
+    pub fn verify(&mut self) -> Result<(), Error<E>> {
+        if !self
+            .status(&mut NoDelay)?
+            .contains(StatusFlags::CALIBRATION_ENABLE)
+        {
+            return Err(Error::Uncalibrated);
+        }
+
+        self.calibrated = true;
+        Ok(())
+    }
+
     /// Self-calibrate the sensor.
-    pub fn calibrate(&mut self, delay: &mut impl DelayMs<u16>) -> Result<(), Error<E>> {
-        // Send calibrate command
-        self.i2c.write(I2C_ADDRESS, &[0xE1, 0x08, 0x00])?;
+    ///
+    /// Fails with [`Error::MaxTriesExceeded`] if the sensor stays busy, and with
+    /// [`Error::Uncalibrated`] if it does not report itself as calibrated afterwards. Models
+    /// without an initialization command, see [`Variant`], are only polled, as is a DHT20 already
+    /// reporting itself as calibrated.
+    pub fn calibrate(&mut self, delay: &mut impl Delay) -> Result<(), Error<E>> {
+        let max_polls = self.config.calibration_max_polls;
+        self.calibrate_polling(delay, |poll| poll == max_polls)
+    }
 
-        // Wait until not busy or max tries exceeded
-        let mut max_tries = 10u8;
-        while self.status()?.contains(StatusFlags::BUSY) {
-            delay.delay_ms(10);
-            max_tries -= 1;
+    /// Sends the initialization command if the sensor has one and needs it, lets the sensor
+    /// settle, then polls it until it is idle and confirms it is calibrated.
+    ///
+    /// Gives up with [`Error::MaxTriesExceeded`] once `expired` returns true for the number of
+    /// polls made so far.
+    fn calibrate_polling(
+        &mut self,
+        delay: &mut impl Delay,
+        mut expired: impl FnMut(u32) -> bool,
+    ) -> Result<(), Error<E>> {
+        // Send calibrate command, if the sensor has one and, for the DHT20, needs it
+        let send = match self.config.variant {
+            Variant::Dht20 => !self
+                .status(delay)?
+                .contains(StatusFlags::CALIBRATION_ENABLE),
+            variant => variant.has_init_command(),
+        };
+        if send {
+            self.write(self.command(Command::Initialize), delay)?;
+        }
 
-            if max_tries == 0 {
-                return Err(Error::Uncalibrated);
+        // Let the sensor settle before polling
+        delay.delay_ms(self.config.calibration_poll_interval_ms);
+
+        // Wait until not busy or max tries exceeded
+        let interval_us = self.config.calibration_poll_interval_ms as u32 * 1000;
+        let mut poll = 0;
+        while self.status(delay)?.contains(StatusFlags::BUSY) {
+            if expired(poll) {
+                return Err(Error::MaxTriesExceeded);
             }
+            self.poll_delay(delay, poll, interval_us);
+            poll += 1;
         }
 
         // Confirm sensor is calibrated
-        if !self.status()?.contains(StatusFlags::CALIBRATION_ENABLE) {
+        if !self
+            .status(delay)?
+            .contains(StatusFlags::CALIBRATION_ENABLE)
+        {
             return Err(Error::Uncalibrated);
         }
 
+        self.calibrated = true;
+        Ok(())
+    }
+
+    /// Calibrates the sensor unless it already reports itself as calibrated.
+    ///
+    /// Returns whether a calibration was performed.
+    pub fn ensure_calibrated(&mut self, delay: &mut impl Delay) -> Result<bool, Error<E>> {
+        if self
+            .status(delay)?
+            .contains(StatusFlags::CALIBRATION_ENABLE)
+        {
+            self.calibrated = true;
+            return Ok(false);
+        }
+
+        self.calibrate(delay)?;
+        Ok(true)
+    }
+
+    /// Self-calibrate the sensor like [`calibrate`](Self::calibrate), giving up once `timeout_ms`
+    /// have elapsed.
+    ///
+    /// `now` returns a monotonic millisecond timestamp, which may wrap. The sensor is polled at the
+    /// calibration poll interval, or following the poll schedule, see [`Config`]. Fails with
+    /// [`Error::Timeout`] carrying the time waited if the sensor stays busy.
+    pub fn calibrate_until(
+        &mut self,
+        delay: &mut impl Delay,
+        mut now: impl FnMut() -> u32,
+        timeout_ms: u32,
+    ) -> Result<(), Error<E>> {
+        let start = now();
+        let mut waited_ms = 0;
+        self.calibrate_polling(delay, |_| {
+            waited_ms = now().wrapping_sub(start);
+            waited_ms >= timeout_ms
+        })
+        .map_err(|e| e.timed_out(waited_ms))
+    }
+
+    /// Soft resets and recalibrates the sensor, recovering it from a glitch.
+    ///
+    /// Can be called at any time, including after failed readings, fatal ones included; any
+    /// measurement in flight is abandoned. On success the next reading starts afresh.
+    pub fn reset_and_recalibrate(&mut self, delay: &mut impl Delay) -> Result<(), Error<E>> {
+        self.measuring = false;
+        self.reset(delay)?;
+        self.calibrate(delay)?;
+        self.initialized = true;
+        self.poisoned = false;
+
+        Ok(())
+    }
+
+    /// Waits out the configured power-on delay, soft resets the sensor if enabled in the
+    /// configuration, then calibrates it if enabled, unless it already is.
+    ///
+    /// An address NACK on the first transaction fails with [`Error::DeviceNotFound`], later ones
+    /// with [`Error::Bus`], so a missing sensor can be told from a misbehaving one.
+    fn init_sequence(&mut self, delay: &mut impl Delay) -> Result<(), Error<E>> {
+        // Give a sensor powered up together with the MCU time to start
+        if self.config.power_on_delay_ms > 0 {
+            delay.delay_ms(self.config.power_on_delay_ms);
+        }
+
+        let mut first = true;
+        if self.config.perform_soft_reset {
+            self.reset(delay).map_err(|e| self.not_found_or_bus(e))?;
+            first = false;
+        }
+        if !self.config.perform_calibration {
+            return Ok(());
+        }
+
+        let mut status = match self.status(delay) {
+            Ok(status) => status,
+            Err(e) if first => return Err(self.not_found_or_bus(e)),
+            Err(e) => return Err(Error::Bus(e)),
+        };
+
+        // The datasheet asks for a register reset unless the status reads 0x18 after power-on
+        // It reads registers back with a plain read, so buses without one skip it
+        if I2C::PLAIN_READ && self.config.resets_registers(status) {
+            self.reset_registers(delay)?;
+            status = self.status(delay)?;
+        }
+
+        if status.contains(StatusFlags::CALIBRATION_ENABLE) {
+            self.calibrated = true;
+        } else if self.config.variant.has_init_command() {
+            self.calibrate(delay)?;
+        }
+
+        // Some models need a pause between the status check and the first trigger
+        let settle_ms = self.config.variant.trigger_settle_ms();
+        if settle_ms > 0 {
+            delay.delay_ms(settle_ms);
+        }
         Ok(())
     }
 
+    /// Resets the sensor's 0x1B, 0x1C and 0x1E registers, following the vendor's power-on
+    /// sequence.
+    ///
+    /// Each register is selected, read back, then written back with its two data bytes, all as
+    /// a single operation, see [`Bus::begin`].
+    fn reset_registers(&mut self, delay: &mut impl Delay) -> Result<(), E> {
+        self.i2c.begin()?;
+        for register in consts::RESET_REGISTERS {
+            let select = [register, 0x00, 0x00];
+            self.retrying(delay, |i2c, address| i2c.write(address, &select))?;
+            delay.delay_ms(consts::REGISTER_READ_MS);
+
+            let buf = &mut [0u8; 3];
+            self.retrying(delay, |i2c, address| i2c.read(address, buf))?;
+            delay.delay_ms(consts::REGISTER_WRITE_MS);
+
+            let write = [consts::REGISTER_WRITE | register, buf[1], buf[2]];
+            self.retrying(delay, |i2c, address| i2c.write(address, &write))?;
+        }
+
+        // Let the registers settle before the sensor is used
+        delay.delay_ms(consts::REGISTER_WRITE_MS);
+        Ok(())
+    }
+
+    /// Classifies `error` as [`Error::DeviceNotFound`] if it is an address NACK.
+    fn not_found_or_bus(&self, error: E) -> Error<E> {
+        if self.i2c.is_address_nack(&error) {
+            Error::DeviceNotFound
+        } else {
+            Error::Bus(error)
+        }
+    }
+
     /// Soft resets the sensor.
-    pub fn reset(&mut self, delay: &mut impl DelayMs<u16>) -> Result<(), E> {
+    pub fn reset(&mut self, delay: &mut impl Delay) -> Result<(), E> {
         // Send soft reset command
-        self.i2c.write(I2C_ADDRESS, &[0xBA])?;
+        self.calibrated = false;
+        self.write(self.command(Command::SoftReset), delay)?;
 
         // Wait 20ms as stated in specification
-        delay.delay_ms(20);
+        delay.delay_ms(consts::SOFT_RESET_MS);
+
+        Ok(())
+    }
+}
+
+impl<I2C, E, P> Aht20<I2C, Ready, P>
+where
+    I2C: Bus<Error = E>,
+{
+    /// Initializes the sensor, soft resetting and calibrating it as enabled in the configuration.
+    ///
+    /// The calibration is skipped if the sensor already reports itself as calibrated.
+    pub fn init(&mut self, delay: &mut impl Delay) -> Result<(), Error<E>> {
+        self.initialized = false;
+        self.measuring = false;
+        self.init_sequence(delay)?;
+        self.initialized = true;
+        self.poisoned = false;
+        Ok(())
+    }
+
+    /// Recovers a misbehaving sensor, including after a fatal error, see [`Error::is_fatal`].
+    ///
+    /// The sensor is soft reset, then initialized as in the datasheet, calibrating it only if it
+    /// lost its calibration, and a fresh status read confirms it is calibrated and idle. If
+    /// [`Config::flush_on_recover`] is set, a measurement is then taken and discarded, in case the
+    /// sensor held stale data. Any measurement in flight is abandoned.
+    ///
+    /// The driver is poisoned until the sensor is confirmed calibrated, so it stays poisoned if
+    /// the reset, initialization or verification fails. The error notes the step that failed.
+    pub fn recover(&mut self, delay: &mut impl Delay) -> Result<RecoveryReport, RecoveryError<E>> {
+        let fail = |step| move |error| RecoveryError { step, error };
+        let mut delay = recovery::Timed::new(delay);
 
+        self.measuring = false;
+        self.poisoned = true;
+        self.reset(&mut delay)
+            .map_err(Error::Bus)
+            .map_err(fail(RecoveryStep::Reset))?;
+        let reinitialized = self
+            .ensure_calibrated(&mut delay)
+            .map_err(fail(RecoveryStep::Initialize))?;
+        self.confirm_idle(&mut delay)
+            .map_err(fail(RecoveryStep::Verify))?;
+        self.initialized = true;
+        self.poisoned = false;
+
+        let flushed = self.config.flush_on_recover;
+        if flushed {
+            let result = self.measure(&mut delay);
+            self.poison_if_fatal(result)
+                .map_err(fail(RecoveryStep::Flush))?;
+        }
+
+        Ok(RecoveryReport {
+            reinitialized,
+            flushed,
+            delay_ms: delay.elapsed_ms(),
+        })
+    }
+
+    /// Power cycles the sensor through its power pin, then initializes it again.
+    ///
+    /// The pin is held low for the configured off time, then the sensor is given its datasheet
+    /// power-on time before [`init`](Self::init). Fails with [`PowerError::Unsupported`] when the
+    /// driver has no power pin.
+    pub fn power_cycle(&mut self, delay: &mut impl Delay) -> Result<(), PowerError<E, P::Error>>
+    where
+        P: PowerPin,
+    {
+        if !P::CONNECTED {
+            return Err(PowerError::Unsupported);
+        }
+
+        // Switch the supply off and back on
+        self.initialized = false;
+        self.measuring = false;
+        self.power_pin.set_low().map_err(PowerError::Pin)?;
+        delay.delay_ms(self.config.power_off_ms);
+        self.power_pin.set_high().map_err(PowerError::Pin)?;
+
+        // Wait for the sensor to power up as stated in specification
+        delay.delay_ms(consts::POWER_ON_MS);
+
+        self.init(delay)?;
         Ok(())
     }
 
+    /// Turns the driver back into the [`Uninitialized`] state, without any bus traffic.
+    ///
+    /// Use this when a reading fails with [`Error::Uncalibrated`], so that [`init`](Aht20::init)
+    /// has to be called again before the next reading, or let [`read`](Self::read) recalibrate
+    /// by itself with [`Config::recover_uncalibrated`].
+    pub fn into_uninitialized(self) -> Aht20<I2C, Uninitialized, P> {
+        Aht20 {
+            i2c: self.i2c,
+            config: self.config,
+            initialized: false,
+            calibrated: false,
+            measuring: false,
+            poisoned: false,
+            clock: self.clock,
+            last_trigger_ms: self.last_trigger_ms,
+            recalibrations: self.recalibrations,
+            crc_verified: self.crc_verified,
+            power_pin: self.power_pin,
+            _state: PhantomData,
+        }
+    }
+
     /// Reads humidity and temperature.
-    pub fn read(&mut self, delay: &mut impl DelayMs<u16>) -> Result<(Humidity, Temperature), Error<E>> {
-        lazy_static! {
-            static ref CRC: CrcAlgo<u8> = CrcAlgo::<u8>::new(49, 8, 0xFF, 0x00, false);
+    ///
+    /// The sensor is first left alone for the configured initial wait, then polled until it is
+    /// done. Measurements failing their checksum are retried as set in the configuration, and
+    /// the configured minimum interval since the previous measurement is enforced.
+    ///
+    /// If enabled in the configuration, a sensor that stays busy is soft reset and recalibrated,
+    /// then measured once more, failing with [`Error::StuckBusy`] if it is still busy. Likewise, a
+    /// sensor reporting itself as uncalibrated, as after a brown-out, is recalibrated and measured
+    /// once more, see [`recalibrations`](Aht20::recalibrations). Readings
+    /// out of range fail with [`Error::OutOfRange`] if [`Config::check_range`] is set.
+    pub fn read(&mut self, delay: &mut impl Delay) -> Result<(Humidity, Temperature), Error<E>> {
+        let (humidity, temperature) = self.read_unchecked(delay)?;
+        if self.config.check_range
+            && (humidity.check_range().is_err() || temperature.check_range().is_err())
+        {
+            return Err(Error::OutOfRange {
+                humidity: humidity.raw(),
+                temperature: temperature.raw(),
+            });
         }
 
-        // Send trigger measurement command
-        self.i2c.write(I2C_ADDRESS, &[0xAC, 0x33, 0x00])?;
+        Ok((humidity, temperature))
+    }
 
+    /// Reads humidity and temperature like [`read`](Self::read), without the range check.
+    fn read_unchecked(
+        &mut self,
+        delay: &mut impl Delay,
+    ) -> Result<(Humidity, Temperature), Error<E>> {
+        if !self.initialized {
+            return Err(Error::NotInitialized);
+        }
+        if self.poisoned {
+            return Err(Error::NeedsRecovery);
+        }
 
-        
-        // Wait until not busy or max tries exceeded
-        let mut max_tries = 5u8;
-        while self.status()?.contains(StatusFlags::BUSY) || max_tries == 0 {
-            delay.delay_ms(10);
-            max_tries -= 1;
+        let result = self.measure_recovering(delay);
+        self.poison_if_fatal(result)
+    }
+
+    /// Measures, retrying and recovering the sensor as set in the configuration.
+    fn measure_recovering(
+        &mut self,
+        delay: &mut impl Delay,
+    ) -> Result<(Humidity, Temperature), Error<E>> {
+        let mut retries = self.config.checksum_retries;
+        let mut recovered = false;
+        let mut recalibrated = false;
+        loop {
+            match self.measure(delay) {
+                Err(Error::Checksum { .. }) if retries > 0 => retries -= 1,
+                Err(Error::Uncalibrated) if self.config.recover_uncalibrated && !recalibrated => {
+                    recalibrated = true;
+                    self.reset_and_recalibrate(delay)
+                        .inspect_err(|_| self.poisoned = true)?;
+                    self.recalibrations = self.recalibrations.wrapping_add(1);
+                }
+                Err(Error::MaxTriesExceeded) if self.config.recover_stuck_busy => {
+                    if recovered {
+                        return Err(Error::StuckBusy);
+                    }
+                    recovered = true;
+                    self.reset_and_recalibrate(delay)
+                        .inspect_err(|_| self.poisoned = true)?;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// Triggers a single measurement and waits for its result.
+    fn measure(&mut self, delay: &mut impl Delay) -> Result<(Humidity, Temperature), Error<E>> {
+        self.wait_min_interval(delay);
+        self.trigger(delay)?;
+
+        // Wait for the conversion to be nearly done before polling
+        delay.delay_ms(self.config.initial_wait_ms);
+
+        if self.polls_in_frame() {
+            // Fetch whole frames and check their status byte, so that status and data are read in
+            // the same transaction
+            let mut poll = 0;
+            loop {
+                match self.fetch_measurement(delay) {
+                    Err(Error::Busy) if poll < self.config.measurement_max_polls => {
+                        self.poll_delay(delay, poll, self.config.measurement_poll_interval_us);
+                        poll += 1;
+                    }
+                    Err(Error::Busy) => return Err(Error::MaxTriesExceeded),
+                    result => return result,
+                }
+            }
+        } else {
+            // Wait until not busy or max tries exceeded
+            let mut poll = 0;
+            while !self.poll_ready(delay)? {
+                if poll == self.config.measurement_max_polls {
+                    return Err(Error::MaxTriesExceeded);
+                }
+                self.poll_delay(delay, poll, self.config.measurement_poll_interval_us);
+                poll += 1;
+            }
+
+            self.fetch_measurement(delay)
         }
+    }
+
+    /// Reads humidity and temperature, giving up once `timeout_ms` have been spent waiting.
+    ///
+    /// The poll interval is shortened for small budgets, so that at least four polls fit in.
+    /// Fails with [`Error::Timeout`] carrying the time actually waited.
+    pub fn read_with_timeout(
+        &mut self,
+        delay: &mut impl Delay,
+        timeout_ms: u16,
+    ) -> Result<(Humidity, Temperature), Error<E>> {
+        self.wait_min_interval(delay);
+        self.trigger(delay)?;
+
+        let result = self.fetch_within(delay, timeout_ms);
+        self.poison_if_fatal(result)
+    }
+
+    /// Polls for a triggered measurement until it is done or `timeout_ms` have been spent.
+    fn fetch_within<D: Delay>(
+        &mut self,
+        delay: &mut D,
+        timeout_ms: u16,
+    ) -> Result<(Humidity, Temperature), Error<E>> {
+        let budget_us = timeout_ms as u32 * 1000;
+        let mut interval_us = self
+            .config
+            .measurement_poll_interval_us
+            .min(budget_us / 4)
+            .max(1);
 
-        if max_tries == 0 {
-            return Err(Error::MaxTriesExceeded);
+        // Delays without microsecond resolution wait whole milliseconds anyway
+        if !D::MICROS {
+            interval_us = interval_us.div_ceil(1000) * 1000;
         }
+        let mut waited_us = 0;
+        loop {
+            match self.fetch_measurement(delay) {
+                Err(Error::Busy) if waited_us < budget_us => {
+                    let us = interval_us.min(budget_us - waited_us);
+                    delay.delay_us(us);
+                    waited_us += us;
+                }
+                Err(Error::Busy) => {
+                    return Err(Error::Timeout {
+                        waited_ms: (waited_us / 1000) as u16,
+                    })
+                }
+                result => return result,
+            }
+        }
+    }
 
-        // Read in sensor data
-        let buf = &mut [0u8; 7];
-        self.i2c.write_read(I2C_ADDRESS, &[0u8], buf)?;
-
-        // Check for CRC mismatch
-        let crc = &mut 0u8;
-        CRC.init_crc(crc);
-        if CRC.update_crc(crc, &buf[..=5]) != buf[6] {
-            return Err(Error::Checksum);
-        };
+    /// Reads humidity and temperature after a single fixed wait, without polling the status.
+    ///
+    /// The wait is set in the configuration, defaulting to the datasheet's 80ms, so each reading
+    /// takes exactly two transactions. Fails with [`Error::Busy`] if the sensor is not done yet.
+    pub fn read_fixed_delay(
+        &mut self,
+        delay: &mut impl Delay,
+    ) -> Result<(Humidity, Temperature), Error<E>> {
+        self.wait_min_interval(delay);
+        self.trigger(delay)?;
+        delay.delay_ms(self.config.fixed_delay_ms);
+        self.fetch_measurement(delay)
+    }
+
+    /// Returns an iterator taking a reading with [`read`](Self::read), then waiting
+    /// `interval_ms` before the next one.
+    ///
+    /// Errors are yielded like readings, so the caller decides whether to carry on.
+    pub fn readings<'a, D: Delay>(
+        &'a mut self,
+        delay: &'a mut D,
+        interval_ms: u16,
+    ) -> Readings<'a, I2C, D, P> {
+        Readings::new(self, delay, interval_ms)
+    }
+
+    /// Drains measurements buffered in the sensor's FIFO into `out`, returning how many were read.
+    ///
+    /// Frames are read until one reports the FIFO empty, which holds no measurement, or until
+    /// `out` is full. Each frame's CRC is checked as configured in [`Config::crc`]. Fails with
+    /// [`Error::Unsupported`] on models without a FIFO, see [`enable_fifo`](Self::enable_fifo).
+    ///
+    /// A failure part way, such as a checksum mismatch, notes how many readings were already
+    /// drained into `out` in the [`FifoError`].
+    pub fn read_fifo(&mut self, out: &mut [Reading]) -> Result<usize, FifoError<E>> {
+        let fail = |read| move |error| FifoError { read, error };
+        self.check_fifo().map_err(fail(0))?;
+
+        let buf = &mut [0u8; frame::FRAME_LEN];
+        for (count, slot) in out.iter_mut().enumerate() {
+            self.read_frame(buf, &mut NoDelay)
+                .map_err(|e| fail(count)(Error::Bus(e)))?;
+            if Status::from_bits(buf[0]).fifo().empty {
+                return Ok(count);
+            }
+
+            let (humidity, temperature) = self.decode(buf).map_err(fail(count))?;
+            *slot = Reading {
+                humidity,
+                temperature,
+                crc_verified: self.crc_verified,
+            };
+        }
+        Ok(out.len())
+    }
+
+    /// Reads humidity and temperature, like [`read`](Self::read), as a [`Reading`].
+    pub fn read_reading(&mut self, delay: &mut impl Delay) -> Result<Reading, Error<E>> {
+        let (humidity, temperature) = self.read(delay)?;
+        Ok(Reading {
+            humidity,
+            temperature,
+            crc_verified: self.crc_verified,
+        })
+    }
+
+    /// Reads the temperature, from a full measurement.
+    pub fn read_temperature(&mut self, delay: &mut impl Delay) -> Result<Temperature, Error<E>> {
+        self.read(delay).map(|(_, t)| t)
+    }
+
+    /// Reads the humidity, from a full measurement.
+    pub fn read_humidity(&mut self, delay: &mut impl Delay) -> Result<Humidity, Error<E>> {
+        self.read(delay).map(|(h, _)| h)
+    }
+
+    /// Reads humidity and temperature, giving up once `timeout_ms` have elapsed.
+    ///
+    /// `now` returns a monotonic millisecond timestamp, which may wrap. The sensor is polled every
+    /// poll interval, see [`set_poll_interval_us`](Self::set_poll_interval_us), or following the
+    /// poll schedule. Fails with [`Error::Timeout`] carrying the time waited if the sensor stays
+    /// busy.
+    pub fn read_until(
+        &mut self,
+        delay: &mut impl Delay,
+        mut now: impl FnMut() -> u32,
+        timeout_ms: u32,
+    ) -> Result<(Humidity, Temperature), Error<E>> {
+        self.wait_min_interval(delay);
+        let start = now();
+        self.trigger(delay)?;
 
-        // Check calibration
-        let status = StatusFlags { bits: buf[0] };
+        let buf = &mut [0u8; frame::FRAME_LEN];
+        let mut waited_ms = 0;
+        let result = self
+            .fetch_when_ready(delay, buf, || {
+                waited_ms = now().wrapping_sub(start);
+                waited_ms >= timeout_ms
+            })
+            .map_err(|e| e.timed_out(waited_ms));
+        self.poison_if_fatal(result)?;
+
+        self.measuring = false;
+        self.decode(buf)
+    }
+
+    /// Checks that the sensor is calibrated and not busy, with a single status read.
+    fn confirm_idle(&mut self, delay: &mut impl Delay) -> Result<(), Error<E>> {
+        let status = self.status(delay)?;
+        if status.contains(StatusFlags::BUSY) {
+            return Err(Error::StuckBusy);
+        }
         if !status.contains(StatusFlags::CALIBRATION_ENABLE) {
             return Err(Error::Uncalibrated);
         }
 
-        // Extract humitidy and temperature values from data
-        let hum = ((buf[1] as u32) << 12) | ((buf[2] as u32) << 4) | ((buf[3] as u32) >> 4);
-        let temp = (((buf[3] as u32) & 0x0f) << 16) | ((buf[4] as u32) << 8) | (buf[5] as u32);
+        self.calibrated = true;
+        Ok(())
+    }
 
-        Ok((Humidity { h: hum }, Temperature { t: temp }))
+    /// Poisons the driver if `result` is a fatal error, see [`Error::is_fatal`].
+    fn poison_if_fatal<T>(&mut self, result: Result<T, Error<E>>) -> Result<T, Error<E>> {
+        if let Err(e) = &result {
+            self.poisoned |= e.is_fatal();
+        }
+        result
     }
-}
 
+    /// Waits for a triggered measurement and reads its frame into `buf`.
+    ///
+    /// The sensor is polled every poll interval, or following the poll schedule, until it is
+    /// ready, or until `expired` returns true.
+    fn fetch_when_ready(
+        &mut self,
+        delay: &mut impl Delay,
+        buf: &mut [u8; frame::FRAME_LEN],
+        mut expired: impl FnMut() -> bool,
+    ) -> Result<(), Error<E>> {
+        let mut poll = 0;
+        loop {
+            let busy = if self.polls_in_frame() {
+                match self.read_frame(buf, delay) {
+                    Ok(()) => StatusFlags { bits: buf[0] }.contains(StatusFlags::BUSY),
+                    Err(e) if self.i2c.is_address_nack(&e) => true,
+                    Err(e) => return Err(Error::Bus(e)),
+                }
+            } else {
+                !self.poll_ready(delay)?
+            };
+            if !busy {
+                break;
+            }
+
+            if expired() {
+                return Err(Error::MaxTriesExceeded);
+            }
+            self.poll_delay(delay, poll, self.config.measurement_poll_interval_us);
+            poll += 1;
+        }
+
+        // Read in sensor data
+        if !self.polls_in_frame() {
+            self.read_frame(buf, delay)?;
+        }
+
+        Ok(())
+    }
+
+    /// Waits out the remainder of the minimum interval, if the policy is to block.
+    ///
+    /// The interval is re-checked and waited out once more in case the delay ran short of the
+    /// clock, but no further, so a stalled clock cannot hang the driver: the trigger then fails
+    /// with [`Error::TooSoon`].
+    fn wait_min_interval(&mut self, delay: &mut impl Delay) {
+        if self.config.min_interval_policy != MinIntervalPolicy::Block {
+            return;
+        }
+
+        for _ in 0..2 {
+            let ms = self.interval_remaining_ms();
+            if ms == 0 {
+                break;
+            }
+            delay.delay_ms(ms.min(u16::MAX as u32) as u16);
+        }
+    }
+
+    /// Fails with [`Error::TooSoon`] until the minimum interval has elapsed.
+    fn check_min_interval(&self) -> Result<(), Error<E>> {
+        match self.interval_remaining_ms() {
+            0 => Ok(()),
+            remaining_ms => Err(Error::TooSoon { remaining_ms }),
+        }
+    }
+
+    /// Triggers a measurement, to be fetched with [`read_measurement`](Self::read_measurement).
+    ///
+    /// Fails with [`Error::TooSoon`] if the configured minimum interval since the previous
+    /// measurement has not elapsed, whatever the policy, as there is no delay to wait with.
+    pub fn trigger_measurement(&mut self) -> Result<(), Error<E>> {
+        self.trigger(&mut NoDelay)
+    }
+
+    /// Triggers a measurement, waiting `delay` between bus retries.
+    fn trigger(&mut self, delay: &mut impl Delay) -> Result<(), Error<E>> {
+        if !self.initialized {
+            return Err(Error::NotInitialized);
+        }
+        if self.poisoned {
+            return Err(Error::NeedsRecovery);
+        }
+        self.check_min_interval()?;
+
+        // Send trigger measurement command
+        self.measuring = false;
+        self.write(self.command(Command::TriggerMeasurement), delay)?;
+        self.measuring = true;
+        self.last_trigger_ms = self.clock.map(|now| now());
+
+        Ok(())
+    }
+
+    /// Checks whether the sensor is done measuring, with a single status read.
+    ///
+    /// Sensors NACKing their address while measuring are reported as not ready, as classified by
+    /// [`Bus::is_address_nack`].
+    pub fn is_ready(&mut self) -> Result<bool, Error<E>> {
+        self.poll_ready(&mut NoDelay)
+    }
+
+    /// Checks whether the sensor is done measuring, waiting `delay` between bus retries.
+    fn poll_ready(&mut self, delay: &mut impl Delay) -> Result<bool, Error<E>> {
+        match self.status(delay) {
+            Ok(status) => Ok(!status.contains(StatusFlags::BUSY)),
+            Err(e) if self.i2c.is_address_nack(&e) => Ok(false),
+            Err(e) => Err(Error::Bus(e)),
+        }
+    }
+
+    /// Fetches and decodes the result of a triggered measurement, in a single transaction.
+    ///
+    /// Fails with [`Error::Busy`] while the sensor is still measuring, including when it NACKs its
+    /// address as classified by [`Bus::is_address_nack`].
+    pub fn read_measurement(&mut self) -> Result<(Humidity, Temperature), Error<E>> {
+        self.fetch_measurement(&mut NoDelay)
+    }
+
+    /// Fetches and decodes a triggered measurement, waiting `delay` between bus retries.
+    fn fetch_measurement(
+        &mut self,
+        delay: &mut impl Delay,
+    ) -> Result<(Humidity, Temperature), Error<E>> {
+        // Read in sensor data, the first byte being the status
+        let buf = &mut [0u8; frame::FRAME_LEN];
+        match self.read_frame(buf, delay) {
+            Err(e) if self.i2c.is_address_nack(&e) => return Err(Error::Busy),
+            result => result?,
+        }
+
+        let result = self.decode(buf);
+        if !matches!(result, Err(Error::Busy)) {
+            self.measuring = false;
+        }
+        result
+    }
+
+    /// Triggers a measurement to be collected with [`try_read`](Self::try_read).
+    pub fn start_measurement(&mut self) -> Result<(), Error<E>> {
+        self.trigger_measurement()
+    }
+
+    /// Reads humidity and temperature without blocking.
+    ///
+    /// Returns `WouldBlock` while the sensor is busy. If no measurement is in flight, one is
+    /// started and `WouldBlock` is returned. Each call performs a single I2C transaction.
+    ///
+    /// Starting a measurement before the minimum interval has elapsed returns `WouldBlock` with
+    /// [`MinIntervalPolicy::Block`], and [`Error::TooSoon`] with [`MinIntervalPolicy::Reject`].
+    pub fn try_read(&mut self) -> nb::Result<(Humidity, Temperature), Error<E>> {
+        if !self.measuring {
+            match self.start_measurement() {
+                Err(Error::TooSoon { .. })
+                    if self.config.min_interval_policy == MinIntervalPolicy::Block => {}
+                result => result?,
+            }
+            return Err(nb::Error::WouldBlock);
+        }
+
+        match self.read_measurement() {
+            Err(Error::Busy) => Err(nb::Error::WouldBlock),
+            result => result.map_err(nb::Error::Other),
+        }
+    }
+}
@@ -0,0 +1,72 @@
+//! Floating point functions missing from `core`, backed by `libm` or `micromath`.
+//!
+//! `libm` is preferred when both features are enabled, being the more accurate of the two.
+
+/// Arctangent, in radians.
+#[cfg(feature = "libm")]
+pub(crate) fn atan(x: f32) -> f32 {
+    libm::atanf(x)
+}
+
+/// Arctangent, in radians.
+#[cfg(all(feature = "micromath", not(feature = "libm")))]
+pub(crate) fn atan(x: f32) -> f32 {
+    micromath::F32Ext::atan(x)
+}
+
+/// Exponential function.
+#[cfg(feature = "libm")]
+pub(crate) fn exp(x: f32) -> f32 {
+    libm::expf(x)
+}
+
+/// Exponential function.
+#[cfg(all(feature = "micromath", not(feature = "libm")))]
+pub(crate) fn exp(x: f32) -> f32 {
+    micromath::F32Ext::exp(x)
+}
+
+/// Square root.
+#[cfg(feature = "libm")]
+pub(crate) fn sqrt(x: f32) -> f32 {
+    libm::sqrtf(x)
+}
+
+/// Square root.
+#[cfg(all(feature = "micromath", not(feature = "libm")))]
+pub(crate) fn sqrt(x: f32) -> f32 {
+    micromath::F32Ext::sqrt(x)
+}
+
+/// Natural logarithm.
+#[cfg(feature = "libm")]
+pub(crate) fn ln(x: f32) -> f32 {
+    libm::logf(x)
+}
+
+/// Natural logarithm.
+///
+/// `micromath`'s own logarithm is only accurate to about 0.1, so it is refined with two Newton
+/// steps on its much more accurate exponential.
+#[cfg(all(feature = "micromath", not(feature = "libm")))]
+pub(crate) fn ln(x: f32) -> f32 {
+    use micromath::F32Ext;
+
+    let mut y = F32Ext::ln(x);
+    for _ in 0..2 {
+        y += x / F32Ext::exp(y) - 1.0;
+    }
+    y
+}
+
+/// Exponential function, in double precision.
+#[cfg(feature = "libm")]
+pub(crate) fn exp_f64(x: f64) -> f64 {
+    libm::exp(x)
+}
+
+/// Natural logarithm, in double precision.
+#[cfg(feature = "libm")]
+pub(crate) fn ln_f64(x: f64) -> f64 {
+    libm::log(x)
+}
@@ -0,0 +1,342 @@
+//! Quantities derived from a humidity and temperature reading.
+//!
+//! Requires the `libm` or `micromath` feature. With `libm`, the Magnus formula based quantities
+//! are also available in double precision, computed from the raw readings without going through
+//! `f32`. The empirical fits, such as the heat index, are not accurate enough to warrant it.
+
+use crate::{math, psychrometrics::STANDARD_PRESSURE_PA, Humidity, Temperature};
+
+/// Magnus coefficient `b` over water, dimensionless (Sonntag 1990).
+const MAGNUS_B_WATER: f64 = 17.62;
+/// Magnus coefficient `c` over water, in degrees Celsius (Sonntag 1990).
+const MAGNUS_C_WATER: f64 = 243.12;
+/// Magnus coefficient `b` over ice, dimensionless (Sonntag 1990).
+const MAGNUS_B_ICE: f64 = 22.46;
+/// Magnus coefficient `c` over ice, in degrees Celsius (Sonntag 1990).
+const MAGNUS_C_ICE: f64 = 272.62;
+
+/// Magnus saturation vapor pressure at 0°C, in pascals (Sonntag 1990).
+const MAGNUS_A: f64 = 611.2;
+
+/// Molar mass of water, in grams per mole.
+const WATER_MOLAR_MASS: f64 = 18.015_28;
+/// Molar gas constant, in joules per mole and kelvin.
+const GAS_CONSTANT: f64 = 8.314_462;
+
+/// Lowest relative humidity used in logarithms, in percent, keeping results finite.
+const MIN_RH: f64 = 0.01;
+
+/// Dew point, in degrees Celsius.
+///
+/// Uses the Magnus-Tetens approximation over water with Sonntag's coefficients, accurate to
+/// within 0.35°C between -45°C and 60°C. Relative humidity is clamped to between 0.01% and 100%,
+/// so that dry air yields a very low but finite dew point rather than negative infinity.
+///
+/// Below freezing, moisture condenses as frost at the higher [`frost_point`] instead.
+pub fn dew_point(humidity: &Humidity, temperature: &Temperature) -> f32 {
+    magnus(humidity, temperature, MAGNUS_B_WATER, MAGNUS_C_WATER)
+}
+
+/// Dew point, in degrees Celsius, in double precision. See [`dew_point`].
+#[cfg(feature = "libm")]
+pub fn dew_point_f64(humidity: &Humidity, temperature: &Temperature) -> f64 {
+    magnus_f64(humidity, temperature, MAGNUS_B_WATER, MAGNUS_C_WATER)
+}
+
+/// Dew point as a `uom` quantity. See [`dew_point`].
+#[cfg(feature = "uom")]
+pub fn dew_point_uom(
+    humidity: &Humidity,
+    temperature: &Temperature,
+) -> uom::si::f32::ThermodynamicTemperature {
+    use uom::si::{f32::ThermodynamicTemperature, thermodynamic_temperature::degree_celsius};
+
+    ThermodynamicTemperature::new::<degree_celsius>(dew_point(humidity, temperature))
+}
+
+/// Frost point, in degrees Celsius.
+///
+/// The temperature at which the air is saturated over ice, using the Magnus-Tetens
+/// approximation over ice with Sonntag's coefficients, valid between -65°C and 0°C. Prefer it to
+/// [`dew_point`] below freezing, e.g. in freezers, where the over-water dew point reads up to a
+/// few degrees low. Relative humidity is clamped as for [`dew_point`].
+pub fn frost_point(humidity: &Humidity, temperature: &Temperature) -> f32 {
+    magnus(humidity, temperature, MAGNUS_B_ICE, MAGNUS_C_ICE)
+}
+
+/// Frost point, in degrees Celsius, in double precision. See [`frost_point`].
+#[cfg(feature = "libm")]
+pub fn frost_point_f64(humidity: &Humidity, temperature: &Temperature) -> f64 {
+    magnus_f64(humidity, temperature, MAGNUS_B_ICE, MAGNUS_C_ICE)
+}
+
+/// Frost point below 0°C and dew point otherwise, in degrees Celsius.
+pub fn dew_or_frost_point(humidity: &Humidity, temperature: &Temperature) -> f32 {
+    if temperature.celsius() < 0.0 {
+        frost_point(humidity, temperature)
+    } else {
+        dew_point(humidity, temperature)
+    }
+}
+
+/// Absolute humidity, in grams of water vapor per cubic meter.
+///
+/// Computed from the vapor pressure, the Magnus saturation vapor pressure over water scaled by
+/// the relative humidity, with the ideal gas law. Unlike relative humidity, it can be compared
+/// between air at different temperatures, e.g. indoors and outdoors.
+///
+/// Assumes standard sea level pressure, see [`absolute_humidity_at`].
+pub fn absolute_humidity(humidity: &Humidity, temperature: &Temperature) -> f32 {
+    absolute_humidity_at(humidity, temperature, STANDARD_PRESSURE_PA)
+}
+
+/// Absolute humidity at barometric pressure `pressure_pa`, in grams of water vapor per cubic
+/// meter.
+///
+/// The pressure only enters through the enhancement factor of water vapor in air, so results
+/// differ from [`absolute_humidity`] by a fraction of a percent even at altitude.
+pub fn absolute_humidity_at(
+    humidity: &Humidity,
+    temperature: &Temperature,
+    pressure_pa: f32,
+) -> f32 {
+    let vapor_pressure_pa = vapor_pressure_at_pa(humidity, temperature, pressure_pa);
+    vapor_pressure_pa * WATER_MOLAR_MASS as f32 / (GAS_CONSTANT as f32 * temperature.kelvin())
+}
+
+/// Absolute humidity, in grams of water vapor per cubic meter, in double precision. See
+/// [`absolute_humidity`].
+#[cfg(feature = "libm")]
+pub fn absolute_humidity_f64(humidity: &Humidity, temperature: &Temperature) -> f64 {
+    let enhancement = enhancement_factor_f64(STANDARD_PRESSURE_PA as f64);
+    let vapor_pressure_pa = enhancement * vapor_pressure_pa_f64(humidity, temperature);
+    let kelvin = temperature.celsius_f64() + 273.15;
+    vapor_pressure_pa * WATER_MOLAR_MASS / (GAS_CONSTANT * kelvin)
+}
+
+/// Absolute humidity as a `uom` quantity. See [`absolute_humidity`].
+#[cfg(feature = "uom")]
+pub fn absolute_humidity_uom(
+    humidity: &Humidity,
+    temperature: &Temperature,
+) -> uom::si::f32::MassDensity {
+    use uom::si::{f32::MassDensity, mass_density::gram_per_cubic_meter};
+
+    MassDensity::new::<gram_per_cubic_meter>(absolute_humidity(humidity, temperature))
+}
+
+/// Risk of moisture condensing on a surface, see [`condensation_risk`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum CondensationRisk {
+    /// The surface is warmer than the dew point by more than the margin.
+    None,
+    /// The surface is within the margin above the dew point.
+    Approaching,
+    /// The surface is at or below the dew point, so moisture condenses on it.
+    Condensing,
+}
+
+/// Risk of the air's moisture condensing on a surface at `surface_celsius`, such as a cold water
+/// pipe or a window pane.
+///
+/// Moisture condenses once the surface is at or below the [`dew_point`] of the air, or its
+/// [`frost_point`] for surfaces below 0°C, where it deposits as frost. Surfaces less than
+/// `margin_celsius` warmer than that are [`Approaching`](CondensationRisk::Approaching), leaving
+/// room for the dew point's own error and for the surface cooling further.
+pub fn condensation_risk(
+    humidity: &Humidity,
+    temperature: &Temperature,
+    surface_celsius: f32,
+    margin_celsius: f32,
+) -> CondensationRisk {
+    let saturation_celsius = if surface_celsius < 0.0 {
+        frost_point(humidity, temperature)
+    } else {
+        dew_point(humidity, temperature)
+    };
+
+    if surface_celsius <= saturation_celsius {
+        CondensationRisk::Condensing
+    } else if surface_celsius <= saturation_celsius + margin_celsius {
+        CondensationRisk::Approaching
+    } else {
+        CondensationRisk::None
+    }
+}
+
+/// Heat index, the "feels like" temperature in hot and humid air, in degrees Celsius.
+///
+/// Follows the US National Weather Service: Steadman's simple formula is used first, and only if
+/// its result averaged with the temperature reaches 80°F (26.7°C) is it replaced by the Rothfusz
+/// regression, with the NWS adjustments for humidities below 13% and above 85%. The regression is
+/// fitted on the NWS heat index chart, so results well above 50°C are extrapolated.
+pub fn heat_index(humidity: &Humidity, temperature: &Temperature) -> f32 {
+    let t = temperature.fahrenheit();
+    let rh = humidity.rh().clamp(0.0, 100.0);
+
+    let simple = 0.5 * (t + 61.0 + (t - 68.0) * 1.2 + rh * 0.094);
+    let hi = if (simple + t) / 2.0 < 80.0 {
+        simple
+    } else {
+        let mut hi = -42.379 + 2.049_015_2 * t + 10.143_331 * rh
+            - 0.224_755_4 * t * rh
+            - 0.006_837_83 * t * t
+            - 0.054_817_17 * rh * rh
+            + 0.001_228_74 * t * t * rh
+            + 0.000_852_82 * t * rh * rh
+            - 0.000_001_99 * t * t * rh * rh;
+        if rh < 13.0 && (80.0..=112.0).contains(&t) {
+            hi -= (13.0 - rh) / 4.0 * math::sqrt((17.0 - (t - 95.0).abs()) / 17.0);
+        } else if rh > 85.0 && (80.0..=87.0).contains(&t) {
+            hi += (rh - 85.0) / 10.0 * ((87.0 - t) / 5.0);
+        }
+        hi
+    };
+
+    (hi - 32.0) / 1.8
+}
+
+/// Humidex, the Canadian "feels like" temperature in hot and humid air, in degrees Celsius.
+///
+/// Uses Environment Canada's formula on the vapor pressure, which it derives from the dew point,
+/// so it is computed here with the same Magnus formula as [`dew_point`]. Values below the
+/// temperature, in dry air, are clamped to the temperature.
+pub fn humidex(humidity: &Humidity, temperature: &Temperature) -> f32 {
+    let t = temperature.celsius();
+    let vapor_pressure_hpa = vapor_pressure_pa(humidity, temperature) / 100.0;
+    let humidex = t + 5.0 / 9.0 * (vapor_pressure_hpa - 10.0);
+    humidex.max(t)
+}
+
+/// Vapor pressure deficit, in kilopascals.
+///
+/// The difference between the saturation vapor pressure at the temperature and the actual vapor
+/// pressure, i.e. how much more moisture the air can take up, as used in greenhouse control.
+pub fn vpd_kpa(humidity: &Humidity, temperature: &Temperature) -> f32 {
+    vpd_leaf_kpa(humidity, temperature, temperature.celsius())
+}
+
+/// Vapor pressure deficit, in kilopascals, in double precision. See [`vpd_kpa`].
+#[cfg(feature = "libm")]
+pub fn vpd_kpa_f64(humidity: &Humidity, temperature: &Temperature) -> f64 {
+    let saturation = saturation_vapor_pressure_pa_f64(temperature.celsius_f64());
+    (saturation - vapor_pressure_pa_f64(humidity, temperature)) / 1000.0
+}
+
+/// Leaf vapor pressure deficit, in kilopascals.
+///
+/// Like [`vpd_kpa`], with the saturation vapor pressure taken at the leaf temperature
+/// `leaf_celsius` instead, the air reading giving the actual vapor pressure. Leaves are usually
+/// a degree or two cooler than the air.
+pub fn vpd_leaf_kpa(humidity: &Humidity, air: &Temperature, leaf_celsius: f32) -> f32 {
+    (saturation_vapor_pressure_pa(leaf_celsius) - vapor_pressure_pa(humidity, air)) / 1000.0
+}
+
+/// Wet-bulb temperature, in degrees Celsius, or `None` outside Stull's validity range.
+///
+/// Uses Stull's 2011 empirical fit at sea level pressure, accurate to within -1°C to +0.65°C for
+/// relative humidities between 5% and 99% and temperatures between -20°C and 50°C. Readings
+/// outside that range return `None` rather than an extrapolated value.
+pub fn wet_bulb(humidity: &Humidity, temperature: &Temperature) -> Option<f32> {
+    let t = temperature.celsius();
+    let rh = humidity.rh();
+    if !(5.0..=99.0).contains(&rh) || !(-20.0..=50.0).contains(&t) {
+        return None;
+    }
+
+    Some(
+        t * math::atan(0.151_977 * math::sqrt(rh + 8.313_659)) + math::atan(t + rh)
+            - math::atan(rh - 1.676_331)
+            + 0.003_918_38 * rh * math::sqrt(rh) * math::atan(0.023_101 * rh)
+            - 4.686_035,
+    )
+}
+
+/// Saturation vapor pressure over water at `celsius`, in pascals.
+///
+/// Uses the Magnus formula with Sonntag's 1990 coefficients, `611.2 * exp(17.62 * t / (243.12 +
+/// t))`, which is within 0.3% of steam table values from -20°C to 50°C. Below freezing it is the
+/// pressure over supercooled water, the reference for relative humidity. All quantities in this
+/// module and [`psychrometrics`](crate::psychrometrics) derive from it.
+pub fn saturation_vapor_pressure_pa(celsius: f32) -> f32 {
+    magnus_pressure(celsius, MAGNUS_B_WATER, MAGNUS_C_WATER)
+}
+
+/// Saturation vapor pressure over ice at `celsius`, in pascals.
+///
+/// Uses the Magnus formula with Sonntag's 1990 coefficients over ice, `611.2 * exp(22.46 * t /
+/// (272.62 + t))`, valid from -65°C to 0°C.
+pub fn saturation_vapor_pressure_ice_pa(celsius: f32) -> f32 {
+    magnus_pressure(celsius, MAGNUS_B_ICE, MAGNUS_C_ICE)
+}
+
+/// Magnus saturation vapor pressure at `celsius` with coefficients `b` and `c`, in pascals.
+fn magnus_pressure(celsius: f32, b: f64, c: f64) -> f32 {
+    MAGNUS_A as f32 * math::exp(b as f32 * celsius / (c as f32 + celsius))
+}
+
+/// Saturation vapor pressure over water at `celsius`, in pascals, in double precision.
+#[cfg(feature = "libm")]
+fn saturation_vapor_pressure_pa_f64(celsius: f64) -> f64 {
+    MAGNUS_A * math::exp_f64(MAGNUS_B_WATER * celsius / (MAGNUS_C_WATER + celsius))
+}
+
+/// Vapor pressure of the reading, in pascals.
+pub(crate) fn vapor_pressure_pa(humidity: &Humidity, temperature: &Temperature) -> f32 {
+    saturation_vapor_pressure_pa(temperature.celsius()) * humidity.rh() / 100.0
+}
+
+/// Vapor pressure of the reading in air at barometric pressure `pressure_pa`, in pascals.
+pub(crate) fn vapor_pressure_at_pa(
+    humidity: &Humidity,
+    temperature: &Temperature,
+    pressure_pa: f32,
+) -> f32 {
+    enhancement_factor(pressure_pa) * vapor_pressure_pa(humidity, temperature)
+}
+
+/// Enhancement factor of water vapor in air at `pressure_pa`, as given by the WMO.
+///
+/// Saturation vapor pressure in moist air is this much higher than over pure water, by about
+/// 0.5% at sea level.
+fn enhancement_factor(pressure_pa: f32) -> f32 {
+    let hpa = pressure_pa / 100.0;
+    1.0016 + 3.15e-6 * hpa - 0.074 / hpa
+}
+
+/// Enhancement factor of water vapor in air, like [`enhancement_factor`], in double precision.
+#[cfg(feature = "libm")]
+fn enhancement_factor_f64(pressure_pa: f64) -> f64 {
+    let hpa = pressure_pa / 100.0;
+    1.0016 + 3.15e-6 * hpa - 0.074 / hpa
+}
+
+/// Vapor pressure of the reading, in pascals, in double precision.
+#[cfg(feature = "libm")]
+fn vapor_pressure_pa_f64(humidity: &Humidity, temperature: &Temperature) -> f64 {
+    saturation_vapor_pressure_pa_f64(temperature.celsius_f64()) * humidity.rh_f64() / 100.0
+}
+
+/// Temperature at which the reading's vapor pressure saturates the air, inverting the Magnus
+/// formula with coefficients `b` and `c`.
+///
+/// Relative humidity is relative to water, as the sensor reports it, so the vapor pressure is
+/// always computed over water.
+fn magnus(humidity: &Humidity, temperature: &Temperature, b: f64, c: f64) -> f32 {
+    let (b, c) = (b as f32, c as f32);
+    let rh = humidity.rh().clamp(MIN_RH as f32, 100.0);
+    let vapor_pressure_pa = saturation_vapor_pressure_pa(temperature.celsius()) * rh / 100.0;
+    let gamma = math::ln(vapor_pressure_pa / MAGNUS_A as f32);
+    c * gamma / (b - gamma)
+}
+
+/// Inverts the Magnus formula like [`magnus`], in double precision.
+#[cfg(feature = "libm")]
+fn magnus_f64(humidity: &Humidity, temperature: &Temperature, b: f64, c: f64) -> f64 {
+    let rh = humidity.rh_f64().clamp(MIN_RH, 100.0);
+    let vapor_pressure_pa =
+        saturation_vapor_pressure_pa_f64(temperature.celsius_f64()) * rh / 100.0;
+    let gamma = math::ln_f64(vapor_pressure_pa / MAGNUS_A);
+    c * gamma / (b - gamma)
+}
@@ -0,0 +1,35 @@
+//! Moist air properties at a given barometric pressure, for HVAC calculations.
+//!
+//! Requires the `libm` or `micromath` feature. Air is treated as an ideal gas mixture, as in the
+//! ASHRAE Handbook, with vapor pressures from [`metrics`] corrected for the enhancement factor at
+//! the given pressure. Pass [`STANDARD_PRESSURE_PA`] when no barometer is available; at altitude
+//! this underestimates the mixing ratio considerably, by about a quarter at 2,500m.
+
+use crate::{metrics, Humidity, Temperature};
+
+/// Standard sea level pressure, in pascals.
+pub const STANDARD_PRESSURE_PA: f32 = 101_325.0;
+
+/// Ratio of the molar masses of water and dry air.
+const MOLAR_MASS_RATIO: f32 = 0.621_945;
+
+/// Humidity ratio, in kilograms of water vapor per kilogram of dry air.
+pub fn mixing_ratio(humidity: &Humidity, temperature: &Temperature, pressure_pa: f32) -> f32 {
+    let vapor_pressure_pa = metrics::vapor_pressure_at_pa(humidity, temperature, pressure_pa);
+    MOLAR_MASS_RATIO * vapor_pressure_pa / (pressure_pa - vapor_pressure_pa)
+}
+
+/// Specific humidity, in kilograms of water vapor per kilogram of moist air.
+pub fn specific_humidity(humidity: &Humidity, temperature: &Temperature, pressure_pa: f32) -> f32 {
+    let w = mixing_ratio(humidity, temperature, pressure_pa);
+    w / (1.0 + w)
+}
+
+/// Specific enthalpy of moist air, in kilojoules per kilogram of dry air.
+///
+/// Relative to dry air and liquid water at 0°C, with constant specific heats.
+pub fn moist_air_enthalpy(humidity: &Humidity, temperature: &Temperature, pressure_pa: f32) -> f32 {
+    let t = temperature.celsius();
+    let w = mixing_ratio(humidity, temperature, pressure_pa);
+    1.006 * t + w * (2501.0 + 1.86 * t)
+}
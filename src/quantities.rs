@@ -0,0 +1,56 @@
+//! Conversions of readings to the quantity types of unit crates.
+
+#[cfg(feature = "uom")]
+use uom::si::{
+    f32::{Ratio, ThermodynamicTemperature},
+    ratio::percent,
+    thermodynamic_temperature::kelvin,
+};
+
+use crate::{Humidity, Temperature};
+
+#[cfg(feature = "uom")]
+impl Temperature {
+    /// Temperature as a `uom` quantity.
+    pub fn to_thermodynamic_temperature(&self) -> ThermodynamicTemperature {
+        ThermodynamicTemperature::new::<kelvin>(self.kelvin())
+    }
+}
+
+#[cfg(feature = "uom")]
+impl Humidity {
+    /// Relative humidity as a `uom` ratio, e.g. `0.452` for 45.2%.
+    pub fn to_ratio(&self) -> Ratio {
+        Ratio::new::<percent>(self.rh())
+    }
+}
+
+#[cfg(feature = "uom")]
+impl core::convert::From<Temperature> for ThermodynamicTemperature {
+    fn from(t: Temperature) -> Self {
+        t.to_thermodynamic_temperature()
+    }
+}
+
+#[cfg(feature = "uom")]
+impl core::convert::From<Humidity> for Ratio {
+    fn from(h: Humidity) -> Self {
+        h.to_ratio()
+    }
+}
+
+/// Converted from the raw reading in double precision.
+#[cfg(feature = "measurements")]
+impl core::convert::From<Temperature> for measurements::Temperature {
+    fn from(t: Temperature) -> Self {
+        measurements::Temperature::from_celsius(t.celsius_f64())
+    }
+}
+
+/// Converted from the raw reading in double precision.
+#[cfg(feature = "measurements")]
+impl core::convert::From<Humidity> for measurements::Humidity {
+    fn from(h: Humidity) -> Self {
+        measurements::Humidity::from_percent(h.rh_f64())
+    }
+}
@@ -0,0 +1,50 @@
+//! Iterator over periodic readings.
+
+use crate::{
+    hal::{Bus, Delay},
+    Aht20, Error, Humidity, Ready, Temperature,
+};
+
+/// Iterator taking a reading, then waiting the interval before the next one.
+///
+/// Returned by [`Aht20::readings`]. Failed readings are yielded as errors and the iterator carries
+/// on, so it never ends on its own.
+pub struct Readings<'a, I2C, D, P> {
+    dev: &'a mut Aht20<I2C, Ready, P>,
+    delay: &'a mut D,
+    interval_ms: u16,
+    started: bool,
+}
+
+impl<'a, I2C, D, P> Readings<'a, I2C, D, P> {
+    pub(crate) fn new(
+        dev: &'a mut Aht20<I2C, Ready, P>,
+        delay: &'a mut D,
+        interval_ms: u16,
+    ) -> Self {
+        Self {
+            dev,
+            delay,
+            interval_ms,
+            started: false,
+        }
+    }
+}
+
+impl<I2C, D, E, P> Iterator for Readings<'_, I2C, D, P>
+where
+    I2C: Bus<Error = E>,
+    D: Delay,
+{
+    type Item = Result<(Humidity, Temperature), Error<E>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // Wait the interval since the previous reading
+        if self.started {
+            self.delay.delay_ms(self.interval_ms);
+        }
+        self.started = true;
+
+        Some(self.dev.read(self.delay))
+    }
+}
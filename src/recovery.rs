@@ -0,0 +1,182 @@
+//! Recovery of a stuck I2C bus or a misbehaving sensor.
+
+use crate::{
+    hal::{BusPin, Delay},
+    Error,
+};
+
+/// Half period of the clock pulses, in microseconds, for a 100kHz clock.
+const HALF_PERIOD_US: u32 = 5;
+
+/// Clock pulses after which a device holding SDA low must have released it.
+const MAX_PULSES: u8 = 9;
+
+/// Outcome of a successful [`bus_clear`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct BusClear {
+    /// Whether SDA was held low when the clear started.
+    pub was_stuck: bool,
+    /// Clock pulses it took for SDA to be released.
+    pub pulses: u8,
+}
+
+/// Bus clear error.
+#[derive(Debug, Copy, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum BusClearError<E> {
+    /// SDA was still held low after nine clock pulses.
+    StillStuck,
+    /// Underlying pin error.
+    Pin(E),
+}
+
+impl<E> core::convert::From<E> for BusClearError<E> {
+    fn from(e: E) -> Self {
+        BusClearError::Pin(e)
+    }
+}
+
+/// Clears an I2C bus whose SDA line is held low by a device interrupted mid-transaction.
+///
+/// SCL is pulsed up to nine times until SDA is released, then a stop condition is issued. The
+/// pins must be switched from the I2C peripheral to open-drain GPIOs beforehand, and the sensor
+/// initialized again afterwards. Delays without microsecond resolution slow the clock down, which
+/// devices tolerate.
+pub fn bus_clear<SCL, SDA, E>(
+    scl: &mut SCL,
+    sda: &mut SDA,
+    delay: &mut impl Delay,
+) -> Result<BusClear, BusClearError<E>>
+where
+    SCL: BusPin<Error = E>,
+    SDA: BusPin<Error = E>,
+{
+    // Release both lines
+    sda.set_high()?;
+    scl.set_high()?;
+    delay.delay_us(HALF_PERIOD_US);
+
+    if sda.is_high()? {
+        return Ok(BusClear {
+            was_stuck: false,
+            pulses: 0,
+        });
+    }
+
+    // Clock the device until it releases SDA
+    let mut pulses = 0;
+    while !sda.is_high()? {
+        if pulses == MAX_PULSES {
+            return Err(BusClearError::StillStuck);
+        }
+        scl.set_low()?;
+        delay.delay_us(HALF_PERIOD_US);
+        scl.set_high()?;
+        delay.delay_us(HALF_PERIOD_US);
+        pulses += 1;
+    }
+
+    // Issue a stop condition, SDA rising while SCL is high
+    scl.set_low()?;
+    delay.delay_us(HALF_PERIOD_US);
+    sda.set_low()?;
+    delay.delay_us(HALF_PERIOD_US);
+    scl.set_high()?;
+    delay.delay_us(HALF_PERIOD_US);
+    sda.set_high()?;
+    delay.delay_us(HALF_PERIOD_US);
+
+    Ok(BusClear {
+        was_stuck: true,
+        pulses,
+    })
+}
+
+/// Outcome of a successful [`Aht20::recover`](crate::Aht20::recover).
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct RecoveryReport {
+    /// Whether the sensor had lost its calibration and was initialized again.
+    pub reinitialized: bool,
+    /// Whether a measurement was taken and discarded, see
+    /// [`Config::flush_on_recover`](crate::Config::flush_on_recover).
+    pub flushed: bool,
+    /// Time spent in delays during the recovery, in milliseconds.
+    pub delay_ms: u32,
+}
+
+/// Step of [`Aht20::recover`](crate::Aht20::recover), in the order they are taken.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum RecoveryStep {
+    /// Soft resetting the sensor.
+    Reset,
+    /// Running the datasheet initialization, calibrating the sensor unless it already is.
+    Initialize,
+    /// Confirming the sensor reports itself as calibrated.
+    Verify,
+    /// Taking and discarding a measurement.
+    Flush,
+}
+
+/// Sensor recovery error, noting the step that failed.
+///
+/// Converts into [`Error`] with `?` for callers that don't need the step.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct RecoveryError<E> {
+    /// The step that failed.
+    pub step: RecoveryStep,
+    /// The error that caused the step to fail.
+    pub error: Error<E>,
+}
+
+impl<E: core::fmt::Debug> core::fmt::Display for RecoveryError<E> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "recovery failed at {:?} step: {}", self.step, self.error)
+    }
+}
+
+impl<E: core::error::Error + 'static> core::error::Error for RecoveryError<E> {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+impl<E> core::convert::From<RecoveryError<E>> for Error<E> {
+    fn from(e: RecoveryError<E>) -> Self {
+        e.error
+    }
+}
+
+/// Delay adding up the time it waits.
+pub(crate) struct Timed<'a, D> {
+    delay: &'a mut D,
+    us: u32,
+}
+
+impl<'a, D: Delay> Timed<'a, D> {
+    pub(crate) fn new(delay: &'a mut D) -> Self {
+        Self { delay, us: 0 }
+    }
+
+    /// Time waited so far, in whole milliseconds.
+    pub(crate) fn elapsed_ms(&self) -> u32 {
+        self.us / 1000
+    }
+}
+
+impl<D: Delay> Delay for Timed<'_, D> {
+    const MICROS: bool = D::MICROS;
+
+    fn delay_ms(&mut self, ms: u16) {
+        self.us = self.us.saturating_add(ms as u32 * 1000);
+        self.delay.delay_ms(ms);
+    }
+
+    fn delay_us(&mut self, us: u32) {
+        self.us = self.us.saturating_add(us);
+        self.delay.delay_us(us);
+    }
+}
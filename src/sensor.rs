@@ -0,0 +1,57 @@
+//! Sensor-agnostic traits for application code.
+
+use crate::{
+    hal::{Bus, Delay},
+    Aht20, Error, Humidity, Reading, Ready, Temperature,
+};
+
+/// Sensor measuring temperature.
+pub trait TemperatureSensor {
+    /// Error taking a measurement.
+    type Error;
+
+    /// Measures the temperature.
+    fn temperature(&mut self, delay: &mut impl Delay) -> Result<Temperature, Self::Error>;
+}
+
+/// Sensor measuring relative humidity, and the temperature it is relative to.
+pub trait HumiditySensor: TemperatureSensor {
+    /// Measures the relative humidity.
+    fn humidity(&mut self, delay: &mut impl Delay) -> Result<Humidity, Self::Error>;
+
+    /// Measures humidity and temperature together.
+    ///
+    /// The default takes two separate measurements. Sensors measuring both at once, such as the
+    /// AHT20, take a single one.
+    fn reading(&mut self, delay: &mut impl Delay) -> Result<Reading, Self::Error> {
+        Ok(Reading {
+            humidity: self.humidity(delay)?,
+            temperature: self.temperature(delay)?,
+            crc_verified: false,
+        })
+    }
+}
+
+impl<I2C, E, P> TemperatureSensor for Aht20<I2C, Ready, P>
+where
+    I2C: Bus<Error = E>,
+{
+    type Error = Error<E>;
+
+    fn temperature(&mut self, delay: &mut impl Delay) -> Result<Temperature, Error<E>> {
+        self.read_temperature(delay)
+    }
+}
+
+impl<I2C, E, P> HumiditySensor for Aht20<I2C, Ready, P>
+where
+    I2C: Bus<Error = E>,
+{
+    fn humidity(&mut self, delay: &mut impl Delay) -> Result<Humidity, Error<E>> {
+        self.read_humidity(delay)
+    }
+
+    fn reading(&mut self, delay: &mut impl Delay) -> Result<Reading, Error<E>> {
+        self.read_reading(delay)
+    }
+}
@@ -0,0 +1,75 @@
+//! `serde` support for readings.
+
+use {
+    crate::{consts, Humidity, Reading, Temperature},
+    serde::{
+        de::{Error as _, Unexpected},
+        ser::SerializeStruct,
+        Deserialize, Deserializer, Serialize, Serializer,
+    },
+};
+
+impl Serialize for Humidity {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u32(self.h)
+    }
+}
+
+impl<'de> Deserialize<'de> for Humidity {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserialize_raw(deserializer).map(|h| Humidity { h })
+    }
+}
+
+impl Serialize for Temperature {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u32(self.t)
+    }
+}
+
+impl<'de> Deserialize<'de> for Temperature {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserialize_raw(deserializer).map(|t| Temperature { t })
+    }
+}
+
+/// Deserializes a raw reading, rejecting values that don't fit in 20 bits.
+fn deserialize_raw<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u32, D::Error> {
+    let raw = u32::deserialize(deserializer)?;
+    if raw >= consts::RAW_SCALE {
+        return Err(D::Error::invalid_value(
+            Unexpected::Unsigned(raw as u64),
+            &"a 20-bit raw reading",
+        ));
+    }
+    Ok(raw)
+}
+
+/// Serializes a reading converted to percent Relative Humidity and Celsius, instead of as raw
+/// values.
+///
+/// A [`Reading`] is serialized as a struct with `humidity_rh` and `temperature_celsius` fields.
+/// The conversion is lossy, so there is no matching `Deserialize` implementation.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct HumanReadable<T>(pub T);
+
+impl Serialize for HumanReadable<Humidity> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_f32(self.0.rh())
+    }
+}
+
+impl Serialize for HumanReadable<Temperature> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_f32(self.0.celsius())
+    }
+}
+
+impl Serialize for HumanReadable<Reading> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("Reading", 2)?;
+        state.serialize_field("humidity_rh", &self.0.humidity.rh())?;
+        state.serialize_field("temperature_celsius", &self.0.temperature.celsius())?;
+        state.end()
+    }
+}
@@ -0,0 +1,40 @@
+//! Driver state kept across a teardown.
+
+use crate::Config;
+
+/// Driver state, as returned by [`Aht20::into_parts`](crate::Aht20::into_parts).
+///
+/// Hand it back to [`Aht20::from_parts`](crate::Aht20::from_parts) to rebuild the driver without
+/// redoing the initialization, e.g. after re-creating the I2C peripheral on wake from deep sleep.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct State {
+    pub(crate) config: Config,
+    pub(crate) initialized: bool,
+    pub(crate) calibrated: bool,
+    pub(crate) measuring: bool,
+    pub(crate) poisoned: bool,
+    pub(crate) last_trigger_ms: Option<u32>,
+    pub(crate) recalibrations: u32,
+}
+
+impl State {
+    /// Returns the driver configuration.
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    /// Returns whether the driver has confirmed the sensor is calibrated.
+    ///
+    /// Set by a successful calibration or [`verify`](crate::Aht20::verify), cleared by a soft
+    /// reset.
+    pub fn is_calibrated(&self) -> bool {
+        self.calibrated
+    }
+
+    /// Returns whether a measurement started with
+    /// [`start_measurement`](crate::Aht20::start_measurement) is still to be collected.
+    pub fn is_measuring(&self) -> bool {
+        self.measuring
+    }
+}
@@ -0,0 +1,68 @@
+//! Sensor status byte.
+
+use crate::StatusFlags;
+
+/// Status byte reported by the sensor, see [`Aht20::read_status`](crate::Aht20::read_status).
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "ufmt", derive(ufmt::derive::uDebug))]
+pub struct Status {
+    bits: u8,
+}
+
+impl Status {
+    /// Wraps a raw status byte, e.g. the first byte of a measurement frame.
+    pub const fn from_bits(bits: u8) -> Self {
+        Self { bits }
+    }
+
+    /// Returns the raw status byte.
+    pub const fn bits(&self) -> u8 {
+        self.bits
+    }
+
+    /// Whether the sensor is busy measuring.
+    pub fn is_busy(&self) -> bool {
+        self.flags().contains(StatusFlags::BUSY)
+    }
+
+    /// Whether the sensor reports itself as calibrated.
+    pub fn is_calibrated(&self) -> bool {
+        self.flags().contains(StatusFlags::CALIBRATION_ENABLE)
+    }
+
+    /// Whether the CRC flag is set, which parts checking and appending a CRC report.
+    ///
+    /// See [`CrcMode::FromStatus`](crate::CrcMode::FromStatus) to check the CRC only then.
+    pub fn has_crc(&self) -> bool {
+        self.flags().contains(StatusFlags::CRC)
+    }
+
+    /// Returns the FIFO flags, see [`Aht20::fifo_status`](crate::Aht20::fifo_status).
+    pub fn fifo(&self) -> FifoStatus {
+        let flags = self.flags();
+        FifoStatus {
+            enabled: flags.contains(StatusFlags::FIFO_ENABLE),
+            full: flags.contains(StatusFlags::FIFO_FULL),
+            empty: flags.contains(StatusFlags::FIFO_EMPTY),
+        }
+    }
+
+    /// Decodes the status byte.
+    fn flags(&self) -> StatusFlags {
+        StatusFlags { bits: self.bits }
+    }
+}
+
+/// FIFO flags of the status byte, see [`Aht20::fifo_status`](crate::Aht20::fifo_status).
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "ufmt", derive(ufmt::derive::uDebug))]
+pub struct FifoStatus {
+    /// The FIFO is buffering measurements.
+    pub enabled: bool,
+    /// The FIFO holds as many measurements as it can.
+    pub full: bool,
+    /// The FIFO holds no measurements.
+    pub empty: bool,
+}
@@ -0,0 +1,82 @@
+//! Driver variant owning its delay provider.
+
+use crate::{
+    hal::{Bus, Delay},
+    Aht20, Error, Humidity, InitError, Reading, Temperature,
+};
+
+/// AHT20 driver owning its delay provider.
+///
+/// The delay is moved into the driver, e.g. `Aht20WithDelay::new(i2c, linux_embedded_hal::Delay)`.
+/// Use [`Aht20`] instead to share one delay between several drivers.
+pub struct Aht20WithDelay<I2C, D> {
+    dev: Aht20<I2C>,
+    delay: D,
+}
+
+impl<I2C, D, E> Aht20WithDelay<I2C, D>
+where
+    I2C: Bus<Error = E>,
+    D: Delay,
+{
+    /// Creates a new AHT20 device from an I2C peripheral and a Delay.
+    ///
+    /// On failure the peripheral is handed back in the [`InitError`].
+    pub fn new(i2c: I2C, mut delay: D) -> Result<Self, InitError<I2C, E>> {
+        let dev = Aht20::new(i2c, &mut delay)?;
+        Ok(Self { dev, delay })
+    }
+
+    /// Self-calibrate the sensor.
+    pub fn calibrate(&mut self) -> Result<(), Error<E>> {
+        self.dev.calibrate(&mut self.delay)
+    }
+
+    /// Soft resets the sensor.
+    pub fn reset(&mut self) -> Result<(), E> {
+        self.dev.reset(&mut self.delay)
+    }
+
+    /// Soft resets and recalibrates the sensor, recovering it from a glitch.
+    pub fn reset_and_recalibrate(&mut self) -> Result<(), Error<E>> {
+        self.dev.reset_and_recalibrate(&mut self.delay)
+    }
+
+    /// Reads humidity and temperature.
+    pub fn read(&mut self) -> Result<(Humidity, Temperature), Error<E>> {
+        self.dev.read(&mut self.delay)
+    }
+
+    /// Reads humidity and temperature as a [`Reading`].
+    pub fn read_reading(&mut self) -> Result<Reading, Error<E>> {
+        self.dev.read_reading(&mut self.delay)
+    }
+
+    /// Reads humidity and temperature, giving up once `timeout_ms` have been spent waiting.
+    pub fn read_with_timeout(
+        &mut self,
+        timeout_ms: u16,
+    ) -> Result<(Humidity, Temperature), Error<E>> {
+        self.dev.read_with_timeout(&mut self.delay, timeout_ms)
+    }
+
+    /// Reads humidity and temperature after a single fixed wait, without polling the status.
+    pub fn read_fixed_delay(&mut self) -> Result<(Humidity, Temperature), Error<E>> {
+        self.dev.read_fixed_delay(&mut self.delay)
+    }
+
+    /// Reads the temperature, from a full measurement.
+    pub fn read_temperature(&mut self) -> Result<Temperature, Error<E>> {
+        self.dev.read_temperature(&mut self.delay)
+    }
+
+    /// Reads the humidity, from a full measurement.
+    pub fn read_humidity(&mut self) -> Result<Humidity, Error<E>> {
+        self.dev.read_humidity(&mut self.delay)
+    }
+
+    /// Destroys the driver, returning the I2C peripheral and the delay.
+    pub fn free(self) -> (I2C, D) {
+        (self.dev.free(), self.delay)
+    }
+}
@@ -0,0 +1,632 @@
+//! The async driver, which must behave exactly like the blocking one.
+
+#![cfg(feature = "async")]
+
+mod common;
+
+use {
+    aht20::{Aht20Async, Config, CrcMode, Error, PollStrategy},
+    common::{
+        frame, frame_with_status, AsyncRecorder, BUSY, HUMIDITY, IDLE, TEMPERATURE, UNCALIBRATED,
+    },
+    embedded_hal_async::i2c::ErrorKind,
+    embedded_hal_mock::eh1::i2c::{Mock, Transaction},
+    futures::executor::block_on,
+};
+
+const ADDRESS: u8 = aht20::consts::ADDRESS;
+
+fn soft_reset() -> Transaction {
+    Transaction::write(ADDRESS, vec![0xBA])
+}
+
+fn status(status: u8) -> Transaction {
+    Transaction::write_read(ADDRESS, vec![0x71], vec![status])
+}
+
+fn trigger() -> Transaction {
+    Transaction::write(ADDRESS, vec![0xAC, 0x33, 0x00])
+}
+
+fn fetch(frame: Vec<u8>) -> Transaction {
+    Transaction::write_read(ADDRESS, vec![0x71], frame)
+}
+
+/// Raw humidity and temperature read, or the error.
+type Outcome = Result<(u32, u32), Error<ErrorKind>>;
+
+/// Creates a driver with `config` against `transcript`, following the initialization, reads
+/// once and returns the result with the waits of the read.
+fn read(config: Config, transcript: &[Transaction]) -> (Outcome, Vec<u32>) {
+    let mut expectations = vec![soft_reset(), status(IDLE)];
+    expectations.extend_from_slice(transcript);
+    let mut i2c = Mock::new(&expectations);
+    let mut delay = AsyncRecorder::default();
+
+    let result = block_on(async {
+        let mut dev = Aht20Async::new_with_config(i2c.clone(), config, &mut delay).await?;
+        delay.waits_us.clear();
+        let (humidity, temperature) = dev.read(&mut delay).await?;
+        Ok((humidity.raw(), temperature.raw()))
+    });
+    i2c.done();
+    (result, delay.waits_us)
+}
+
+#[test]
+fn reads() {
+    let (result, waits) = read(Config::default(), &[trigger(), fetch(frame())]);
+    assert_eq!(result, Ok((HUMIDITY, TEMPERATURE)));
+    assert_eq!(waits, [80_000]);
+}
+
+#[test]
+fn polls_in_frame_like_the_blocking_driver() {
+    let busy = frame_with_status(BUSY, 0, 0);
+    let transcript = [trigger(), fetch(busy.clone()), fetch(busy), fetch(frame())];
+    let (result, waits) = read(Config::default(), &transcript);
+    assert_eq!(result, Ok((HUMIDITY, TEMPERATURE)));
+    assert_eq!(waits, [80_000, 10_000, 10_000]);
+}
+
+#[test]
+fn follows_the_configured_timing() {
+    let config = Config {
+        initial_wait_ms: 40,
+        poll_schedule: &[5, 2],
+        measurement_max_polls: 3,
+        ..Config::default()
+    };
+    let busy = frame_with_status(BUSY, 0, 0);
+    let mut transcript = vec![trigger()];
+    transcript.extend((0..4).map(|_| fetch(busy.clone())));
+
+    let (result, waits) = read(config, &transcript);
+    assert_eq!(result, Err(Error::MaxTriesExceeded));
+    assert_eq!(waits, [40_000, 5_000, 2_000, 2_000]);
+}
+
+#[test]
+fn follows_the_crc_mode() {
+    let mut bad = frame();
+    bad[6] ^= 0xFF;
+    let (result, _) = read(Config::default(), &[trigger(), fetch(bad.clone())]);
+    assert!(matches!(result, Err(Error::Checksum { .. })));
+
+    let config = Config {
+        crc: CrcMode::Disabled,
+        ..Config::default()
+    };
+    let (result, _) = read(config, &[trigger(), fetch(frame()[..6].to_vec())]);
+    assert_eq!(result, Ok((HUMIDITY, TEMPERATURE)));
+
+    let config = Config {
+        checksum_retries: 1,
+        ..Config::default()
+    };
+    let transcript = [trigger(), fetch(bad), trigger(), fetch(frame())];
+    assert_eq!(read(config, &transcript).0, Ok((HUMIDITY, TEMPERATURE)));
+}
+
+#[test]
+fn retries_bus_errors() {
+    let config = Config {
+        bus_retries: 1,
+        bus_retry_delay_us: 7,
+        ..Config::default()
+    };
+    let transcript = [
+        trigger().with_error(ErrorKind::ArbitrationLoss),
+        trigger(),
+        fetch(frame()),
+    ];
+    let (result, waits) = read(config, &transcript);
+    assert_eq!(result, Ok((HUMIDITY, TEMPERATURE)));
+    assert_eq!(waits, [7, 80_000]);
+
+    let transcript = [trigger().with_error(ErrorKind::Overrun)];
+    let (result, _) = read(config, &transcript);
+    assert_eq!(result, Err(Error::Bus(ErrorKind::Overrun)));
+}
+
+#[test]
+fn rejects_invalid_configurations() {
+    let config = Config {
+        measurement_max_polls: 0,
+        ..Config::default()
+    };
+    let mut i2c = Mock::new(&[]);
+    let result = block_on(Aht20Async::new_with_config(
+        i2c.clone(),
+        config,
+        &mut AsyncRecorder::default(),
+    ));
+    assert!(matches!(result, Err(Error::InvalidConfig(_))));
+    i2c.done();
+}
+
+#[cfg(feature = "eh1")]
+#[test]
+fn waits_like_the_blocking_driver() {
+    use {
+        aht20::{eh1::Eh1, Aht20},
+        common::{eh1, Recorder, Wait},
+    };
+
+    let config = Config {
+        initial_wait_ms: 60,
+        poll_schedule: &[15, 5],
+        ..Config::default()
+    };
+    let busy = frame_with_status(BUSY, 0, 0);
+
+    let (result, async_waits) = read(
+        config,
+        &[
+            trigger(),
+            fetch(busy.clone()),
+            fetch(busy.clone()),
+            fetch(busy.clone()),
+            fetch(frame()),
+        ],
+    );
+    assert_eq!(result, Ok((HUMIDITY, TEMPERATURE)));
+
+    let expectations = [
+        eh1::write(&[0xBA]),
+        eh1::status(IDLE),
+        eh1::write(&[0xAC, 0x33, 0x00]),
+        eh1::write_read(&[0x71], busy.clone()),
+        eh1::write_read(&[0x71], busy.clone()),
+        eh1::write_read(&[0x71], busy),
+        eh1::write_read(&[0x71], frame()),
+    ]
+    .concat();
+    let mut i2c = embedded_hal_mock::eh1::i2c::Mock::new(&expectations);
+    let mut delay = Recorder::default();
+    let mut dev = Aht20::new_with_config(Eh1(i2c.clone()), config, &mut delay).unwrap();
+    delay.clear();
+    dev.read(&mut delay).unwrap();
+    i2c.done();
+
+    let blocking_waits: Vec<u32> = delay
+        .waits
+        .iter()
+        .map(|wait| match *wait {
+            Wait::Ms(ms) => ms as u32 * 1000,
+            Wait::Us(us) => us,
+        })
+        .collect();
+    assert_eq!(async_waits, blocking_waits);
+    assert_eq!(async_waits, [60_000, 15_000, 5_000, 5_000]);
+}
+
+#[test]
+fn calibrates_only_when_needed() {
+    let initialize = || Transaction::write(ADDRESS, vec![0xBE, 0x08, 0x00]);
+    let config = Config {
+        perform_register_reset: false,
+        ..Config::default()
+    };
+
+    for (first, calibration) in [
+        (IDLE, vec![]),
+        (UNCALIBRATED, vec![initialize(), status(IDLE), status(IDLE)]),
+    ] {
+        let mut expectations = vec![soft_reset(), status(first)];
+        expectations.extend(calibration);
+        let mut i2c = Mock::new(&expectations);
+        block_on(Aht20Async::new_with_config(
+            i2c.clone(),
+            config,
+            &mut AsyncRecorder::default(),
+        ))
+        .unwrap();
+        i2c.done();
+    }
+}
+
+#[test]
+fn reports_a_missing_device() {
+    use embedded_hal_async::i2c::NoAcknowledgeSource;
+
+    let nack = ErrorKind::NoAcknowledge(NoAcknowledgeSource::Address);
+    let mut i2c = Mock::new(&[soft_reset().with_error(nack)]);
+    let result = block_on(Aht20Async::new(i2c.clone(), &mut AsyncRecorder::default()));
+    assert!(matches!(result, Err(Error::DeviceNotFound)));
+    i2c.done();
+}
+
+/// Creates a driver with `config` against `transcript`, following the initialization, with the
+/// waits of the initialization cleared.
+fn driver(config: Config, transcript: &[Transaction]) -> (Aht20Async<Mock>, Mock, AsyncRecorder) {
+    let mut expectations = vec![soft_reset(), status(IDLE)];
+    expectations.extend_from_slice(transcript);
+    let i2c = Mock::new(&expectations);
+    let mut delay = AsyncRecorder::default();
+    let dev = block_on(Aht20Async::new_with_config(i2c.clone(), config, &mut delay)).unwrap();
+    delay.waits_us.clear();
+    (dev, i2c, delay)
+}
+
+/// Reads once with `dev`, keeping the raw humidity and temperature.
+fn read_with(dev: &mut Aht20Async<Mock>, delay: &mut AsyncRecorder) -> Outcome {
+    block_on(dev.read(delay)).map(|(h, t)| (h.raw(), t.raw()))
+}
+
+/// Transcript of a measurement staying busy past the default poll limit, polled in frame.
+fn stuck_busy() -> Vec<Transaction> {
+    let busy = frame_with_status(BUSY, 0, 0);
+    let mut transcript = vec![trigger()];
+    transcript.extend((0..6).map(|_| fetch(busy.clone())));
+    transcript
+}
+
+#[test]
+fn polls_the_status_when_configured() {
+    let config = Config {
+        poll_strategy: PollStrategy::Status,
+        ..Config::default()
+    };
+    let transcript = [trigger(), status(BUSY), status(IDLE), fetch(frame())];
+    let (result, waits) = read(config, &transcript);
+    assert_eq!(result, Ok((HUMIDITY, TEMPERATURE)));
+    assert_eq!(waits, [80_000, 10_000]);
+
+    // Gives up after as many status polls as the blocking driver
+    let mut transcript = vec![trigger()];
+    transcript.extend((0..6).map(|_| status(BUSY)));
+    let (result, waits) = read(config, &transcript);
+    assert_eq!(result, Err(Error::MaxTriesExceeded));
+    assert_eq!(waits, [80_000, 10_000, 10_000, 10_000, 10_000, 10_000]);
+}
+
+/// The minimum interval, mirroring the blocking driver's policies.
+mod min_interval {
+    use {
+        super::*,
+        aht20::MinIntervalPolicy,
+        core::sync::atomic::{AtomicU32, Ordering},
+        embedded_hal_async::delay::DelayNs,
+    };
+
+    /// Transcript of two measurements, each ready on the first poll.
+    fn two_measurements() -> Vec<Transaction> {
+        vec![trigger(), fetch(frame()), trigger(), fetch(frame())]
+    }
+
+    /// Configuration enforcing a 1s interval with `policy`.
+    fn config(policy: MinIntervalPolicy) -> Config {
+        Config {
+            min_interval_ms: 1000,
+            min_interval_policy: policy,
+            ..Config::default()
+        }
+    }
+
+    #[test]
+    fn block() {
+        static NOW: AtomicU32 = AtomicU32::new(0);
+
+        /// Delay advancing [`NOW`] as it waits.
+        struct Ticking(AsyncRecorder);
+
+        impl DelayNs for Ticking {
+            async fn delay_ns(&mut self, ns: u32) {
+                self.0.delay_ns(ns).await;
+            }
+
+            async fn delay_ms(&mut self, ms: u32) {
+                NOW.fetch_add(ms, Ordering::Relaxed);
+                self.0.delay_ms(ms).await;
+            }
+        }
+
+        let (mut dev, mut i2c, _) = driver(config(MinIntervalPolicy::Block), &two_measurements());
+        dev.set_clock(|| NOW.load(Ordering::Relaxed));
+        let mut delay = Ticking(AsyncRecorder::default());
+
+        // The second measurement waits out what is left of the interval
+        block_on(dev.read(&mut delay)).unwrap();
+        NOW.fetch_add(300, Ordering::Relaxed);
+        delay.0.waits_us.clear();
+        block_on(dev.read(&mut delay)).unwrap();
+        assert_eq!(delay.0.waits_us, [620_000, 80_000]);
+        i2c.done();
+    }
+
+    #[test]
+    fn block_with_a_stalled_clock() {
+        let (mut dev, mut i2c, mut delay) =
+            driver(config(MinIntervalPolicy::Block), &two_measurements()[..2]);
+        dev.set_clock(|| 0);
+
+        // The interval is waited out twice at most, then the measurement is refused
+        read_with(&mut dev, &mut delay).unwrap();
+        delay.waits_us.clear();
+        let result = read_with(&mut dev, &mut delay);
+        assert_eq!(result, Err(Error::TooSoon { remaining_ms: 1000 }));
+        assert_eq!(delay.waits_us, [1_000_000, 1_000_000]);
+        i2c.done();
+    }
+
+    #[test]
+    fn reject() {
+        static NOW: AtomicU32 = AtomicU32::new(0);
+
+        let (mut dev, mut i2c, mut delay) =
+            driver(config(MinIntervalPolicy::Reject), &two_measurements());
+        dev.set_clock(|| NOW.load(Ordering::Relaxed));
+
+        // Refused without bus traffic until the interval has elapsed
+        read_with(&mut dev, &mut delay).unwrap();
+        NOW.store(400, Ordering::Relaxed);
+        let result = read_with(&mut dev, &mut delay);
+        assert_eq!(result, Err(Error::TooSoon { remaining_ms: 600 }));
+        NOW.store(1000, Ordering::Relaxed);
+        read_with(&mut dev, &mut delay).unwrap();
+        i2c.done();
+    }
+}
+
+/// Poisoning and recovery, mirroring the blocking driver.
+mod recovery {
+    use {
+        super::*,
+        aht20::{RecoveryError, RecoveryReport, RecoveryStep},
+    };
+
+    /// Transcript of a soft reset and full calibration of a sensor idle on the first poll.
+    fn reset_and_recalibrate() -> [Transaction; 4] {
+        [
+            soft_reset(),
+            Transaction::write(ADDRESS, vec![0xBE, 0x08, 0x00]),
+            status(IDLE),
+            status(IDLE),
+        ]
+    }
+
+    /// Transcript of [`Aht20Async::recover`] on a healthy sensor.
+    fn recover() -> [Transaction; 3] {
+        [soft_reset(), status(IDLE), status(IDLE)]
+    }
+
+    #[test]
+    fn poisoned_until_recovered() {
+        let mut transcript = stuck_busy();
+        transcript.extend(recover());
+        transcript.extend([trigger(), fetch(frame())]);
+        let (mut dev, mut i2c, mut delay) = driver(Config::default(), &transcript);
+
+        assert_eq!(
+            read_with(&mut dev, &mut delay),
+            Err(Error::MaxTriesExceeded)
+        );
+        assert!(dev.needs_recovery());
+
+        // Refused without bus traffic
+        assert_eq!(read_with(&mut dev, &mut delay), Err(Error::NeedsRecovery));
+
+        let report = block_on(dev.recover(&mut delay)).unwrap();
+        assert_eq!(
+            report,
+            RecoveryReport {
+                reinitialized: false,
+                flushed: false,
+                delay_ms: 20,
+            }
+        );
+        assert!(!dev.needs_recovery());
+        assert_eq!(read_with(&mut dev, &mut delay), Ok((HUMIDITY, TEMPERATURE)));
+        i2c.done();
+    }
+
+    #[test]
+    fn poisoned_after_a_failed_recovery() {
+        let config = Config {
+            recover_stuck_busy: true,
+            ..Config::default()
+        };
+        let mut transcript = stuck_busy();
+        transcript.push(soft_reset().with_error(ErrorKind::Other));
+        let (mut dev, mut i2c, mut delay) = driver(config, &transcript);
+
+        assert_eq!(
+            read_with(&mut dev, &mut delay),
+            Err(Error::Bus(ErrorKind::Other))
+        );
+        assert!(dev.needs_recovery());
+        assert_eq!(read_with(&mut dev, &mut delay), Err(Error::NeedsRecovery));
+        i2c.done();
+    }
+
+    #[test]
+    fn stuck_busy_after_recovery() {
+        let config = Config {
+            recover_stuck_busy: true,
+            ..Config::default()
+        };
+        let mut transcript = stuck_busy();
+        transcript.extend(reset_and_recalibrate());
+        transcript.extend(stuck_busy());
+        let (mut dev, mut i2c, mut delay) = driver(config, &transcript);
+
+        assert_eq!(read_with(&mut dev, &mut delay), Err(Error::StuckBusy));
+        assert!(dev.needs_recovery());
+        i2c.done();
+    }
+
+    #[test]
+    fn flush_on_recover() {
+        let config = Config {
+            flush_on_recover: true,
+            ..Config::default()
+        };
+        let mut transcript = recover().to_vec();
+        transcript.extend([trigger(), fetch(frame())]);
+        let (mut dev, mut i2c, mut delay) = driver(config, &transcript);
+
+        let report = block_on(dev.recover(&mut delay)).unwrap();
+        assert_eq!(
+            report,
+            RecoveryReport {
+                reinitialized: false,
+                flushed: true,
+                delay_ms: 100,
+            }
+        );
+        i2c.done();
+    }
+
+    #[test]
+    fn failed_recovery_notes_its_step() {
+        let (mut dev, mut i2c, mut delay) = driver(
+            Config::default(),
+            &[soft_reset(), status(IDLE), status(BUSY)],
+        );
+
+        let result = block_on(dev.recover(&mut delay));
+        assert_eq!(
+            result,
+            Err(RecoveryError {
+                step: RecoveryStep::Verify,
+                error: Error::StuckBusy,
+            })
+        );
+        assert!(dev.needs_recovery());
+        i2c.done();
+    }
+
+    #[test]
+    fn counts_recalibrations() {
+        let config = Config {
+            recover_uncalibrated: true,
+            ..Config::default()
+        };
+        let mut transcript = vec![
+            trigger(),
+            fetch(frame_with_status(UNCALIBRATED, HUMIDITY, TEMPERATURE)),
+        ];
+        transcript.extend(reset_and_recalibrate());
+        transcript.extend([trigger(), fetch(frame())]);
+        let (mut dev, mut i2c, mut delay) = driver(config, &transcript);
+
+        assert_eq!(dev.recalibrations(), 0);
+        assert_eq!(read_with(&mut dev, &mut delay), Ok((HUMIDITY, TEMPERATURE)));
+        assert_eq!(dev.recalibrations(), 1);
+        i2c.done();
+    }
+}
+
+/// Sensor simulated behind an async bus, for reads dropped part way.
+mod cancellation {
+    use {
+        super::*,
+        core::{
+            cell::RefCell,
+            future::Future,
+            pin::{pin, Pin},
+            task::{Context, Poll},
+        },
+        embedded_hal_async::{
+            delay::DelayNs,
+            i2c::{ErrorType, I2c, Operation},
+        },
+    };
+
+    /// State of the simulated sensor.
+    #[derive(Default)]
+    struct Sensor {
+        /// Status reads left before the measurement in flight completes.
+        busy: u32,
+        triggers: u32,
+    }
+
+    /// Future returning pending once, so that every transaction and wait is a cancellation point.
+    struct Yield(bool);
+
+    impl Future for Yield {
+        type Output = ();
+
+        fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+            if self.0 {
+                return Poll::Ready(());
+            }
+            self.0 = true;
+            cx.waker().wake_by_ref();
+            Poll::Pending
+        }
+    }
+
+    /// Bus to the simulated sensor, applying each transaction before yielding once.
+    struct Bus<'a>(&'a RefCell<Sensor>);
+
+    impl ErrorType for Bus<'_> {
+        type Error = ErrorKind;
+    }
+
+    impl I2c for Bus<'_> {
+        async fn transaction(
+            &mut self,
+            _address: u8,
+            operations: &mut [Operation<'_>],
+        ) -> Result<(), ErrorKind> {
+            {
+                let mut sensor = self.0.borrow_mut();
+                for operation in operations.iter_mut() {
+                    match operation {
+                        Operation::Write(bytes) if **bytes == [0xAC, 0x33, 0x00] => {
+                            assert_eq!(sensor.busy, 0, "triggered while measuring");
+                            sensor.triggers += 1;
+                            sensor.busy = 3;
+                        }
+                        Operation::Write(_) => {}
+                        Operation::Read(buf) => {
+                            let status = match sensor.busy {
+                                0 => IDLE,
+                                _ => BUSY,
+                            };
+                            sensor.busy = sensor.busy.saturating_sub(1);
+                            let frame = frame_with_status(status, HUMIDITY, TEMPERATURE);
+                            buf.copy_from_slice(&frame[..buf.len()]);
+                        }
+                    }
+                }
+            }
+            Yield(false).await;
+            Ok(())
+        }
+    }
+
+    /// Delay yielding once instead of waiting.
+    struct Yielding;
+
+    impl DelayNs for Yielding {
+        async fn delay_ns(&mut self, _ns: u32) {
+            Yield(false).await
+        }
+    }
+
+    #[test]
+    fn drop_at_each_stage() {
+        let waker = futures::task::noop_waker();
+        let mut cx = Context::from_waker(&waker);
+
+        // Drop the first read after more and more polls, until it completes by itself
+        for polls in 1.. {
+            let sensor = RefCell::new(Sensor::default());
+            let mut dev = block_on(Aht20Async::new(Bus(&sensor), &mut Yielding)).unwrap();
+            let mut delay = Yielding;
+            {
+                let mut read = pin!(dev.read(&mut delay));
+                if (0..polls).any(|_| read.as_mut().poll(&mut cx).is_ready()) {
+                    break;
+                }
+            }
+
+            let (humidity, temperature) = block_on(dev.read(&mut Yielding)).unwrap();
+            assert_eq!((humidity.raw(), temperature.raw()), (HUMIDITY, TEMPERATURE));
+            assert!(sensor.borrow().triggers <= 2);
+        }
+    }
+}
@@ -0,0 +1,135 @@
+//! Options of the builder, each changing the transcript or the timing of the driver.
+
+mod common;
+
+use {
+    aht20::{Aht20, Aht20Builder, Config, Error},
+    common::{
+        fetch, frame, frame_with_status, init, soft_reset, status, trigger, Recorder, Wait, BUSY,
+        HUMIDITY, IDLE,
+    },
+    embedded_hal_mock::eh0::{
+        i2c::{Mock, Transaction},
+        MockError,
+    },
+};
+
+/// Builds the driver with `builder` and reads a measurement, against `expectations`, returning
+/// the result and the waits of the read.
+fn build_and_read(
+    builder: Aht20Builder,
+    expectations: &[Transaction],
+) -> (Result<(), Error<MockError>>, Vec<Wait>) {
+    let mut i2c = Mock::new(expectations);
+    let mut delay = Recorder::default();
+
+    let mut dev = builder.build(i2c.clone(), &mut delay).unwrap();
+    delay.clear();
+    let result = dev.read(&mut delay).map(|_| ());
+    i2c.done();
+    (result, delay.waits)
+}
+
+#[test]
+fn defaults_match_new() {
+    let mut i2c = Mock::new(&init());
+    let dev = Aht20::new(i2c.clone(), &mut Recorder::default()).unwrap();
+
+    assert_eq!(Aht20Builder::new().config(), Ok(*dev.config()));
+    assert_eq!(Aht20Builder::new().config(), Ok(Config::default()));
+    i2c.done();
+}
+
+#[test]
+fn address() {
+    let expectations = [
+        Transaction::write(0x39, vec![0xBA]),
+        Transaction::write_read(0x39, vec![0x71], vec![IDLE]),
+        Transaction::write(0x39, vec![0xAC, 0x33, 0x00]),
+        Transaction::write_read(0x39, vec![0x71], vec![IDLE]),
+        Transaction::write_read(0x39, vec![0x71], frame()),
+    ];
+    let (result, _) = build_and_read(Aht20Builder::new().address(0x39), &expectations);
+    assert_eq!(result, Ok(()));
+}
+
+#[test]
+fn max_retries() {
+    let mut expectations = init();
+    expectations.push(trigger());
+    expectations.extend((0..3).map(|_| status(BUSY)));
+    let (result, waits) = build_and_read(Aht20Builder::new().max_retries(2), &expectations);
+    assert_eq!(result, Err(Error::MaxTriesExceeded));
+    assert_eq!(waits.len(), 3);
+}
+
+#[test]
+fn poll_interval_ms() {
+    let mut expectations = init();
+    expectations.extend([trigger(), status(BUSY), status(IDLE), fetch(frame())]);
+    let (result, waits) = build_and_read(Aht20Builder::new().poll_interval_ms(2), &expectations);
+    assert_eq!(result, Ok(()));
+    assert_eq!(waits, [Wait::Ms(80), Wait::Us(2000)]);
+}
+
+#[test]
+fn skip_initial_calibration() {
+    // No status read follows the soft reset
+    let expectations = [soft_reset(), trigger(), status(IDLE), fetch(frame())];
+    let builder = Aht20Builder::new().skip_initial_calibration(true);
+    let (result, _) = build_and_read(builder, &expectations);
+    assert_eq!(result, Ok(()));
+}
+
+#[cfg(feature = "fugit")]
+#[test]
+fn typed_durations() {
+    use {aht20::ConfigError, fugit::ExtU32};
+
+    let config = Aht20Builder::new()
+        .poll_interval(500.micros())
+        .timeout(20.millis())
+        .config()
+        .unwrap();
+    assert_eq!(config.measurement_poll_interval_us, 500);
+    assert_eq!(config.measurement_max_polls, 40);
+
+    // Out of range timeouts are rejected rather than truncated
+    let builder = Aht20Builder::new().poll_interval(500.micros());
+    assert_eq!(
+        builder.timeout(0.millis()).config(),
+        Err(ConfigError::TimeoutTooShort)
+    );
+    assert_eq!(
+        builder.timeout(u32::MAX.millis()).config(),
+        Err(ConfigError::TimeoutTooLong)
+    );
+}
+
+#[test]
+fn check_range() {
+    // Just inside the guard band above 85°C, then just outside it
+    let measurement = |temperature| {
+        let mut expectations = init();
+        expectations.push(trigger());
+        expectations.push(status(IDLE));
+        expectations.push(fetch(frame_with_status(0x1C, HUMIDITY, temperature)));
+        expectations
+    };
+    let builder = || Aht20Builder::new().check_range(true);
+
+    let (result, _) = build_and_read(builder(), &measurement(734_005));
+    assert_eq!(result, Ok(()));
+    let (result, _) = build_and_read(builder(), &measurement(734_006));
+    assert_eq!(
+        result,
+        Err(Error::OutOfRange {
+            humidity: HUMIDITY,
+            temperature: 734_006,
+        })
+    );
+
+    // Unchecked by default
+    let (result, _) = build_and_read(Aht20Builder::new(), &measurement(734_006));
+    assert_eq!(result, Ok(()));
+}
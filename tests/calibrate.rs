@@ -0,0 +1,149 @@
+//! Calibration of the sensor and waits bounded by a clock.
+
+mod common;
+
+use {
+    aht20::{Aht20, Error},
+    common::{
+        fetch, frame, init, initialize, soft_reset, status, trigger, Recorder, BUSY, HUMIDITY, IDLE,
+    },
+    core::cell::Cell,
+    embedded_hal_mock::eh0::i2c::{Mock, Transaction},
+};
+
+/// Transcript of a full calibration of a sensor idle on the first poll.
+fn calibration() -> [Transaction; 3] {
+    [initialize(), status(IDLE), status(IDLE)]
+}
+
+/// Millisecond clock advancing by `step` each time it is read.
+fn clock(step: u32) -> impl FnMut() -> u32 {
+    let now = Cell::new(0);
+    move || {
+        now.set(now.get() + step);
+        now.get()
+    }
+}
+
+#[test]
+fn calibrate_until_outlasts_the_poll_limit() {
+    // Twenty busy polls, twice the calibration poll limit, fit in the timeout
+    let mut expectations = vec![initialize()];
+    expectations.extend((0..20).map(|_| status(BUSY)));
+    expectations.extend([status(IDLE), status(IDLE)]);
+    let mut i2c = Mock::new(&expectations);
+
+    let mut dev = Aht20::attach(i2c.clone());
+    assert_eq!(
+        dev.calibrate_until(&mut Recorder::default(), clock(10), 1000),
+        Ok(())
+    );
+    i2c.done();
+}
+
+#[test]
+fn calibrate_until_times_out() {
+    let mut expectations = vec![initialize()];
+    expectations.extend((0..5).map(|_| status(BUSY)));
+    let mut i2c = Mock::new(&expectations);
+
+    let mut dev = Aht20::attach(i2c.clone());
+    assert_eq!(
+        dev.calibrate_until(&mut Recorder::default(), clock(10), 50),
+        Err(Error::Timeout { waited_ms: 50 })
+    );
+    i2c.done();
+}
+
+#[test]
+fn read_until_outlasts_the_poll_limit() {
+    let mut expectations = vec![trigger()];
+    expectations.extend((0..10).map(|_| status(BUSY)));
+    expectations.extend([status(IDLE), fetch(frame())]);
+    let mut i2c = Mock::new(&expectations);
+
+    let mut dev = Aht20::attach(i2c.clone());
+    let result = dev.read_until(&mut Recorder::default(), clock(10), 1000);
+    assert_eq!(result.map(|(h, _)| h.raw()), Ok(HUMIDITY));
+    i2c.done();
+}
+
+#[test]
+fn read_until_times_out() {
+    let mut expectations = vec![trigger()];
+    expectations.extend((0..3).map(|_| status(BUSY)));
+    let mut i2c = Mock::new(&expectations);
+
+    let mut dev = Aht20::attach(i2c.clone());
+    let result = dev.read_until(&mut Recorder::default(), clock(25), 60);
+    assert_eq!(result.map(|_| ()), Err(Error::Timeout { waited_ms: 75 }));
+    i2c.done();
+}
+
+#[test]
+fn recalibrates_after_a_checksum_storm() {
+    let mut corrupted = frame();
+    corrupted[6] ^= 0xFF;
+    let mut expectations = init();
+    for _ in 0..3 {
+        expectations.extend([trigger(), status(IDLE), fetch(corrupted.clone())]);
+    }
+    expectations.push(soft_reset());
+    expectations.extend(calibration());
+    expectations.extend([trigger(), status(IDLE), fetch(frame())]);
+    let mut i2c = Mock::new(&expectations);
+    let mut delay = Recorder::default();
+
+    let mut dev = Aht20::new(i2c.clone(), &mut delay).unwrap();
+    for _ in 0..3 {
+        let result = dev.read(&mut delay).map(|_| ());
+        assert!(matches!(result, Err(Error::Checksum { .. })));
+    }
+    dev.reset_and_recalibrate(&mut delay).unwrap();
+    let (humidity, _) = dev.read(&mut delay).unwrap();
+    assert_eq!(humidity.raw(), HUMIDITY);
+    i2c.done();
+}
+
+#[test]
+fn recalibrates_after_a_fatal_error() {
+    let mut expectations = init();
+    expectations.push(trigger());
+    expectations.extend((0..6).map(|_| status(BUSY)));
+    expectations.push(soft_reset());
+    expectations.extend(calibration());
+    expectations.extend([trigger(), status(IDLE), fetch(frame())]);
+    let mut i2c = Mock::new(&expectations);
+    let mut delay = Recorder::default();
+
+    let mut dev = Aht20::new(i2c.clone(), &mut delay).unwrap();
+    assert_eq!(
+        dev.read(&mut delay).map(|_| ()),
+        Err(Error::MaxTriesExceeded)
+    );
+    dev.reset_and_recalibrate(&mut delay).unwrap();
+    assert!(!dev.needs_recovery());
+    dev.read(&mut delay).unwrap();
+    i2c.done();
+}
+
+#[test]
+fn calibrate_outcomes() {
+    use common::{Wait, UNCALIBRATED};
+
+    let mut expectations = vec![initialize(), status(BUSY), status(IDLE), status(IDLE)];
+    expectations.push(initialize());
+    expectations.extend((0..11).map(|_| status(BUSY)));
+    expectations.extend([initialize(), status(UNCALIBRATED), status(UNCALIBRATED)]);
+    let mut i2c = Mock::new(&expectations);
+    let mut delay = Recorder::default();
+    let mut dev = Aht20::attach(i2c.clone());
+
+    // The sensor settles before the first poll
+    assert_eq!(dev.calibrate(&mut delay), Ok(()));
+    assert_eq!(delay.waits, [Wait::Ms(10), Wait::Us(10_000)]);
+
+    assert_eq!(dev.calibrate(&mut delay), Err(Error::MaxTriesExceeded));
+    assert_eq!(dev.calibrate(&mut delay), Err(Error::Uncalibrated));
+    i2c.done();
+}
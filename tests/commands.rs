@@ -0,0 +1,102 @@
+//! Commands and constants of the sensor, as seen on the bus.
+
+mod common;
+
+use {
+    aht20::{consts, Aht20},
+    common::{frame, Recorder, Wait, IDLE},
+    embedded_hal_mock::eh0::i2c::{Mock, Transaction},
+};
+
+#[test]
+fn transcript_from_constants() {
+    let expectations = [
+        Transaction::write(consts::ADDRESS, consts::SOFT_RESET.to_vec()),
+        Transaction::write_read(consts::ADDRESS, consts::READ_STATUS.to_vec(), vec![IDLE]),
+        Transaction::write(consts::ADDRESS, consts::TRIGGER_MEASUREMENT.to_vec()),
+        Transaction::write_read(consts::ADDRESS, consts::READ_STATUS.to_vec(), vec![IDLE]),
+        Transaction::write_read(consts::ADDRESS, consts::READ_STATUS.to_vec(), frame()),
+    ];
+    let mut i2c = Mock::new(&expectations);
+    let mut delay = Recorder::default();
+
+    let mut dev = Aht20::new(i2c.clone(), &mut delay).unwrap();
+    dev.read(&mut delay).unwrap();
+    assert_eq!(
+        delay.waits,
+        [
+            Wait::Ms(consts::SOFT_RESET_MS),
+            Wait::Ms(consts::MEASUREMENT_MS)
+        ]
+    );
+    i2c.done();
+}
+
+#[test]
+fn command_bytes() {
+    use aht20::{commands::Command, Variant};
+
+    assert_eq!(Command::TriggerMeasurement.bytes(), [0xAC, 0x33, 0x00]);
+    assert_eq!(Command::Initialize.bytes(), [0xBE, 0x08, 0x00]);
+    assert_eq!(Command::SoftReset.bytes(), [0xBA]);
+    assert_eq!(Command::ReadStatus.bytes(), [0x71]);
+    assert_eq!(
+        Command::Raw(&[0x1B, 0x00, 0x00]).bytes(),
+        [0x1B, 0x00, 0x00]
+    );
+
+    // The AHT10 takes its own initialization and status commands
+    assert_eq!(
+        Command::Initialize.bytes_for(Variant::Aht10),
+        [0xE1, 0x08, 0x00]
+    );
+    assert_eq!(Command::ReadStatus.bytes_for(Variant::Aht10), [0x00]);
+    assert_eq!(Command::SoftReset.bytes_for(Variant::Aht10), [0xBA]);
+}
+
+#[test]
+fn send_command() {
+    use aht20::commands::Command;
+
+    let expectations = [
+        Transaction::write(consts::ADDRESS, vec![0xBA]),
+        Transaction::write(consts::ADDRESS, vec![0xE1, 0x08, 0x00]),
+    ];
+    let mut i2c = Mock::new(&expectations);
+
+    let mut dev = Aht20::attach(i2c.clone());
+    dev.send_command(Command::SoftReset).unwrap();
+    dev.send_command(Command::Raw(&[0xE1, 0x08, 0x00])).unwrap();
+    i2c.done();
+}
+
+#[test]
+fn status_command() {
+    // The datasheet's 0x71, whatever the driver's state
+    let status = |byte| Transaction::write_read(0x38, vec![0x71], vec![byte]);
+    let expectations = [
+        status(0x18),
+        status(0x98),
+        status(0x10),
+        status(0x1C),
+        status(0x98),
+    ];
+    let mut i2c = Mock::new(&expectations);
+
+    let mut dev = Aht20::new_uninit(i2c.clone());
+    let idle = dev.read_status().unwrap();
+    assert_eq!(idle.bits(), 0x18);
+    assert!(idle.is_calibrated() && !idle.is_busy() && idle.has_crc());
+
+    let mut dev = Aht20::attach(dev.free());
+    let busy = dev.read_status().unwrap();
+    assert!(busy.is_busy() && busy.is_calibrated());
+    let uncalibrated = dev.read_status().unwrap();
+    assert!(!uncalibrated.is_calibrated() && !uncalibrated.is_busy());
+    let fifo = dev.read_status().unwrap().fifo();
+    assert!(fifo.enabled && !fifo.full && !fifo.empty);
+
+    // Readiness checks read the same byte
+    assert_eq!(dev.is_ready(), Ok(false));
+    i2c.done();
+}
@@ -0,0 +1,178 @@
+//! Helpers shared by the integration tests: frames, transcripts and a recording delay.
+
+#![allow(dead_code)]
+
+use {
+    aht20::{consts, hal::Delay},
+    embedded_hal_mock::eh0::i2c::Transaction,
+};
+
+/// Raw humidity of [`frame`], 50 %RH.
+pub const HUMIDITY: u32 = 0x80000;
+/// Raw temperature of [`frame`], 25 °C.
+pub const TEMPERATURE: u32 = 0x60000;
+
+/// Status byte of an idle, calibrated sensor, as read after power-on.
+pub const IDLE: u8 = 0x18;
+/// Status byte of a calibrated sensor busy measuring.
+pub const BUSY: u8 = 0x98;
+/// Status byte of an idle sensor that lost its calibration.
+pub const UNCALIBRATED: u8 = 0x10;
+
+/// CRC-8 of the datasheet, computed independently of the driver.
+pub fn crc8(data: &[u8]) -> u8 {
+    let mut crc = 0xFF;
+    for byte in data {
+        crc ^= byte;
+        for _ in 0..8 {
+            crc = if crc & 0x80 != 0 {
+                (crc << 1) ^ 0x31
+            } else {
+                crc << 1
+            };
+        }
+    }
+    crc
+}
+
+/// Measurement frame with the `status` byte and the raw readings, closed by its CRC.
+pub fn frame_with_status(status: u8, humidity: u32, temperature: u32) -> Vec<u8> {
+    let mut frame = vec![
+        status,
+        (humidity >> 12) as u8,
+        (humidity >> 4) as u8,
+        ((humidity & 0xF) << 4) as u8 | ((temperature >> 16) & 0xF) as u8,
+        (temperature >> 8) as u8,
+        temperature as u8,
+    ];
+    frame.push(crc8(&frame));
+    frame
+}
+
+/// Measurement frame of an idle sensor holding [`HUMIDITY`] and [`TEMPERATURE`].
+pub fn frame() -> Vec<u8> {
+    frame_with_status(0x1C, HUMIDITY, TEMPERATURE)
+}
+
+/// Soft reset command.
+pub fn soft_reset() -> Transaction {
+    Transaction::write(consts::ADDRESS, vec![0xBA])
+}
+
+/// Status read answered with `status`.
+pub fn status(status: u8) -> Transaction {
+    Transaction::write_read(consts::ADDRESS, vec![0x71], vec![status])
+}
+
+/// Initialization command.
+pub fn initialize() -> Transaction {
+    Transaction::write(consts::ADDRESS, vec![0xBE, 0x08, 0x00])
+}
+
+/// Trigger measurement command.
+pub fn trigger() -> Transaction {
+    Transaction::write(consts::ADDRESS, vec![0xAC, 0x33, 0x00])
+}
+
+/// Measurement frame fetch answered with `frame`.
+pub fn fetch(frame: Vec<u8>) -> Transaction {
+    Transaction::write_read(consts::ADDRESS, vec![0x71], frame)
+}
+
+/// Initialization of a sensor already calibrated: soft reset, then a single status read.
+pub fn init() -> Vec<Transaction> {
+    vec![soft_reset(), status(IDLE)]
+}
+
+/// A wait requested from the driver.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Wait {
+    /// `delay_ms`.
+    Ms(u16),
+    /// `delay_us`.
+    Us(u32),
+}
+
+/// Delay recording every wait instead of waiting.
+#[derive(Debug, Default)]
+pub struct Recorder {
+    pub waits: Vec<Wait>,
+}
+
+impl Recorder {
+    /// Total time waited, in microseconds.
+    pub fn total_us(&self) -> u32 {
+        self.waits
+            .iter()
+            .map(|wait| match *wait {
+                Wait::Ms(ms) => ms as u32 * 1000,
+                Wait::Us(us) => us,
+            })
+            .sum()
+    }
+
+    /// Forgets the waits recorded so far.
+    pub fn clear(&mut self) {
+        self.waits.clear();
+    }
+}
+
+impl Delay for Recorder {
+    const MICROS: bool = true;
+
+    fn delay_ms(&mut self, ms: u16) {
+        self.waits.push(Wait::Ms(ms));
+    }
+
+    fn delay_us(&mut self, us: u32) {
+        self.waits.push(Wait::Us(us));
+    }
+}
+
+/// Transcripts for `embedded-hal` 1.0 mock buses, which see combined transactions as such.
+#[cfg(feature = "eh1")]
+pub mod eh1 {
+    use {aht20::consts, embedded_hal_mock::eh1::i2c::Transaction};
+
+    /// Write of `bytes`.
+    pub fn write(bytes: &[u8]) -> Vec<Transaction> {
+        vec![Transaction::write(consts::ADDRESS, bytes.to_vec())]
+    }
+
+    /// Combined transaction writing `bytes`, then reading `response`.
+    pub fn write_read(bytes: &[u8], response: Vec<u8>) -> Vec<Transaction> {
+        vec![
+            Transaction::transaction_start(consts::ADDRESS),
+            Transaction::write(consts::ADDRESS, bytes.to_vec()),
+            Transaction::read(consts::ADDRESS, response),
+            Transaction::transaction_end(consts::ADDRESS),
+        ]
+    }
+
+    /// Status read answered with `status`.
+    pub fn status(status: u8) -> Vec<Transaction> {
+        write_read(&[0x71], vec![status])
+    }
+}
+
+/// Async delay recording every wait instead of waiting, in microseconds.
+#[cfg(feature = "async")]
+#[derive(Debug, Default)]
+pub struct AsyncRecorder {
+    pub waits_us: Vec<u32>,
+}
+
+#[cfg(feature = "async")]
+impl embedded_hal_async::delay::DelayNs for AsyncRecorder {
+    async fn delay_ns(&mut self, ns: u32) {
+        self.waits_us.push(ns.div_ceil(1000));
+    }
+
+    async fn delay_us(&mut self, us: u32) {
+        self.waits_us.push(us);
+    }
+
+    async fn delay_ms(&mut self, ms: u32) {
+        self.waits_us.push(ms * 1000);
+    }
+}
@@ -0,0 +1,240 @@
+//! Conversions of raw readings, checked against hand-computed values.
+
+use aht20::{Humidity, Temperature};
+
+/// Largest raw reading, 20 bits.
+const FULL_SCALE: u32 = 0xFFFFF;
+
+/// Asserts that `actual` is within `epsilon` of `expected`.
+fn assert_near(actual: f32, expected: f32, epsilon: f32) {
+    assert!(
+        (actual - expected).abs() <= epsilon,
+        "{} is not within {} of {}",
+        actual,
+        epsilon,
+        expected
+    );
+}
+
+#[test]
+fn fahrenheit() {
+    // -50°C, -25°C, 0°C, 50°C and 150°C
+    assert_near(Temperature::from_raw(0).fahrenheit(), -58.0, 1e-4);
+    assert_near(Temperature::from_raw(0x20000).fahrenheit(), -13.0, 1e-4);
+    assert_near(Temperature::from_raw(0x40000).fahrenheit(), 32.0, 1e-4);
+    assert_near(Temperature::from_raw(0x80000).fahrenheit(), 122.0, 1e-4);
+    assert_near(
+        Temperature::from_raw(FULL_SCALE).fahrenheit(),
+        302.0 - 360.0 / 1048576.0,
+        1e-3,
+    );
+}
+
+#[test]
+fn kelvin() {
+    // Raw codes of -40°C and 85°C, the ends of the sensor's range, rounded
+    assert_near(Temperature::from_raw(52429).kelvin(), 233.15, 1e-3);
+    assert_near(Temperature::from_raw(707789).kelvin(), 358.15, 1e-3);
+
+    for raw in (0..=FULL_SCALE).step_by(997) {
+        let t = Temperature::from_raw(raw);
+        assert_near(t.kelvin() - t.celsius(), 273.15, 1e-3);
+    }
+}
+
+#[test]
+fn integer_conversions() {
+    // Every raw value, against the float paths
+    for raw in 0..=FULL_SCALE {
+        let t = Temperature::from_raw(raw);
+        let milli = t.celsius_milli();
+        assert!(
+            (milli as f32 - t.celsius() * 1000.0).abs() <= 1.0,
+            "raw {}",
+            raw
+        );
+        assert!(
+            (milli as f64 - t.celsius_f64() * 1000.0).abs() <= 0.5,
+            "raw {}",
+            raw
+        );
+
+        let h = Humidity::from_raw(raw);
+        let centi = h.rh_centi();
+        let milli = h.rh_milli();
+        assert!((centi as f32 - h.rh() * 100.0).abs() <= 1.0, "raw {}", raw);
+        assert!((milli as f32 - h.rh() * 1000.0).abs() <= 1.0, "raw {}", raw);
+        assert!(
+            (milli as f64 - h.rh_f64() * 1000.0).abs() <= 0.5,
+            "raw {}",
+            raw
+        );
+    }
+
+    // The ends of the range, across the -50°C offset
+    assert_eq!(Temperature::from_raw(0).celsius_milli(), -50_000);
+    assert_eq!(Temperature::from_raw(0x40000).celsius_milli(), 0);
+    assert_eq!(Temperature::from_raw(FULL_SCALE).celsius_milli(), 150_000);
+    assert_eq!(Humidity::from_raw(0).rh_centi(), 0);
+    assert_eq!(Humidity::from_raw(FULL_SCALE).rh_centi(), 10_000);
+    assert_eq!(Humidity::from_raw(FULL_SCALE).rh_milli(), 100_000);
+}
+
+#[test]
+fn double_precision() {
+    // One raw step is 200/2^20 °C and 100/2^20 %RH
+    let lsb_c = 200.0 / 1048576.0;
+    let lsb_rh = 100.0 / 1048576.0;
+
+    for raw in [FULL_SCALE, FULL_SCALE - 1, 0x80001, 1].iter().copied() {
+        let t = Temperature::from_raw(raw);
+        let exact = 200.0 * raw as f64 / 1048576.0 - 50.0;
+        assert!((t.celsius_f64() - exact).abs() < 1e-12);
+        let error = (t.celsius() as f64 - t.celsius_f64()).abs();
+        assert!(error < lsb_c / 4.0, "raw {}: {}", raw, error);
+
+        let h = Humidity::from_raw(raw);
+        let exact = 100.0 * raw as f64 / 1048576.0;
+        assert!((h.rh_f64() - exact).abs() < 1e-12);
+        let error = (h.rh() as f64 - h.rh_f64()).abs();
+        assert!(error < lsb_rh / 4.0, "raw {}: {}", raw, error);
+    }
+
+    // At full scale the f32 path rounds away a visible fraction of a step
+    let t = Temperature::from_raw(FULL_SCALE);
+    assert_ne!(t.celsius() as f64, t.celsius_f64());
+}
+
+#[test]
+fn rational_constants() {
+    use aht20::consts::{self, raw_to_celsius_milli, raw_to_rh_centi, raw_to_rh_milli};
+
+    // Usable in constants, e.g. thresholds computed at compile time
+    const FREEZING: i32 = raw_to_celsius_milli(consts::RAW_SCALE / 4);
+    const HALF: u16 = raw_to_rh_centi(consts::RAW_SCALE / 2);
+    assert_eq!(FREEZING, 0);
+    assert_eq!(HALF, 5_000);
+
+    // The documented rational, temperature = raw * 200 / 2^20 - 50
+    for raw in [0, 1, 0x20000, 0x40000, 0x60000, 0x80000, FULL_SCALE]
+        .iter()
+        .copied()
+    {
+        let celsius = (raw * consts::TEMPERATURE_SPAN_C) as f32 / consts::RAW_SCALE as f32
+            + consts::TEMPERATURE_OFFSET_C as f32;
+        assert_near(Temperature::from_raw(raw).celsius(), celsius, 1e-4);
+        assert_near(raw_to_celsius_milli(raw) as f32, celsius * 1000.0, 1.0);
+
+        let rh = (raw as u64 * consts::HUMIDITY_SPAN_RH as u64) as f32 / consts::RAW_SCALE as f32;
+        assert_near(Humidity::from_raw(raw).rh(), rh, 1e-4);
+        assert_near(raw_to_rh_milli(raw) as f32, rh * 1000.0, 1.0);
+    }
+}
+
+#[test]
+fn checked() {
+    use aht20::RangeError;
+
+    // The guard band reaches from -45°C to 90°C
+    assert_eq!(
+        Temperature::from_raw(26_211).celsius_checked(),
+        Err(RangeError::TooLow)
+    );
+    assert!(Temperature::from_raw(26_212).celsius_checked().is_ok());
+    assert!(Temperature::from_raw(734_005).celsius_checked().is_ok());
+    assert_eq!(
+        Temperature::from_raw(734_006).celsius_checked(),
+        Err(RangeError::TooHigh)
+    );
+
+    // Clamping instead is the caller's choice
+    assert_near(Temperature::from_raw(734_006).celsius_clamped(), 85.0, 1e-4);
+    assert_near(Temperature::from_raw(0).celsius_clamped(), -40.0, 1e-4);
+
+    // Twenty bits never exceed 100%
+    assert!(Humidity::from_raw(FULL_SCALE).rh_checked().is_ok());
+    assert_near(Humidity::from_raw(FULL_SCALE).rh_clamped(), 100.0, 1e-3);
+}
+
+#[test]
+fn deltas() {
+    // Across zero: -10°C to 10°C, and back
+    let cold = Temperature::from_raw(0x33333);
+    let warm = Temperature::from_raw(0x4CCCD);
+    assert_eq!((warm - cold).millikelvin(), 20_000);
+    assert_eq!((cold - warm).millikelvin(), -20_000);
+    assert_near((warm - cold).fahrenheit(), 36.0, 1e-3);
+    assert_eq!((warm - warm).millikelvin(), 0);
+
+    // The whole raw range, without overflow
+    let span = Temperature::from_raw(FULL_SCALE) - Temperature::from_raw(0);
+    assert_eq!(span.millikelvin(), 200_000);
+    let span = Humidity::from_raw(0) - Humidity::from_raw(FULL_SCALE);
+    assert_eq!(span.rh_centi(), -10_000);
+    assert_near(span.rh(), -100.0, 1e-3);
+
+    // Rounded once from the raw difference, a single code is 0.19 mK and 0.0095 %
+    let one = Temperature::from_raw(1) - Temperature::from_raw(0);
+    assert_eq!(one.millikelvin(), 0);
+    let step = Humidity::from_raw(0x80000 + 105) - Humidity::from_raw(0x80000);
+    assert_eq!(step.rh_centi(), 1);
+    assert!(Humidity::from_raw(10) - Humidity::from_raw(5) < step);
+}
+
+#[test]
+fn thresholds() {
+    // Round trips within half a code
+    for &celsius in &[-50.0, -40.0, -0.1, 0.0, 23.4, 85.0, 149.9] {
+        assert_near(Temperature::from_celsius(celsius).celsius(), celsius, 1e-4);
+    }
+    for &rh in &[0.0, 0.5, 45.2, 99.99] {
+        assert_near(Humidity::from_rh(rh).rh(), rh, 1e-4);
+    }
+
+    // Saturating at the ends of the raw range
+    assert_eq!(Temperature::from_celsius(-80.0).raw(), 0);
+    assert_eq!(Temperature::from_celsius(200.0).raw(), FULL_SCALE);
+    assert_eq!(Humidity::from_rh(-5.0).raw(), 0);
+    assert_eq!(Humidity::from_rh(120.0).raw(), FULL_SCALE);
+    assert_eq!(Humidity::from_rh(f32::NAN).raw(), 0);
+
+    // Compared against readings in the same type
+    let limit = Temperature::from_celsius(30.0);
+    assert!(Temperature::from_raw(0x60000) < limit);
+    assert!(Temperature::from_celsius(30.01) > limit);
+}
+
+#[test]
+fn from_raw() {
+    for raw in (0..=FULL_SCALE).step_by(4099).chain(Some(FULL_SCALE)) {
+        assert_eq!(Temperature::from_raw(raw).raw(), raw);
+        assert_eq!(Humidity::from_raw(raw).raw(), raw);
+    }
+
+    // Bits above the lowest 20 are masked off
+    assert_eq!(Temperature::from_raw(0x1_00000).raw(), 0);
+    assert_eq!(Humidity::from_raw(0xFFF_12345).raw(), 0x12345);
+
+    let reading = aht20::Reading::from_raw(0x12345, 0x6789A);
+    assert_eq!(reading.humidity, Humidity::from_raw(0x12345));
+    assert_eq!(reading.temperature, Temperature::from_raw(0x6789A));
+}
+
+#[test]
+fn monotonic() {
+    let mut previous = (Temperature::from_raw(0), Humidity::from_raw(0));
+    for raw in 1..=FULL_SCALE {
+        let t = Temperature::from_raw(raw);
+        let h = Humidity::from_raw(raw);
+        assert!(t.celsius() >= previous.0.celsius(), "raw {}", raw);
+        assert!(
+            t.celsius_milli() >= previous.0.celsius_milli(),
+            "raw {}",
+            raw
+        );
+        assert!(h.rh() >= previous.1.rh(), "raw {}", raw);
+        assert!(h.rh_centi() >= previous.1.rh_centi(), "raw {}", raw);
+        assert!(t > previous.0 && h > previous.1);
+        previous = (t, h);
+    }
+}
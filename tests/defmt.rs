@@ -0,0 +1,53 @@
+//! `defmt` support, checked from outside the crate as a downstream user would log.
+//!
+//! Nothing is logged, as host tests have no global logger: the tests fail to compile if an
+//! implementation goes missing.
+
+#![cfg(feature = "defmt")]
+
+use aht20::{
+    Aht20Builder, Config, CrcMode, Error, ErrorKind, FifoError, Humidity, HumidityDelta,
+    MinIntervalPolicy, ParseError, PollStrategy, RangeError, Reading, State, Status, Temperature,
+    TemperatureDelta, Variant,
+};
+
+/// Bus error of a downstream HAL, deriving `defmt::Format` itself.
+#[derive(defmt::Format)]
+enum BusError {
+    Nack,
+}
+
+fn format<T: defmt::Format + ?Sized>() {}
+
+#[test]
+fn readings() {
+    format::<Humidity>();
+    format::<Temperature>();
+    format::<Reading>();
+    format::<HumidityDelta>();
+    format::<TemperatureDelta>();
+    format::<[Reading]>();
+}
+
+#[test]
+fn errors_over_a_downstream_bus_error() {
+    format::<BusError>();
+    format::<Error<BusError>>();
+    format::<FifoError<BusError>>();
+    format::<ErrorKind>();
+    format::<RangeError>();
+    format::<ParseError>();
+    let _ = BusError::Nack;
+}
+
+#[test]
+fn configuration_and_status() {
+    format::<Config>();
+    format::<Aht20Builder>();
+    format::<PollStrategy>();
+    format::<MinIntervalPolicy>();
+    format::<Variant>();
+    format::<CrcMode>();
+    format::<State>();
+    format::<Status>();
+}
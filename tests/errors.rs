@@ -0,0 +1,404 @@
+//! Errors as seen by callers: their messages, sources and interoperability.
+
+mod common;
+
+use {
+    aht20::{hal::NackAware, Aht20, ConfigError, Error, ErrorKind},
+    common::{fetch, frame_with_status, init, soft_reset, status, trigger, Recorder, BUSY, IDLE},
+    core::fmt,
+    embedded_hal_mock::eh0::{
+        i2c::{Mock, Transaction},
+        MockError,
+    },
+    std::collections::HashSet,
+};
+
+/// Bus error of a downstream HAL, implementing `std::error::Error` itself.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+struct BusError;
+
+impl fmt::Display for BusError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("arbitration lost")
+    }
+}
+
+impl std::error::Error for BusError {}
+
+#[test]
+fn display() {
+    let cases: [(Error<BusError>, &str); 15] = [
+        (Error::Uncalibrated, "sensor not calibrated"),
+        (Error::Bus(BusError), "bus error: BusError"),
+        (
+            Error::Checksum {
+                expected: 0x5A,
+                computed: 0x0F,
+                frame: [0x1C, 0x80, 0x00, 0x06, 0x00, 0x00, 0x5A],
+            },
+            "checksum mismatch (expected 0x5a, computed 0x0f, frame [1c, 80, 00, 06, 00, 00, 5a])",
+        ),
+        (
+            Error::MaxTriesExceeded,
+            "sensor still busy after the maximum number of polls",
+        ),
+        (Error::NotInitialized, "driver not initialized"),
+        (
+            Error::InvalidConfig(ConfigError::ZeroRetries),
+            "invalid configuration: no busy polls allowed",
+        ),
+        (Error::Busy, "sensor busy measuring"),
+        (
+            Error::Timeout { waited_ms: 120 },
+            "measurement timed out after 120 ms",
+        ),
+        (Error::StuckBusy, "sensor stuck busy after a reset"),
+        (
+            Error::OutOfRange {
+                humidity: 1,
+                temperature: 734_006,
+            },
+            "reading out of range (raw humidity 1, raw temperature 734006)",
+        ),
+        (
+            Error::TooSoon { remaining_ms: 600 },
+            "measurement started 600 ms too soon",
+        ),
+        (Error::InvalidData, "invalid measurement data"),
+        (
+            Error::NeedsRecovery,
+            "driver needs recovery after a fatal error",
+        ),
+        (Error::DeviceNotFound, "no sensor found at the address"),
+        (Error::Unsupported, "not supported by the sensor model"),
+    ];
+
+    for (error, message) in cases.iter() {
+        assert_eq!(error.to_string(), *message);
+    }
+}
+
+#[test]
+fn sources() {
+    use std::error::Error as _;
+
+    let error: Error<BusError> = Error::Bus(BusError);
+    assert_eq!(error.source().unwrap().to_string(), "arbitration lost");
+    let error: Error<BusError> = Error::InvalidConfig(ConfigError::TimeoutTooShort);
+    assert_eq!(
+        error.source().unwrap().to_string(),
+        "timeout shorter than the first poll interval"
+    );
+    let error: Error<BusError> = Error::Busy;
+    assert!(error.source().is_none());
+}
+
+#[test]
+fn anyhow() {
+    fn read() -> anyhow::Result<()> {
+        Err(Error::Bus(BusError))?;
+        Ok(())
+    }
+
+    let error = read().unwrap_err();
+    assert_eq!(error.to_string(), "bus error: BusError");
+    assert_eq!(error.chain().count(), 2);
+    assert_eq!(
+        error.downcast_ref::<Error<BusError>>(),
+        Some(&Error::Bus(BusError))
+    );
+    assert_eq!(error.root_cause().to_string(), "arbitration lost");
+
+    // Context wraps the driver error in turn
+    let error = anyhow::Context::context(Err::<(), _>(Error::<BusError>::Busy), "reading sensor")
+        .unwrap_err();
+    assert_eq!(
+        format!("{:#}", error),
+        "reading sensor: sensor busy measuring"
+    );
+}
+
+/// Every variant, with a payload where it has one.
+fn every_variant() -> Vec<Error<BusError>> {
+    vec![
+        Error::Uncalibrated,
+        Error::Bus(BusError),
+        Error::Checksum {
+            expected: 0,
+            computed: 1,
+            frame: [0; 7],
+        },
+        Error::MaxTriesExceeded,
+        Error::NotInitialized,
+        Error::InvalidConfig(ConfigError::TimeoutTooLong),
+        Error::Busy,
+        Error::Timeout { waited_ms: 1 },
+        Error::StuckBusy,
+        Error::OutOfRange {
+            humidity: 0,
+            temperature: 0,
+        },
+        Error::TooSoon { remaining_ms: 1 },
+        Error::InvalidData,
+        Error::NeedsRecovery,
+        Error::DeviceNotFound,
+        Error::Unsupported,
+    ]
+}
+
+#[test]
+fn kinds() {
+    // One kind per variant, kept by `map_bus`
+    let kinds: HashSet<ErrorKind> = every_variant().iter().map(Error::kind).collect();
+    assert_eq!(kinds.len(), 15);
+    for error in every_variant() {
+        let kind = error.kind();
+        assert_eq!(error.map_bus(|_| 0u8).kind(), kind);
+    }
+    assert_eq!(Error::Bus(BusError).map_bus(|_| 7u8), Error::<u8>::Bus(7));
+
+    let fatal: Vec<ErrorKind> = every_variant()
+        .iter()
+        .filter(|e| e.is_fatal())
+        .map(Error::kind)
+        .collect();
+    assert_eq!(
+        fatal,
+        [
+            ErrorKind::MaxTriesExceeded,
+            ErrorKind::Timeout,
+            ErrorKind::StuckBusy
+        ]
+    );
+}
+
+/// Reads `count` times after the initialization, against `transcript`, returning each result.
+fn reads(transcript: &[Transaction], count: usize) -> Vec<Result<(), Error<MockError>>> {
+    let mut expectations = init();
+    expectations.extend_from_slice(transcript);
+    let mut i2c = Mock::new(&expectations);
+    let mut delay = Recorder::default();
+
+    let mut dev = Aht20::new(i2c.clone(), &mut delay).unwrap();
+    let results = (0..count)
+        .map(|_| dev.read(&mut delay).map(|_| ()))
+        .collect();
+    i2c.done();
+    results
+}
+
+#[test]
+fn invalid_data() {
+    let empty = frame_with_status(0x1C, 0, 0);
+    let results = reads(&[trigger(), status(IDLE), fetch(empty)], 1);
+    assert_eq!(results, [Err(Error::InvalidData)]);
+}
+
+#[test]
+fn needs_recovery() {
+    // Polls run out, then the driver refuses to measure without touching the bus
+    let mut transcript = vec![trigger()];
+    transcript.extend((0..6).map(|_| status(BUSY)));
+    let results = reads(&transcript, 2);
+    assert_eq!(
+        results,
+        [Err(Error::MaxTriesExceeded), Err(Error::NeedsRecovery)]
+    );
+}
+
+#[test]
+fn polls_and_time_budgets_running_out() {
+    use common::initialize;
+
+    /// Initializes a driver against `transcript`, following the initialization.
+    fn new(transcript: &[Transaction]) -> (Aht20<Mock>, Mock, Recorder) {
+        let mut expectations = init();
+        expectations.extend_from_slice(transcript);
+        let i2c = Mock::new(&expectations);
+        let mut delay = Recorder::default();
+        match Aht20::new(i2c.clone(), &mut delay) {
+            Ok(dev) => (dev, i2c, delay),
+            Err(e) => panic!("failed to initialize: {:?}", e.error),
+        }
+    }
+
+    /// Clock advancing 10ms on each call.
+    fn ticking() -> impl FnMut() -> u32 {
+        let mut now = 0;
+        move || {
+            now += 10;
+            now
+        }
+    }
+
+    let busy = || fetch(frame_with_status(BUSY, 0, 0));
+
+    // A measurement out of polls, then out of time
+    let mut transcript = vec![trigger()];
+    transcript.extend((0..6).map(|_| status(BUSY)));
+    let (mut dev, mut i2c, mut delay) = new(&transcript);
+    let result = dev.read(&mut delay).map(|_| ());
+    assert_eq!(result, Err(Error::MaxTriesExceeded));
+    i2c.done();
+
+    let mut transcript = vec![trigger()];
+    transcript.extend((0..5).map(|_| busy()));
+    let (mut dev, mut i2c, mut delay) = new(&transcript);
+    let result = dev.read_with_timeout(&mut delay, 20).map(|_| ());
+    assert_eq!(result, Err(Error::Timeout { waited_ms: 20 }));
+    i2c.done();
+
+    let mut transcript = vec![trigger()];
+    transcript.extend((0..3).map(|_| status(BUSY)));
+    let (mut dev, mut i2c, mut delay) = new(&transcript);
+    let result = dev.read_until(&mut delay, ticking(), 25).map(|_| ());
+    assert_eq!(result, Err(Error::Timeout { waited_ms: 30 }));
+    i2c.done();
+
+    // A calibration out of polls, then out of time
+    let mut transcript = vec![initialize()];
+    transcript.extend((0..11).map(|_| status(BUSY)));
+    let (mut dev, mut i2c, mut delay) = new(&transcript);
+    assert_eq!(dev.calibrate(&mut delay), Err(Error::MaxTriesExceeded));
+    i2c.done();
+
+    let mut transcript = vec![initialize()];
+    transcript.extend((0..3).map(|_| status(BUSY)));
+    let (mut dev, mut i2c, mut delay) = new(&transcript);
+    let result = dev.calibrate_until(&mut delay, ticking(), 25);
+    assert_eq!(result, Err(Error::Timeout { waited_ms: 30 }));
+    i2c.done();
+}
+
+#[test]
+fn unsupported() {
+    let mut i2c = Mock::new(&init());
+    let mut dev = Aht20::new(i2c.clone(), &mut Recorder::default()).unwrap();
+    assert_eq!(dev.enable_fifo(), Err(Error::Unsupported));
+    assert_eq!(dev.fifo_status(), Err(Error::Unsupported));
+    i2c.done();
+}
+
+#[test]
+fn device_not_found() {
+    const NACK: MockError = MockError::Io(std::io::ErrorKind::NotFound);
+
+    let mut i2c = Mock::new(&[soft_reset().with_error(NACK)]);
+    let bus = NackAware::new(i2c.clone(), |e| *e == NACK);
+    match Aht20::new(bus, &mut Recorder::default()) {
+        Ok(_) => panic!("initialized without a sensor"),
+        Err(e) => assert_eq!(e.error, Error::DeviceNotFound),
+    }
+    i2c.done();
+
+    // A NACK later on is a bus error, as the sensor was there
+    let mut expectations = init();
+    expectations.push(trigger().with_error(NACK));
+    let mut i2c = Mock::new(&expectations);
+    let bus = NackAware::new(i2c.clone(), |e| *e == NACK);
+    let mut delay = Recorder::default();
+    let mut dev = Aht20::new(bus, &mut delay).unwrap();
+    assert_eq!(dev.read(&mut delay).map(|_| ()), Err(Error::Bus(NACK)));
+    i2c.done();
+}
+
+#[test]
+fn map_bus() {
+    // Payloads survive a round trip through another bus error type
+    for error in every_variant() {
+        let mapped: Error<u8> = error.map_bus(|_| 1);
+        assert_eq!(mapped.map_bus(|_| BusError), error);
+    }
+
+    // A downstream error type converts in one line
+    #[derive(Debug, PartialEq)]
+    enum BoardError {
+        I2c(&'static str),
+        Sensor(ErrorKind),
+    }
+
+    impl From<Error<BusError>> for BoardError {
+        fn from(e: Error<BusError>) -> Self {
+            match e.map_bus(|_| "i2c") {
+                Error::Bus(bus) => BoardError::I2c(bus),
+                other => BoardError::Sensor(other.kind()),
+            }
+        }
+    }
+
+    assert_eq!(
+        BoardError::from(Error::Bus(BusError)),
+        BoardError::I2c("i2c")
+    );
+    assert_eq!(
+        BoardError::from(Error::Timeout { waited_ms: 5 }),
+        BoardError::Sensor(ErrorKind::Timeout)
+    );
+}
+
+#[test]
+fn kind_of_each_variant() {
+    let kinds = [
+        ErrorKind::Uncalibrated,
+        ErrorKind::Bus,
+        ErrorKind::Checksum,
+        ErrorKind::MaxTriesExceeded,
+        ErrorKind::NotInitialized,
+        ErrorKind::InvalidConfig,
+        ErrorKind::Busy,
+        ErrorKind::Timeout,
+        ErrorKind::StuckBusy,
+        ErrorKind::OutOfRange,
+        ErrorKind::TooSoon,
+        ErrorKind::InvalidData,
+        ErrorKind::NeedsRecovery,
+        ErrorKind::DeviceNotFound,
+        ErrorKind::Unsupported,
+    ];
+    let actual: Vec<ErrorKind> = every_variant().iter().map(Error::kind).collect();
+    assert_eq!(actual, kinds);
+}
+
+#[test]
+fn equality() {
+    // Each variant equals itself and no other
+    let variants = every_variant();
+    for (i, a) in variants.iter().enumerate() {
+        for (j, b) in variants.iter().enumerate() {
+            assert_eq!(a == b, i == j, "{:?} and {:?}", a, b);
+        }
+    }
+
+    // Payloads are compared, bus errors included
+    let nack: Error<MockError> = Error::Bus(MockError::Io(std::io::ErrorKind::NotFound));
+    let fault: Error<MockError> = Error::Bus(MockError::Io(std::io::ErrorKind::Other));
+    assert_ne!(nack, fault);
+    assert_eq!(nack, nack.clone());
+    assert_ne!(
+        Error::<BusError>::Timeout { waited_ms: 1 },
+        Error::Timeout { waited_ms: 2 }
+    );
+    assert_ne!(
+        Error::<BusError>::OutOfRange {
+            humidity: 0,
+            temperature: 1,
+        },
+        Error::OutOfRange {
+            humidity: 0,
+            temperature: 2,
+        }
+    );
+
+    // Checksum mismatches compare their frames too
+    let checksum = |last| Error::<BusError>::Checksum {
+        expected: 0,
+        computed: 1,
+        frame: [0, 0, 0, 0, 0, 0, last],
+    };
+    assert_eq!(checksum(0), checksum(0));
+    assert_ne!(checksum(0), checksum(1));
+
+    // Eq too, when the bus error is
+    fn eq<T: Eq>(_: &T) {}
+    eq(&checksum(0));
+}
@@ -0,0 +1,148 @@
+//! The AHT10's measurement FIFO: enabling it, its flags and draining it.
+
+mod common;
+
+use {
+    aht20::{consts::ADDRESS, Aht20, Error, FifoError, FifoStatus, Reading},
+    common::{crc8, frame_with_status, Recorder, HUMIDITY, IDLE, TEMPERATURE},
+    core::convert::TryInto,
+    embedded_hal_mock::eh0::i2c::{Mock, Transaction},
+};
+
+/// Status byte of a calibrated sensor with a FIFO holding measurements.
+const FILLED: u8 = 0x1C;
+/// Status byte of a calibrated sensor with an enabled, empty FIFO.
+const EMPTY: u8 = 0x1D;
+
+/// Status read answered with `response`, through the AHT10's `0x00`.
+fn status(response: Vec<u8>) -> Transaction {
+    Transaction::write_read(ADDRESS, vec![0x00], response)
+}
+
+/// Buffered frame number `n`, with distinct readings.
+fn buffered(n: u32) -> Vec<u8> {
+    frame_with_status(FILLED, HUMIDITY + n, TEMPERATURE + n)
+}
+
+/// Frame reporting the FIFO empty.
+fn empty() -> Vec<u8> {
+    frame_with_status(EMPTY, 0, 0)
+}
+
+/// An AHT10 driver initialized against its calibrated status, then expecting `expectations`.
+fn aht10(expectations: &[Transaction]) -> (Aht20<Mock>, Mock) {
+    let mut transcript = vec![Transaction::write(ADDRESS, vec![0xBA]), status(vec![IDLE])];
+    transcript.extend_from_slice(expectations);
+    let i2c = Mock::new(&transcript);
+    match Aht20::new_aht10(i2c.clone(), &mut Recorder::default()) {
+        Ok(dev) => (dev, i2c),
+        Err(e) => panic!("failed to initialize: {:?}", e.error),
+    }
+}
+
+/// Output slots to drain into.
+fn slots<const N: usize>() -> [Reading; N] {
+    [Reading::from_raw(0, 0); N]
+}
+
+#[test]
+fn enable_and_disable() {
+    let (mut dev, mut i2c) = aht10(&[
+        Transaction::write(ADDRESS, vec![0xE1, 0x0C, 0x00]),
+        Transaction::write(ADDRESS, vec![0xE1, 0x08, 0x00]),
+    ]);
+    dev.enable_fifo().unwrap();
+    dev.disable_fifo().unwrap();
+    i2c.done();
+}
+
+#[test]
+fn fifo_status() {
+    let (mut dev, mut i2c) = aht10(&[status(vec![0x1E]), status(vec![EMPTY]), status(vec![IDLE])]);
+    let status = |enabled, full, empty| FifoStatus {
+        enabled,
+        full,
+        empty,
+    };
+    assert_eq!(dev.fifo_status(), Ok(status(true, true, false)));
+    assert_eq!(dev.fifo_status(), Ok(status(true, false, true)));
+    assert_eq!(dev.fifo_status(), Ok(status(false, false, false)));
+    i2c.done();
+}
+
+#[test]
+fn drains_until_empty() {
+    let (mut dev, mut i2c) = aht10(&[
+        status(buffered(0)),
+        status(buffered(1)),
+        status(buffered(2)),
+        status(empty()),
+    ]);
+    let mut out = slots::<8>();
+    assert_eq!(dev.read_fifo(&mut out), Ok(3));
+    for (n, reading) in out[..3].iter().enumerate() {
+        assert_eq!(reading.humidity.raw(), HUMIDITY + n as u32);
+        assert_eq!(reading.temperature.raw(), TEMPERATURE + n as u32);
+        assert!(reading.crc_verified);
+    }
+    i2c.done();
+}
+
+#[test]
+fn empty_drain() {
+    let (mut dev, mut i2c) = aht10(&[status(empty())]);
+    assert_eq!(dev.read_fifo(&mut slots::<8>()), Ok(0));
+    i2c.done();
+}
+
+#[test]
+fn stops_when_the_output_is_full() {
+    let (mut dev, mut i2c) = aht10(&[status(buffered(0)), status(buffered(1))]);
+    assert_eq!(dev.read_fifo(&mut slots::<2>()), Ok(2));
+
+    // Nothing is read into no room
+    assert_eq!(dev.read_fifo(&mut []), Ok(0));
+    i2c.done();
+}
+
+#[test]
+fn failure_counts_the_readings_drained() {
+    let mut corrupted = buffered(1);
+    corrupted[6] ^= 0x01;
+    let (mut dev, mut i2c) = aht10(&[status(buffered(0)), status(corrupted.clone())]);
+    let mut out = slots::<8>();
+    assert_eq!(
+        dev.read_fifo(&mut out),
+        Err(FifoError {
+            read: 1,
+            error: Error::Checksum {
+                expected: corrupted[6],
+                computed: crc8(&corrupted[..6]),
+                frame: corrupted.as_slice().try_into().unwrap(),
+            },
+        })
+    );
+    assert_eq!(out[0].humidity.raw(), HUMIDITY);
+    i2c.done();
+}
+
+#[test]
+fn unsupported_without_a_fifo() {
+    // No bus traffic past initialization
+    let mut i2c = Mock::new(&common::init());
+    let mut dev = match Aht20::new(i2c.clone(), &mut Recorder::default()) {
+        Ok(dev) => dev,
+        Err(e) => panic!("failed to initialize: {:?}", e.error),
+    };
+    assert_eq!(dev.enable_fifo(), Err(Error::Unsupported));
+    assert_eq!(dev.disable_fifo(), Err(Error::Unsupported));
+    assert_eq!(dev.fifo_status(), Err(Error::Unsupported));
+    assert_eq!(
+        dev.read_fifo(&mut slots::<8>()),
+        Err(FifoError {
+            read: 0,
+            error: Error::Unsupported,
+        })
+    );
+    i2c.done();
+}
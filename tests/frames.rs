@@ -0,0 +1,232 @@
+//! Parsing of measurement frames into readings, as captured off the bus.
+
+mod common;
+
+use {
+    aht20::{Aht20, ParseError, Reading},
+    common::{
+        crc8, fetch, frame, frame_with_status, init, status, trigger, Recorder, HUMIDITY, IDLE,
+        TEMPERATURE,
+    },
+    core::convert::{TryFrom, TryInto},
+    embedded_hal_mock::eh0::i2c::Mock,
+};
+
+/// The reading of [`frame`].
+fn expected() -> Reading {
+    Reading {
+        crc_verified: true,
+        ..Reading::from_raw(HUMIDITY, TEMPERATURE)
+    }
+}
+
+#[test]
+fn valid() {
+    let buf: [u8; 7] = frame().try_into().unwrap();
+    assert_eq!(Reading::try_from(buf), Ok(expected()));
+    assert_eq!(Reading::try_from(&frame()[..]), Ok(expected()));
+
+    // Bits of both values meet in the middle byte
+    let buf = frame_with_status(0x1C, 0xABCDE, 0xF1234);
+    let reading = Reading::try_from(&buf[..]).unwrap();
+    assert_eq!(reading.humidity.raw(), 0xABCDE);
+    assert_eq!(reading.temperature.raw(), 0xF1234);
+}
+
+#[test]
+fn bad_crc() {
+    let mut buf = frame();
+    let crc = buf[6];
+    buf[6] ^= 0x01;
+    assert_eq!(
+        Reading::try_from(&buf[..]),
+        Err(ParseError::Checksum {
+            expected: crc ^ 0x01,
+            computed: crc,
+        })
+    );
+
+    // A flipped data bit changes the computed CRC instead
+    let mut buf = frame();
+    buf[3] ^= 0x10;
+    assert_eq!(
+        Reading::try_from(&buf[..]),
+        Err(ParseError::Checksum {
+            expected: crc,
+            computed: crc8(&buf[..6]),
+        })
+    );
+}
+
+#[test]
+fn busy() {
+    let buf = frame_with_status(0x9C, HUMIDITY, TEMPERATURE);
+    assert_eq!(Reading::try_from(&buf[..]), Err(ParseError::Busy));
+
+    // Checked before the CRC, which is stale as well
+    let mut buf = buf;
+    buf[6] ^= 0xFF;
+    assert_eq!(Reading::try_from(&buf[..]), Err(ParseError::Busy));
+}
+
+#[test]
+fn uncalibrated_and_empty() {
+    let buf = frame_with_status(0x14, HUMIDITY, TEMPERATURE);
+    assert_eq!(Reading::try_from(&buf[..]), Err(ParseError::Uncalibrated));
+
+    let buf = frame_with_status(0x1C, 0, 0);
+    assert_eq!(Reading::try_from(&buf[..]), Err(ParseError::InvalidData));
+    let buf = frame_with_status(0x1C, 0xFFFFF, 0xFFFFF);
+    assert_eq!(Reading::try_from(&buf[..]), Err(ParseError::InvalidData));
+}
+
+#[test]
+fn wrong_length() {
+    let buf = frame();
+    assert_eq!(Reading::try_from(&buf[..6]), Err(ParseError::InvalidLength));
+    assert_eq!(Reading::try_from(&[][..]), Err(ParseError::InvalidLength));
+
+    let mut long = buf.clone();
+    long.push(0);
+    assert_eq!(Reading::try_from(&long[..]), Err(ParseError::InvalidLength));
+}
+
+#[test]
+fn read_reading() {
+    let mut expectations = init();
+    expectations.extend_from_slice(&[trigger(), status(IDLE), fetch(frame())]);
+    let mut i2c = Mock::new(&expectations);
+    let mut delay = Recorder::default();
+
+    let mut dev = Aht20::new(i2c.clone(), &mut delay).unwrap();
+    assert_eq!(dev.read_reading(&mut delay), Ok(expected()));
+    i2c.done();
+}
+
+mod crc_mode {
+    use {
+        super::*,
+        aht20::{Aht20Builder, CrcMode, Error},
+        embedded_hal_mock::eh0::MockError,
+    };
+
+    /// Reads a measurement with CRC `mode`, the driver fetching `response`.
+    fn read(mode: CrcMode, response: &[u8]) -> Result<Reading, Error<MockError>> {
+        let mut expectations = init();
+        expectations.extend_from_slice(&[trigger(), status(IDLE), fetch(response.to_vec())]);
+        let mut i2c = Mock::new(&expectations);
+        let mut delay = Recorder::default();
+
+        let mut dev = match Aht20Builder::new().crc(mode).build(i2c.clone(), &mut delay) {
+            Ok(dev) => dev,
+            Err(e) => panic!("failed to initialize: {:?}", e.error),
+        };
+        let reading = dev.read_reading(&mut delay);
+        i2c.done();
+        reading
+    }
+
+    /// [`frame`] with its CRC byte flipped.
+    fn bad() -> Vec<u8> {
+        let mut buf = frame();
+        buf[6] ^= 0x01;
+        buf
+    }
+
+    /// [`frame`] from a part sending no CRC byte, the bus reading `0xFF` in its place.
+    fn no_crc() -> Vec<u8> {
+        let mut buf = frame();
+        buf[6] = 0xFF;
+        buf
+    }
+
+    /// The reading of [`frame`], unverified.
+    fn unverified() -> Reading {
+        Reading::from_raw(HUMIDITY, TEMPERATURE)
+    }
+
+    /// The checksum error of a `frame` failing its CRC.
+    fn mismatch(frame: Vec<u8>) -> Error<MockError> {
+        Error::Checksum {
+            expected: frame[6],
+            computed: crc8(&frame[..6]),
+            frame: frame.as_slice().try_into().unwrap(),
+        }
+    }
+
+    #[test]
+    fn required() {
+        assert_eq!(read(CrcMode::Required, &frame()), Ok(expected()));
+        assert_eq!(read(CrcMode::Required, &bad()), Err(mismatch(bad())));
+        assert_eq!(read(CrcMode::Required, &no_crc()), Err(mismatch(no_crc())));
+    }
+
+    #[test]
+    fn optional() {
+        assert_eq!(read(CrcMode::Optional, &frame()), Ok(expected()));
+        assert_eq!(read(CrcMode::Optional, &bad()), Err(mismatch(bad())));
+        assert_eq!(read(CrcMode::Optional, &no_crc()), Ok(unverified()));
+    }
+
+    #[test]
+    fn disabled() {
+        // Only 6 bytes are read, as the mock checks, whatever the part would send as its 7th
+        assert_eq!(read(CrcMode::Disabled, &frame()[..6]), Ok(unverified()));
+        assert_eq!(read(CrcMode::Disabled, &bad()[..6]), Ok(unverified()));
+        assert_eq!(read(CrcMode::Disabled, &no_crc()[..6]), Ok(unverified()));
+    }
+
+    #[test]
+    fn from_status() {
+        // CRC flag set: checked as if required
+        assert_eq!(read(CrcMode::FromStatus, &frame()), Ok(expected()));
+        assert_eq!(read(CrcMode::FromStatus, &bad()), Err(mismatch(bad())));
+
+        // CRC flag clear: the 7th byte is read but ignored
+        let mut clear = frame_with_status(0x0C, HUMIDITY, TEMPERATURE);
+        clear[6] = 0xFF;
+        assert_eq!(read(CrcMode::FromStatus, &clear), Ok(unverified()));
+        clear[6] = 0x00;
+        assert_eq!(read(CrcMode::FromStatus, &clear), Ok(unverified()));
+    }
+
+    #[test]
+    fn resolved_from_status() {
+        use aht20::Status;
+
+        let with_crc = Status::from_bits(0x18);
+        let without_crc = Status::from_bits(0x08);
+        assert!(with_crc.has_crc() && !without_crc.has_crc());
+
+        assert_eq!(CrcMode::FromStatus.for_status(with_crc), CrcMode::Required);
+        assert_eq!(
+            CrcMode::FromStatus.for_status(without_crc),
+            CrcMode::Disabled
+        );
+
+        // Other modes do not depend on the flag
+        for mode in [CrcMode::Required, CrcMode::Optional, CrcMode::Disabled] {
+            assert_eq!(mode.for_status(with_crc), mode);
+            assert_eq!(mode.for_status(without_crc), mode);
+        }
+    }
+
+    #[test]
+    fn effective_crc_mode() {
+        let mut expectations = init();
+        expectations.extend_from_slice(&[status(0x18), status(0x08)]);
+        let mut i2c = Mock::new(&expectations);
+        let mut delay = Recorder::default();
+
+        let mut dev = match Aht20Builder::new()
+            .crc(CrcMode::FromStatus)
+            .build(i2c.clone(), &mut delay)
+        {
+            Ok(dev) => dev,
+            Err(e) => panic!("failed to initialize: {:?}", e.error),
+        };
+        assert_eq!(dev.effective_crc_mode(), Ok(CrcMode::Required));
+        assert_eq!(dev.effective_crc_mode(), Ok(CrcMode::Disabled));
+        i2c.done();
+    }
+}
@@ -0,0 +1,333 @@
+//! The driver over each supported set of HAL traits.
+
+mod common;
+
+use {
+    aht20::{hal::Delay, Aht20},
+    common::{fetch, frame, init, status, trigger, BUSY, HUMIDITY, IDLE, TEMPERATURE},
+    embedded_hal::blocking::delay::{DelayMs, DelayUs},
+    embedded_hal_mock::eh0::{delay::NoopDelay, i2c::Mock},
+};
+
+/// `embedded-hal` 0.2 delay of every width, recording each wait in microseconds.
+#[derive(Default)]
+struct Waits(Vec<u32>);
+
+impl DelayMs<u8> for Waits {
+    fn delay_ms(&mut self, ms: u8) {
+        self.0.push(ms as u32 * 1000);
+    }
+}
+
+impl DelayMs<u16> for Waits {
+    fn delay_ms(&mut self, ms: u16) {
+        self.0.push(ms as u32 * 1000);
+    }
+}
+
+impl DelayMs<u32> for Waits {
+    fn delay_ms(&mut self, ms: u32) {
+        self.0.push(ms * 1000);
+    }
+}
+
+impl DelayUs<u32> for Waits {
+    fn delay_us(&mut self, us: u32) {
+        self.0.push(us);
+    }
+}
+
+/// Initializes the driver and reads a measurement ready on the second poll, with `delay`.
+fn init_and_read<D: Delay>(mut delay: D) -> D {
+    let mut expectations = init();
+    expectations.extend([trigger(), status(BUSY), status(IDLE), fetch(frame())]);
+    let mut i2c = Mock::new(&expectations);
+
+    let mut dev = Aht20::new(i2c.clone(), &mut delay).unwrap();
+    dev.read(&mut delay).unwrap();
+    i2c.done();
+    delay
+}
+
+#[test]
+fn embedded_hal_02() {
+    let mut expectations = init();
+    expectations.extend([trigger(), status(IDLE), fetch(frame())]);
+    let mut i2c = Mock::new(&expectations);
+    let mut delay = NoopDelay::new();
+
+    let mut dev = Aht20::new(i2c.clone(), &mut delay).unwrap();
+    let (humidity, temperature) = dev.read(&mut delay).unwrap();
+    assert_eq!((humidity.raw(), temperature.raw()), (HUMIDITY, TEMPERATURE));
+    i2c.done();
+}
+
+#[cfg(feature = "eh1")]
+#[test]
+fn embedded_hal_1() {
+    use {
+        aht20::eh1::Eh1,
+        common::eh1,
+        embedded_hal_mock::eh1::{delay::NoopDelay, i2c::Mock},
+    };
+
+    let expectations = [
+        eh1::write(&[0xBA]),
+        eh1::status(IDLE),
+        eh1::write(&[0xAC, 0x33, 0x00]),
+        eh1::write_read(&[0x71], frame()),
+    ]
+    .concat();
+    let mut i2c = Mock::new(&expectations);
+    let mut delay = NoopDelay::new();
+
+    let mut dev = Aht20::new(Eh1(i2c.clone()), &mut Eh1(&mut delay)).unwrap();
+    let (humidity, temperature) = dev.read(&mut Eh1(&mut delay)).unwrap();
+    assert_eq!((humidity.raw(), temperature.raw()), (HUMIDITY, TEMPERATURE));
+    i2c.done();
+}
+
+#[test]
+fn split_transactions() {
+    use {
+        aht20::{consts::ADDRESS, hal::Split},
+        embedded_hal_mock::eh0::i2c::Transaction,
+    };
+
+    // Each combined transaction becomes a write followed by a separate read
+    let expectations = [
+        Transaction::write(ADDRESS, vec![0xBA]),
+        Transaction::write(ADDRESS, vec![0x71]),
+        Transaction::read(ADDRESS, vec![IDLE]),
+        trigger(),
+        Transaction::write(ADDRESS, vec![0x71]),
+        Transaction::read(ADDRESS, vec![IDLE]),
+        Transaction::write(ADDRESS, vec![0x71]),
+        Transaction::read(ADDRESS, frame()),
+    ];
+    let mut i2c = Mock::new(&expectations);
+    let mut delay = NoopDelay::new();
+
+    let mut dev = Aht20::new(Split(i2c.clone()), &mut delay).unwrap();
+    let (humidity, temperature) = dev.read(&mut delay).unwrap();
+    assert_eq!((humidity.raw(), temperature.raw()), (HUMIDITY, TEMPERATURE));
+    i2c.done();
+}
+
+#[cfg(feature = "eh1")]
+#[test]
+fn repeated_start_fetch() {
+    use {
+        aht20::eh1::Eh1,
+        common::{eh1, frame_with_status, BUSY},
+        embedded_hal_mock::eh1::{delay::NoopDelay, i2c::Mock},
+    };
+
+    // The busy bit is read from the frame itself, without a separate status poll
+    let expectations = [
+        eh1::write(&[0xBA]),
+        eh1::status(IDLE),
+        eh1::write(&[0xAC, 0x33, 0x00]),
+        eh1::write_read(&[0x71], frame_with_status(BUSY, 0, 0)),
+        eh1::write_read(&[0x71], frame_with_status(BUSY, 0, 0)),
+        eh1::write_read(&[0x71], frame()),
+    ]
+    .concat();
+    let mut i2c = Mock::new(&expectations);
+    let mut delay = NoopDelay::new();
+
+    let mut dev = Aht20::new(Eh1(i2c.clone()), &mut Eh1(&mut delay)).unwrap();
+    let (humidity, temperature) = dev.read(&mut Eh1(&mut delay)).unwrap();
+    assert_eq!((humidity.raw(), temperature.raw()), (HUMIDITY, TEMPERATURE));
+    i2c.done();
+}
+
+#[test]
+fn delay_widths() {
+    use aht20::hal::{Micros, MsU32, MsU8};
+
+    let expected = [20_000, 80_000, 10_000];
+    assert_eq!(init_and_read(Waits::default()).0, expected);
+    assert_eq!(init_and_read(MsU8(Waits::default())).0 .0, expected);
+    assert_eq!(init_and_read(MsU32(Waits::default())).0 .0, expected);
+    assert_eq!(init_and_read(Micros(Waits::default())).0 .0, expected);
+}
+
+#[test]
+fn bus_hook() {
+    use {
+        aht20::Error,
+        core::cell::Cell,
+        embedded_hal::blocking::i2c::Write,
+        embedded_hal_mock::eh0::{i2c::Transaction, MockError},
+    };
+
+    /// Selects the sensor's channel on a TCA9548A.
+    fn select() -> Transaction {
+        Transaction::write(0x70, vec![0x04])
+    }
+
+    // The channel is selected once before each operation
+    let expectations = [
+        select(),
+        common::soft_reset(),
+        select(),
+        status(IDLE),
+        select(),
+        trigger(),
+        select(),
+        status(IDLE),
+        select(),
+        fetch(frame()),
+    ];
+    let mut i2c = Mock::new(&expectations);
+    let mut delay = NoopDelay::new();
+    let fail = Cell::new(false);
+    let hook = |bus: &mut Mock| match fail.get() {
+        true => Err(MockError::Io(std::io::ErrorKind::Other)),
+        false => bus.write(0x70, &[0x04]),
+    };
+
+    let mut dev = Aht20::with_bus_hook(i2c.clone(), hook, &mut delay).unwrap();
+    let (humidity, temperature) = dev.read(&mut delay).unwrap();
+    assert_eq!((humidity.raw(), temperature.raw()), (HUMIDITY, TEMPERATURE));
+
+    fail.set(true);
+    assert_eq!(
+        dev.is_ready(),
+        Err(Error::Bus(MockError::Io(std::io::ErrorKind::Other)))
+    );
+    i2c.done();
+}
+
+#[test]
+fn bus_hook_runs_once_per_operation() {
+    use {
+        aht20::{consts::ADDRESS, hal::Split},
+        core::cell::Cell,
+        embedded_hal_mock::eh0::{i2c::Transaction, MockError},
+    };
+
+    // One read: the trigger, retried once, two status polls and the fetch
+    let expectations = [
+        common::soft_reset(),
+        status(IDLE),
+        trigger().with_error(MockError::Io(std::io::ErrorKind::Other)),
+        trigger(),
+        status(BUSY),
+        status(IDLE),
+        fetch(frame()),
+    ];
+    let mut i2c = Mock::new(&expectations);
+    let mut delay = NoopDelay::new();
+    let calls = Cell::new(0);
+    let hook = |_: &mut Mock| {
+        calls.set(calls.get() + 1);
+        Ok(())
+    };
+
+    let mut dev = Aht20::with_bus_hook(i2c.clone(), hook, &mut delay).unwrap();
+    dev.set_bus_retries(1);
+    calls.set(0);
+    dev.read(&mut delay).unwrap();
+    assert_eq!(calls.get(), 4);
+    i2c.done();
+
+    // The register reset is a single operation, and so is each split status read
+    let split_status = |byte| {
+        [
+            Transaction::write(ADDRESS, vec![0x71]),
+            Transaction::read(ADDRESS, vec![byte]),
+        ]
+    };
+    let mut expectations = vec![common::soft_reset()];
+    expectations.extend(split_status(0x08));
+    for register in [0x1B, 0x1C, 0x1E] {
+        expectations.extend([
+            Transaction::write(ADDRESS, vec![register, 0x00, 0x00]),
+            Transaction::read(ADDRESS, vec![0x00, 0x12, 0x34]),
+            Transaction::write(ADDRESS, vec![0xB0 | register, 0x12, 0x34]),
+        ]);
+    }
+    expectations.extend(split_status(IDLE));
+    let mut i2c = Mock::new(&expectations);
+    let calls = Cell::new(0);
+    let hook = |_: &mut Split<Mock>| {
+        calls.set(calls.get() + 1);
+        Ok(())
+    };
+
+    Aht20::with_bus_hook(Split(i2c.clone()), hook, &mut delay).unwrap();
+    // The soft reset, both status reads and the register reset, for 14 transactions
+    assert_eq!(calls.get(), 4);
+    i2c.done();
+}
+
+#[cfg(feature = "eh1")]
+#[test]
+fn classifies_embedded_hal_1_nacks() {
+    use {
+        aht20::{eh1::Eh1, hal::Bus},
+        embedded_hal_1::i2c::{ErrorKind, NoAcknowledgeSource},
+    };
+
+    let mut i2c = embedded_hal_mock::eh1::i2c::Mock::new(&[]);
+    let bus = Eh1(i2c.clone());
+    assert!(bus.is_address_nack(&ErrorKind::NoAcknowledge(NoAcknowledgeSource::Address)));
+    assert!(!bus.is_address_nack(&ErrorKind::NoAcknowledge(NoAcknowledgeSource::Data)));
+    assert!(!bus.is_address_nack(&ErrorKind::Bus));
+    i2c.done();
+}
+
+#[cfg(feature = "eh1")]
+#[test]
+fn classifies_embedded_hal_1_errors() {
+    use {
+        aht20::{eh1::Eh1, Error},
+        common::Recorder,
+        embedded_hal_1::i2c::{ErrorKind, NoAcknowledgeSource},
+        embedded_hal_mock::eh1::i2c::{Mock, Transaction},
+    };
+
+    /// Write of `bytes` failing with `kind`.
+    fn failing(bytes: &[u8], kind: ErrorKind) -> Transaction {
+        common::eh1::write(bytes).remove(0).with_error(kind)
+    }
+
+    // Arbitration losses and bus errors are retried, anything else fails at once
+    let kinds = [
+        (ErrorKind::ArbitrationLoss, true),
+        (ErrorKind::Bus, true),
+        (
+            ErrorKind::NoAcknowledge(NoAcknowledgeSource::Address),
+            false,
+        ),
+        (ErrorKind::NoAcknowledge(NoAcknowledgeSource::Data), false),
+        (ErrorKind::Overrun, false),
+        (ErrorKind::Other, false),
+    ];
+    for &(kind, retried) in &kinds {
+        let mut expectations = vec![failing(&[0xBA], kind)];
+        if retried {
+            expectations.extend(common::eh1::write(&[0xBA]));
+        }
+        let mut i2c = Mock::new(&expectations);
+        let mut dev = Aht20::attach(Eh1(i2c.clone()));
+        dev.set_bus_retries(1);
+
+        let expected = if retried { Ok(()) } else { Err(kind) };
+        assert_eq!(dev.reset(&mut Recorder::default()), expected, "{:?}", kind);
+        i2c.done();
+    }
+
+    // The kind is surfaced without knowing the HAL's error type
+    let mut i2c = Mock::new(&[failing(&[0xAC, 0x33, 0x00], ErrorKind::Overrun)]);
+    let mut dev = Aht20::attach(Eh1(i2c.clone()));
+    let error = dev.read(&mut Recorder::default()).map(|_| ()).unwrap_err();
+    assert_eq!(error.bus_kind(), Some(ErrorKind::Overrun));
+    i2c.done();
+
+    let nack = ErrorKind::NoAcknowledge(NoAcknowledgeSource::Address);
+    assert_eq!(Error::<ErrorKind>::DeviceNotFound.bus_kind(), Some(nack));
+    assert_eq!(Error::<ErrorKind>::Busy.bus_kind(), None);
+}
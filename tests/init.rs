@@ -0,0 +1,335 @@
+//! Construction and initialization of the driver.
+
+mod common;
+
+use {
+    aht20::{Aht20, Config, Error},
+    common::{frame, init, status, trigger, Recorder, Wait, BUSY, HUMIDITY, IDLE, UNCALIBRATED},
+    embedded_hal_mock::eh0::i2c::{Mock, Transaction},
+};
+
+#[test]
+fn configured_address() {
+    const ADDRESS: u8 = 0x39;
+
+    // Reset, calibration and measurement all go to the configured address
+    let expectations = [
+        Transaction::write(ADDRESS, vec![0xBA]),
+        Transaction::write_read(ADDRESS, vec![0x71], vec![UNCALIBRATED]),
+        Transaction::write(ADDRESS, vec![0xBE, 0x08, 0x00]),
+        Transaction::write_read(ADDRESS, vec![0x71], vec![IDLE]),
+        Transaction::write_read(ADDRESS, vec![0x71], vec![IDLE]),
+        Transaction::write(ADDRESS, vec![0xAC, 0x33, 0x00]),
+        Transaction::write_read(ADDRESS, vec![0x71], vec![IDLE]),
+        Transaction::write_read(ADDRESS, vec![0x71], frame()),
+        Transaction::write(ADDRESS, vec![0xBA]),
+    ];
+    let mut i2c = Mock::new(&expectations);
+    let mut delay = Recorder::default();
+
+    let mut dev = Aht20::new_with_address(i2c.clone(), ADDRESS, &mut delay).unwrap();
+    let (humidity, _) = dev.read(&mut delay).unwrap();
+    assert_eq!(humidity.raw(), HUMIDITY);
+    dev.reset(&mut delay).unwrap();
+    i2c.done();
+}
+
+#[test]
+fn configured_polling() {
+    let config = Config {
+        measurement_poll_interval_us: 5000,
+        measurement_max_polls: 10,
+        ..Config::default()
+    };
+    let mut expectations = init();
+    expectations.push(trigger());
+    expectations.extend((0..11).map(|_| status(BUSY)));
+    let mut i2c = Mock::new(&expectations);
+    let mut delay = Recorder::default();
+
+    let mut dev = Aht20::new_with_config(i2c.clone(), config, &mut delay).unwrap();
+    assert_eq!(*dev.config(), config);
+
+    // Twice as many polls, half as long apart, as the default
+    delay.clear();
+    assert_eq!(
+        dev.read(&mut delay).map(|_| ()),
+        Err(Error::MaxTriesExceeded)
+    );
+    assert_eq!(delay.waits[0], Wait::Ms(80));
+    assert_eq!(delay.waits[1..], [Wait::Us(5000); 10]);
+    i2c.done();
+}
+
+#[test]
+fn probe() {
+    use embedded_hal_mock::eh0::MockError;
+
+    /// Error of a bus on which no device acknowledged its address.
+    const NACK: MockError = MockError::Io(std::io::ErrorKind::NotFound);
+    /// Any other bus error.
+    const FAULT: MockError = MockError::Io(std::io::ErrorKind::Other);
+
+    let expectations = [
+        status(IDLE),
+        status(0xFF),
+        status(IDLE).with_error(NACK),
+        status(IDLE).with_error(FAULT),
+    ];
+    let mut i2c = Mock::new(&expectations);
+    let is_nack = |e: &MockError| *e == NACK;
+
+    assert_eq!(Aht20::probe(&mut i2c), Ok(true));
+    assert_eq!(Aht20::probe(&mut i2c), Ok(false));
+    assert_eq!(Aht20::probe_with(&mut i2c, is_nack), Ok(false));
+    assert_eq!(Aht20::probe_with(&mut i2c, is_nack), Err(FAULT));
+    i2c.done();
+}
+
+#[test]
+fn calibrates_only_when_needed() {
+    use common::{initialize, soft_reset};
+
+    // Calibrated after power-on: no initialization command
+    let mut i2c = Mock::new(&[soft_reset(), status(IDLE), status(IDLE)]);
+    let mut delay = Recorder::default();
+    let mut dev = Aht20::new(i2c.clone(), &mut delay).unwrap();
+    assert_eq!(dev.ensure_calibrated(&mut delay), Ok(false));
+    i2c.done();
+
+    let expectations = [
+        soft_reset(),
+        status(UNCALIBRATED),
+        initialize(),
+        status(IDLE),
+        status(IDLE),
+        status(UNCALIBRATED),
+        initialize(),
+        status(IDLE),
+        status(IDLE),
+    ];
+    let mut i2c = Mock::new(&expectations);
+    let mut dev = Aht20::new(i2c.clone(), &mut delay).unwrap();
+    assert_eq!(dev.ensure_calibrated(&mut delay), Ok(true));
+    i2c.done();
+}
+
+#[test]
+fn device_not_found() {
+    use {aht20::hal::NackAware, common::soft_reset, embedded_hal_mock::eh0::MockError};
+
+    const NACK: MockError = MockError::Io(std::io::ErrorKind::NotFound);
+
+    /// Initializes the driver over a bus following `expectations`, classifying [`NACK`].
+    fn new(expectations: &[Transaction]) -> Result<(), Error<MockError>> {
+        let mut i2c = Mock::new(expectations);
+        let bus = NackAware::new(i2c.clone(), |e| *e == NACK);
+        let result = Aht20::new(bus, &mut Recorder::default())
+            .map(|_| ())
+            .map_err(|e| e.error);
+        i2c.done();
+        result
+    }
+
+    // Absent: nothing acknowledges the soft reset
+    assert_eq!(
+        new(&[soft_reset().with_error(NACK)]),
+        Err(Error::DeviceNotFound)
+    );
+
+    // Present, then vanishing before the status read
+    assert_eq!(
+        new(&[soft_reset(), status(IDLE).with_error(NACK)]),
+        Err(Error::Bus(NACK))
+    );
+
+    // Present and healthy
+    assert_eq!(new(&init()), Ok(()));
+
+    // Without a classifying bus, a NACK stays a bus error
+    let mut i2c = Mock::new(&[soft_reset().with_error(NACK)]);
+    match Aht20::new(i2c.clone(), &mut Recorder::default()) {
+        Ok(_) => panic!("initialized without a sensor"),
+        Err(e) => assert_eq!(e.error, Error::Bus(NACK)),
+    }
+    i2c.done();
+}
+
+#[cfg(feature = "eh1")]
+#[test]
+fn device_not_found_on_embedded_hal_1() {
+    use {
+        aht20::eh1::Eh1,
+        embedded_hal_1::i2c::{ErrorKind, NoAcknowledgeSource},
+        embedded_hal_mock::eh1::i2c::Mock,
+    };
+
+    let nack = ErrorKind::NoAcknowledge(NoAcknowledgeSource::Address);
+    let mut expectations = common::eh1::write(&[0xBA]);
+    expectations[0] = expectations[0].clone().with_error(nack);
+    let mut i2c = Mock::new(&expectations);
+
+    match Aht20::new(Eh1(i2c.clone()), &mut Recorder::default()) {
+        Ok(_) => panic!("initialized without a sensor"),
+        Err(e) => assert_eq!(e.error, Error::DeviceNotFound),
+    }
+    i2c.done();
+}
+
+#[test]
+fn initialization_command() {
+    use aht20::consts::ADDRESS;
+
+    // The AHT20 datasheet's 0xBE, both in `new` and in `calibrate`
+    let aht20 = || Transaction::write(ADDRESS, vec![0xBE, 0x08, 0x00]);
+    let expectations = [
+        Transaction::write(ADDRESS, vec![0xBA]),
+        Transaction::write_read(ADDRESS, vec![0x71], vec![UNCALIBRATED]),
+        aht20(),
+        Transaction::write_read(ADDRESS, vec![0x71], vec![IDLE]),
+        Transaction::write_read(ADDRESS, vec![0x71], vec![IDLE]),
+        aht20(),
+        Transaction::write_read(ADDRESS, vec![0x71], vec![IDLE]),
+        Transaction::write_read(ADDRESS, vec![0x71], vec![IDLE]),
+    ];
+    let mut i2c = Mock::new(&expectations);
+    let mut delay = Recorder::default();
+    let mut dev = Aht20::new(i2c.clone(), &mut delay).unwrap();
+    dev.calibrate(&mut delay).unwrap();
+    i2c.done();
+
+    // The AHT10 keeps its own 0xE1, after its own status command
+    let aht10_status = |status| Transaction::write_read(ADDRESS, vec![0x00], vec![status]);
+    let expectations = [
+        Transaction::write(ADDRESS, vec![0xBA]),
+        aht10_status(UNCALIBRATED),
+        Transaction::write(ADDRESS, vec![0xE1, 0x08, 0x00]),
+        aht10_status(IDLE),
+        aht10_status(IDLE),
+    ];
+    let mut i2c = Mock::new(&expectations);
+    Aht20::new_aht10(i2c.clone(), &mut delay).unwrap();
+    i2c.done();
+}
+
+#[test]
+fn register_reset() {
+    use aht20::{consts::ADDRESS, hal::Split};
+
+    /// Status read as a write and a separate read, on a bus with plain reads.
+    fn status(byte: u8) -> [Transaction; 2] {
+        [
+            Transaction::write(ADDRESS, vec![0x71]),
+            Transaction::read(ADDRESS, vec![byte]),
+        ]
+    }
+
+    /// Initializes the driver with `config` against `expectations`, returning its waits.
+    fn new(config: Config, expectations: &[Transaction]) -> Vec<Wait> {
+        let mut i2c = Mock::new(expectations);
+        let mut delay = Recorder::default();
+        Aht20::new_with_config(Split(i2c.clone()), config, &mut delay).unwrap();
+        i2c.done();
+        delay.waits
+    }
+
+    // Fast path: 0x18 after power-on
+    let mut expectations = vec![Transaction::write(ADDRESS, vec![0xBA])];
+    expectations.extend(status(0x18));
+    assert_eq!(new(Config::default(), &expectations), [Wait::Ms(20)]);
+
+    // Anything else resets each register: select, read back, write back its data bytes
+    let mut expectations = vec![Transaction::write(ADDRESS, vec![0xBA])];
+    expectations.extend(status(0x08));
+    for register in [0x1B, 0x1C, 0x1E].iter().copied() {
+        expectations.extend([
+            Transaction::write(ADDRESS, vec![register, 0x00, 0x00]),
+            Transaction::read(ADDRESS, vec![0x00, 0xA5, register]),
+            Transaction::write(ADDRESS, vec![0xB0 | register, 0xA5, register]),
+        ]);
+    }
+    expectations.extend(status(0x18));
+    let mut waits = vec![Wait::Ms(20)];
+    for _ in 0..3 {
+        waits.extend([Wait::Ms(5), Wait::Ms(10)]);
+    }
+    waits.push(Wait::Ms(10));
+    assert_eq!(new(Config::default(), &expectations), waits);
+
+    // Skippable: the same status goes straight on
+    let config = Config {
+        perform_register_reset: false,
+        ..Config::default()
+    };
+    let mut expectations = vec![Transaction::write(ADDRESS, vec![0xBA])];
+    expectations.extend(status(0x08));
+    assert_eq!(new(config, &expectations), [Wait::Ms(20)]);
+}
+
+#[test]
+fn no_register_reset_without_a_plain_read() {
+    use common::{initialize, soft_reset};
+
+    // An embedded-hal 0.2 bus used directly has no plain read, so even the default config skips
+    // the reset: no register traffic after a status other than 0x18
+    let mut i2c = Mock::new(&[soft_reset(), status(0x08)]);
+    let mut delay = Recorder::default();
+    let config = Config::default();
+    assert!(config.perform_register_reset);
+    Aht20::new_with_config(i2c.clone(), config, &mut delay).unwrap();
+    assert_eq!(delay.waits, [Wait::Ms(20)]);
+    i2c.done();
+
+    // An uncalibrated sensor goes straight to its initialization command
+    let expectations = [
+        soft_reset(),
+        status(UNCALIBRATED),
+        initialize(),
+        status(IDLE),
+        status(IDLE),
+    ];
+    let mut i2c = Mock::new(&expectations);
+    Aht20::new_with_config(i2c.clone(), Config::default(), &mut Recorder::default()).unwrap();
+    i2c.done();
+}
+
+#[test]
+fn power_on_time() {
+    use {aht20::Aht20Builder, common::soft_reset, embedded_hal_mock::eh0::MockError};
+
+    const NACK: MockError = MockError::Io(std::io::ErrorKind::NotFound);
+
+    // Waited out before the first transaction
+    let mut i2c = Mock::new(&init());
+    let mut delay = Recorder::default();
+    Aht20Builder::new()
+        .power_on_delay_ms(100)
+        .build(i2c.clone(), &mut delay)
+        .unwrap();
+    assert_eq!(delay.waits, [Wait::Ms(100), Wait::Ms(20)]);
+    i2c.done();
+
+    // Or a sensor still starting NACKs a couple of times, then acknowledges a retry
+    let mut expectations = vec![soft_reset().with_error(NACK), soft_reset().with_error(NACK)];
+    expectations.extend(init());
+    let mut i2c = Mock::new(&expectations);
+    let mut delay = Recorder::default();
+    Aht20Builder::new()
+        .bus_retries(3)
+        .bus_retry_delay_us(20_000)
+        .build(i2c.clone(), &mut delay)
+        .unwrap();
+    assert_eq!(
+        delay.waits,
+        [Wait::Us(20_000), Wait::Us(20_000), Wait::Ms(20)]
+    );
+    i2c.done();
+
+    // Without retries the first NACK fails the initialization
+    let mut i2c = Mock::new(&[soft_reset().with_error(NACK)]);
+    match Aht20::new(i2c.clone(), &mut Recorder::default()) {
+        Ok(_) => panic!("initialized without an acknowledgement"),
+        Err(e) => assert_eq!(e.error, Error::Bus(NACK)),
+    }
+    i2c.done();
+}
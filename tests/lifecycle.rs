@@ -0,0 +1,156 @@
+//! Attaching to, tearing down and rebuilding the driver.
+
+mod common;
+
+use {
+    aht20::{Aht20, Error},
+    common::{fetch, frame, init, status, trigger, Recorder, HUMIDITY, IDLE, UNCALIBRATED},
+    embedded_hal_mock::eh0::i2c::{Mock, Transaction},
+};
+
+/// Transcript of a measurement ready on the first poll.
+fn measurement() -> [Transaction; 3] {
+    [trigger(), status(IDLE), fetch(frame())]
+}
+
+#[test]
+fn attach_and_verify() {
+    let expectations = [
+        status(UNCALIBRATED),
+        status(IDLE),
+        trigger(),
+        status(IDLE),
+        fetch(frame()),
+    ];
+    let mut i2c = Mock::new(&expectations);
+    let mut delay = Recorder::default();
+
+    // Attaching needs no bus traffic, verifying a single status read
+    let mut dev = Aht20::attach(i2c.clone());
+    assert_eq!(dev.verify(), Err(Error::Uncalibrated));
+    assert_eq!(dev.verify(), Ok(()));
+    let (humidity, _) = dev.read(&mut delay).unwrap();
+    assert_eq!(humidity.raw(), HUMIDITY);
+    i2c.done();
+}
+
+#[test]
+fn free_and_reconstruct() {
+    let mut expectations = init();
+    expectations.extend(measurement());
+    expectations.extend(measurement());
+    let mut i2c = Mock::new(&expectations);
+    let mut delay = Recorder::default();
+
+    let mut dev = Aht20::new(i2c.clone(), &mut delay).unwrap();
+    dev.read(&mut delay).unwrap();
+
+    let mut dev = Aht20::attach(dev.free());
+    let (humidity, _) = dev.read(&mut delay).unwrap();
+    assert_eq!(humidity.raw(), HUMIDITY);
+    i2c.done();
+}
+
+#[test]
+fn failed_init_hands_back_the_bus() {
+    use embedded_hal_mock::eh0::MockError;
+
+    let fault = MockError::Io(std::io::ErrorKind::Other);
+    let mut expectations = vec![common::soft_reset().with_error(fault.clone())];
+    expectations.extend(init());
+    let mut i2c = Mock::new(&expectations);
+    let mut delay = Recorder::default();
+
+    let failed = match Aht20::new(i2c.clone(), &mut delay) {
+        Ok(_) => panic!("initialized despite the bus fault"),
+        Err(failed) => failed,
+    };
+    assert_eq!(failed.error, Error::Bus(fault));
+    Aht20::new(failed.i2c, &mut delay).unwrap();
+    i2c.done();
+}
+
+#[test]
+fn parts_survive_a_new_peripheral() {
+    let mut before = Mock::new(&init());
+    let mut delay = Recorder::default();
+
+    let mut dev = Aht20::new(before.clone(), &mut delay).unwrap();
+    dev.set_poll_interval_us(5000);
+    let config = *dev.config();
+    let (_, state) = dev.into_parts();
+    assert!(state.is_calibrated());
+    before.done();
+
+    // Rebuilding needs no bus traffic, and the driver reads as before
+    let mut after = Mock::new(&measurement());
+    let mut dev = Aht20::from_parts(after.clone(), state);
+    assert_eq!(*dev.config(), config);
+    let (humidity, _) = dev.read(&mut delay).unwrap();
+    assert_eq!(humidity.raw(), HUMIDITY);
+    after.done();
+}
+
+#[test]
+fn typestate_init() {
+    use {aht20::Uninitialized, embedded_hal_mock::eh0::MockError};
+
+    let fault = MockError::Io(std::io::ErrorKind::Other);
+    let mut expectations = vec![common::soft_reset().with_error(fault.clone())];
+    expectations.extend(init());
+    expectations.extend(measurement());
+    expectations.extend(init());
+    let mut i2c = Mock::new(&expectations);
+    let mut delay = Recorder::default();
+
+    // A failed init hands the uninitialized driver back for another attempt
+    let dev: Aht20<_, Uninitialized> = Aht20::uninitialized(i2c.clone());
+    let (dev, error) = match dev.init(&mut delay) {
+        Ok(_) => panic!("initialized despite the bus fault"),
+        Err(failed) => failed,
+    };
+    assert_eq!(error, Error::Bus(fault));
+    let mut dev = dev.init(&mut delay).map_err(|(_, e)| e).unwrap();
+    dev.read(&mut delay).unwrap();
+
+    dev.into_uninitialized()
+        .init(&mut delay)
+        .map_err(|(_, e)| e)
+        .unwrap();
+    i2c.done();
+}
+
+#[test]
+fn new_uninit_then_init() {
+    let mut expectations = init();
+    expectations.extend(measurement());
+    let mut i2c = Mock::new(&expectations);
+    let mut delay = Recorder::default();
+
+    // Readings fail without bus traffic until initialized
+    let mut dev = Aht20::new_uninit(i2c.clone());
+    assert_eq!(dev.read(&mut delay).map(|_| ()), Err(Error::NotInitialized));
+    dev.init(&mut delay).unwrap();
+    dev.read(&mut delay).unwrap();
+    i2c.done();
+}
+
+#[test]
+fn uninitialized_init_follows_the_configuration() {
+    use aht20::Aht20Builder;
+
+    // Calibration skipped: no status read follows the soft reset
+    let mut expectations = vec![common::soft_reset()];
+    expectations.extend(measurement());
+    let mut i2c = Mock::new(&expectations);
+    let mut delay = Recorder::default();
+
+    let dev = Aht20Builder::new()
+        .skip_initial_calibration(true)
+        .build_uninitialized(i2c.clone())
+        .map_err(|e| e.error)
+        .unwrap();
+    let mut dev = dev.init(&mut delay).map_err(|(_, e)| e).unwrap();
+    dev.read(&mut delay).unwrap();
+    i2c.done();
+}
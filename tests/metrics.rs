@@ -0,0 +1,269 @@
+//! Derived quantities, checked against published reference values.
+
+#![cfg(any(feature = "libm", feature = "micromath"))]
+
+use aht20::{metrics, Humidity, Temperature};
+
+/// Reading of `rh` %RH at `celsius`.
+fn reading(rh: f32, celsius: f32) -> (Humidity, Temperature) {
+    (Humidity::from_rh(rh), Temperature::from_celsius(celsius))
+}
+
+/// Asserts that `actual` is within `tolerance` of `expected`.
+fn assert_near(actual: f32, expected: f32, tolerance: f32) {
+    assert!(
+        (actual - expected).abs() <= tolerance,
+        "{} is not within {} of {}",
+        actual,
+        tolerance,
+        expected
+    );
+}
+
+#[test]
+fn dew_point() {
+    // Celsius, %RH and dew point from psychrometric tables, to within 0.2°C
+    let table = [
+        (25.0, 60.0, 16.7),
+        (20.0, 50.0, 9.3),
+        (30.0, 80.0, 26.2),
+        (10.0, 90.0, 8.4),
+        (0.0, 100.0, 0.0),
+    ];
+    for (celsius, rh, expected) in table {
+        let (h, t) = reading(rh, celsius);
+        assert_near(metrics::dew_point(&h, &t), expected, 0.2);
+    }
+
+    // Dry air yields a very low but finite dew point
+    let (h, t) = reading(0.0, 25.0);
+    let dry = metrics::dew_point(&h, &t);
+    assert!(dry.is_finite() && dry < -60.0);
+}
+
+#[test]
+fn frost_point() {
+    // Frost points over ice, to within 0.2°C
+    let table = [
+        (-10.0, 70.0, -12.9),
+        (-20.0, 80.0, -20.3),
+        (-5.0, 90.0, -5.7),
+    ];
+    for (celsius, rh, expected) in table {
+        let (h, t) = reading(rh, celsius);
+        assert_near(metrics::frost_point(&h, &t), expected, 0.2);
+        assert_eq!(
+            metrics::dew_or_frost_point(&h, &t),
+            metrics::frost_point(&h, &t)
+        );
+    }
+
+    // Above freezing the combined helper picks the dew point
+    let (h, t) = reading(60.0, 25.0);
+    assert_eq!(
+        metrics::dew_or_frost_point(&h, &t),
+        metrics::dew_point(&h, &t)
+    );
+}
+
+#[test]
+fn absolute_humidity() {
+    // Grams per cubic meter at sea level
+    let (h, t) = reading(50.0, 20.0);
+    assert_near(metrics::absolute_humidity(&h, &t), 8.65, 0.1);
+    let (h, t) = reading(80.0, 30.0);
+    assert_near(metrics::absolute_humidity(&h, &t), 24.3, 0.3);
+}
+
+#[test]
+fn heat_index() {
+    // Values of the NWS heat index chart, in °F converted to °C, to within 1°C
+    let chart = [
+        (90.0, 70.0, 106.0),
+        (96.0, 50.0, 108.0),
+        (86.0, 90.0, 105.0),
+    ];
+    for (fahrenheit, rh, expected) in chart {
+        let (h, t) = reading(rh, (fahrenheit - 32.0) / 1.8);
+        assert_near(metrics::heat_index(&h, &t), (expected - 32.0) / 1.8, 1.0);
+    }
+
+    // Mild air stays close to the temperature, and the result jumps by less than a degree where
+    // the regression takes over
+    let (h, t) = reading(50.0, 20.0);
+    assert_near(metrics::heat_index(&h, &t), 20.0, 1.0);
+    let mut previous = metrics::heat_index(&h, &t);
+    for tenths in 200..=320 {
+        let (h, t) = reading(50.0, tenths as f32 / 10.0);
+        let hi = metrics::heat_index(&h, &t);
+        assert_near(hi, previous, 1.0);
+        previous = hi;
+    }
+}
+
+#[test]
+fn humidex() {
+    // Environment Canada's formula at a dew point of 20°C and of 15°C, to within 0.3°C
+    let (h, t) = reading(55.07, 30.0);
+    assert_near(metrics::dew_point(&h, &t), 20.0, 0.1);
+    assert_near(metrics::humidex(&h, &t), 37.5, 0.3);
+    let (h, t) = reading(53.6, 25.0);
+    assert_near(metrics::humidex(&h, &t), 28.9, 0.3);
+
+    // Dry air is clamped to the temperature
+    let (h, t) = reading(5.0, 30.0);
+    assert_eq!(metrics::humidex(&h, &t), t.celsius());
+}
+
+#[test]
+fn vpd() {
+    // Horticulture charts, to within 0.02 kPa
+    let (h, t) = reading(60.0, 25.0);
+    assert_near(metrics::vpd_kpa(&h, &t), 1.27, 0.02);
+    let (h, t) = reading(70.0, 20.0);
+    assert_near(metrics::vpd_kpa(&h, &t), 0.70, 0.02);
+
+    // Leaves cooler than the air see a lower deficit
+    let (h, t) = reading(60.0, 25.0);
+    assert_eq!(
+        metrics::vpd_leaf_kpa(&h, &t, t.celsius()),
+        metrics::vpd_kpa(&h, &t)
+    );
+    assert_near(metrics::vpd_leaf_kpa(&h, &t, 23.0), 0.91, 0.02);
+}
+
+#[test]
+fn wet_bulb() {
+    // Stull's comparison points, to within 0.1°C
+    let (h, t) = reading(50.0, 20.0);
+    assert_near(metrics::wet_bulb(&h, &t).unwrap(), 13.7, 0.1);
+    let (h, t) = reading(80.0, 30.0);
+    assert_near(metrics::wet_bulb(&h, &t).unwrap(), 27.2, 0.2);
+
+    // No extrapolation outside the fit's range
+    let (h, t) = reading(2.0, 20.0);
+    assert_eq!(metrics::wet_bulb(&h, &t), None);
+    let (h, t) = reading(50.0, 60.0);
+    assert_eq!(metrics::wet_bulb(&h, &t), None);
+}
+
+#[test]
+fn psychrometrics() {
+    use aht20::psychrometrics::{
+        mixing_ratio, moist_air_enthalpy, specific_humidity, STANDARD_PRESSURE_PA,
+    };
+
+    // ASHRAE psychrometric chart at standard pressure
+    let (h, t) = reading(50.0, 25.0);
+    assert_near(mixing_ratio(&h, &t, STANDARD_PRESSURE_PA), 0.00988, 0.0001);
+    assert_near(
+        specific_humidity(&h, &t, STANDARD_PRESSURE_PA),
+        0.00978,
+        0.0001,
+    );
+    assert_near(moist_air_enthalpy(&h, &t, STANDARD_PRESSURE_PA), 50.3, 0.3);
+
+    let (h, t) = reading(60.0, 20.0);
+    assert_near(mixing_ratio(&h, &t, STANDARD_PRESSURE_PA), 0.00876, 0.0001);
+    assert_near(moist_air_enthalpy(&h, &t, STANDARD_PRESSURE_PA), 42.3, 0.3);
+
+    // Lower pressure, at altitude, holds more vapor per kilogram of dry air
+    assert!(mixing_ratio(&h, &t, 75_000.0) > mixing_ratio(&h, &t, STANDARD_PRESSURE_PA));
+}
+
+#[test]
+#[cfg(feature = "libm")]
+fn double_precision() {
+    let (h, t) = reading(60.0, 25.0);
+    assert!((metrics::dew_point_f64(&h, &t) - metrics::dew_point(&h, &t) as f64).abs() < 1e-3);
+    assert!((metrics::vpd_kpa_f64(&h, &t) - metrics::vpd_kpa(&h, &t) as f64).abs() < 1e-4);
+    assert!(
+        (metrics::absolute_humidity_f64(&h, &t) - metrics::absolute_humidity(&h, &t) as f64).abs()
+            < 1e-3
+    );
+
+    let (h, t) = reading(70.0, -10.0);
+    assert!((metrics::frost_point_f64(&h, &t) - metrics::frost_point(&h, &t) as f64).abs() < 1e-3);
+}
+
+#[test]
+fn saturation_vapor_pressure() {
+    use metrics::{saturation_vapor_pressure_ice_pa, saturation_vapor_pressure_pa};
+
+    // Steam table values over water, to within 0.5%
+    for &(celsius, expected) in &[(0.0, 611.2), (20.0, 2339.2), (40.0, 7384.9)] {
+        assert_near(
+            saturation_vapor_pressure_pa(celsius),
+            expected,
+            expected * 0.005,
+        );
+    }
+
+    // Over ice, which meets water at the triple point
+    assert_near(saturation_vapor_pressure_ice_pa(-10.0), 259.9, 1.0);
+    assert_near(saturation_vapor_pressure_ice_pa(0.0), 611.2, 0.5);
+    assert!(saturation_vapor_pressure_ice_pa(-10.0) < saturation_vapor_pressure_pa(-10.0));
+
+    // Derived metrics agree with it, e.g. bone dry air at 20°C
+    let (h, t) = reading(0.0, 20.0);
+    assert_near(
+        metrics::vpd_kpa(&h, &t),
+        saturation_vapor_pressure_pa(t.celsius()) / 1000.0,
+        1e-4,
+    );
+}
+
+#[test]
+fn pressure_aware() {
+    use aht20::psychrometrics::{mixing_ratio, specific_humidity, STANDARD_PRESSURE_PA};
+
+    let (h, t) = reading(50.0, 25.0);
+
+    // At 750 hPa, around 2,400m, the same vapor is mixed into 26% less dry air
+    let sea_level = mixing_ratio(&h, &t, STANDARD_PRESSURE_PA);
+    let altitude = mixing_ratio(&h, &t, 75_000.0);
+    assert_near(altitude / sea_level, 1.355, 0.01);
+    assert_near(
+        specific_humidity(&h, &t, 75_000.0) / specific_humidity(&h, &t, STANDARD_PRESSURE_PA),
+        1.35,
+        0.01,
+    );
+
+    // Absolute humidity only moves through the enhancement factor
+    let sea_level = metrics::absolute_humidity(&h, &t);
+    assert_eq!(
+        metrics::absolute_humidity_at(&h, &t, STANDARD_PRESSURE_PA),
+        sea_level
+    );
+    let altitude = metrics::absolute_humidity_at(&h, &t, 75_000.0);
+    assert!(altitude < sea_level && altitude > sea_level * 0.995);
+}
+
+#[test]
+fn condensation_risk() {
+    use metrics::{condensation_risk, CondensationRisk};
+
+    // Dew point 16.7°C, with a 2°C margin up to 18.7°C
+    let (h, t) = reading(60.0, 25.0);
+    assert_eq!(condensation_risk(&h, &t, 20.0, 2.0), CondensationRisk::None);
+    assert_eq!(
+        condensation_risk(&h, &t, 17.0, 2.0),
+        CondensationRisk::Approaching
+    );
+    assert_eq!(
+        condensation_risk(&h, &t, 15.0, 2.0),
+        CondensationRisk::Condensing
+    );
+    assert_eq!(condensation_risk(&h, &t, 17.0, 0.0), CondensationRisk::None);
+
+    // Below 0°C the frost point of -12.9°C applies, above the dew point of -14.4°C
+    let (h, t) = reading(70.0, -10.0);
+    assert_eq!(
+        condensation_risk(&h, &t, -13.5, 0.0),
+        CondensationRisk::Condensing
+    );
+    assert_eq!(
+        condensation_risk(&h, &t, -12.0, 2.0),
+        CondensationRisk::Approaching
+    );
+}
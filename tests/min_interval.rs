@@ -0,0 +1,90 @@
+//! The minimum interval between measurements, under each policy.
+
+mod common;
+
+use {
+    aht20::{hal::Delay, Aht20, Aht20Builder, Error, MinIntervalPolicy},
+    common::{fetch, frame, init, status, trigger, Recorder, Wait, IDLE},
+    core::sync::atomic::{AtomicU32, Ordering},
+    embedded_hal_mock::eh0::i2c::{Mock, Transaction},
+};
+
+/// Transcript of two measurements, each ready on the first poll.
+fn two_measurements() -> Vec<Transaction> {
+    let mut expectations = init();
+    for _ in 0..2 {
+        expectations.extend([trigger(), status(IDLE), fetch(frame())]);
+    }
+    expectations
+}
+
+/// Builds a driver enforcing a 1s interval with `policy`, against `expectations`.
+fn build(policy: MinIntervalPolicy, expectations: &[Transaction]) -> (Aht20<Mock>, Mock) {
+    let i2c = Mock::new(expectations);
+    let dev = Aht20Builder::new()
+        .min_interval_ms(1000)
+        .min_interval_policy(policy)
+        .build(i2c.clone(), &mut Recorder::default())
+        .unwrap();
+    (dev, i2c)
+}
+
+#[test]
+fn block() {
+    static NOW: AtomicU32 = AtomicU32::new(0);
+
+    /// Delay advancing [`NOW`] as it waits.
+    struct Ticking(Recorder);
+
+    impl Delay for Ticking {
+        fn delay_ms(&mut self, ms: u16) {
+            NOW.fetch_add(ms as u32, Ordering::Relaxed);
+            self.0.delay_ms(ms);
+        }
+    }
+
+    let (mut dev, mut i2c) = build(MinIntervalPolicy::Block, &two_measurements());
+    dev.set_clock(|| NOW.load(Ordering::Relaxed));
+    let mut delay = Ticking(Recorder::default());
+
+    // The second measurement waits out what is left of the interval
+    dev.read(&mut delay).unwrap();
+    NOW.fetch_add(300, Ordering::Relaxed);
+    delay.0.clear();
+    dev.read(&mut delay).unwrap();
+    assert_eq!(delay.0.waits[..2], [Wait::Ms(620), Wait::Ms(80)]);
+    i2c.done();
+}
+
+#[test]
+fn block_with_a_stalled_clock() {
+    let (mut dev, mut i2c) = build(MinIntervalPolicy::Block, &two_measurements()[..5]);
+    dev.set_clock(|| 0);
+    let mut delay = Recorder::default();
+
+    // The interval is waited out twice at most, then the measurement is refused
+    dev.read(&mut delay).unwrap();
+    delay.clear();
+    let result = dev.read(&mut delay).map(|_| ());
+    assert_eq!(result, Err(Error::TooSoon { remaining_ms: 1000 }));
+    assert_eq!(delay.waits, [Wait::Ms(1000), Wait::Ms(1000)]);
+    i2c.done();
+}
+
+#[test]
+fn reject() {
+    static NOW: AtomicU32 = AtomicU32::new(0);
+
+    let (mut dev, mut i2c) = build(MinIntervalPolicy::Reject, &two_measurements());
+    dev.set_clock(|| NOW.load(Ordering::Relaxed));
+    let mut delay = Recorder::default();
+
+    // Refused without bus traffic until the interval has elapsed
+    dev.read(&mut delay).unwrap();
+    NOW.store(400, Ordering::Relaxed);
+    let result = dev.read(&mut delay).map(|_| ());
+    assert_eq!(result, Err(Error::TooSoon { remaining_ms: 600 }));
+    NOW.store(1000, Ordering::Relaxed);
+    dev.read(&mut delay).unwrap();
+    i2c.done();
+}
@@ -0,0 +1,72 @@
+//! Conversions of readings to the quantity types of unit crates.
+
+#![cfg(any(feature = "uom", feature = "measurements"))]
+
+use aht20::{Humidity, Temperature};
+
+/// Raw values spread over the whole range.
+const RAWS: [u32; 5] = [0, 0x33333, 0x60000, 0x80000, 0xFFFFF];
+
+#[cfg(feature = "uom")]
+mod to_uom {
+    use {
+        super::*,
+        uom::si::{
+            f32::{Ratio, ThermodynamicTemperature},
+            ratio::{percent, ratio},
+            thermodynamic_temperature::{degree_celsius, kelvin},
+        },
+    };
+
+    #[test]
+    fn readings() {
+        for raw in RAWS.iter().copied() {
+            let t = Temperature::from_raw(raw);
+            let quantity = t.to_thermodynamic_temperature();
+            assert!((quantity.get::<kelvin>() - t.kelvin()).abs() < 1e-3);
+            assert!((quantity.get::<degree_celsius>() - t.celsius()).abs() < 1e-3);
+            assert_eq!(ThermodynamicTemperature::from(t), quantity);
+
+            let h = Humidity::from_raw(raw);
+            let quantity = h.to_ratio();
+            assert!((quantity.get::<percent>() - h.rh()).abs() < 1e-4);
+            assert!((quantity.get::<ratio>() - h.rh() / 100.0).abs() < 1e-6);
+            assert_eq!(Ratio::from(h), quantity);
+        }
+    }
+
+    #[test]
+    #[cfg(any(feature = "libm", feature = "micromath"))]
+    fn metrics() {
+        use {aht20::metrics, uom::si::mass_density::gram_per_cubic_meter};
+
+        let h = Humidity::from_raw(0x80000);
+        let t = Temperature::from_raw(0x60000);
+        let dew_point = metrics::dew_point_uom(&h, &t).get::<degree_celsius>();
+        assert!((dew_point - metrics::dew_point(&h, &t)).abs() < 1e-3);
+        let absolute = metrics::absolute_humidity_uom(&h, &t).get::<gram_per_cubic_meter>();
+        assert!((absolute - metrics::absolute_humidity(&h, &t)).abs() < 1e-4);
+    }
+}
+
+#[cfg(feature = "measurements")]
+mod to_measurements {
+    use super::*;
+
+    #[test]
+    fn readings() {
+        for raw in RAWS.iter().copied() {
+            // Exact in double precision, unlike widening the f32 conversions
+            let t = measurements::Temperature::from(Temperature::from_raw(raw));
+            let celsius = 200.0 * raw as f64 / 1048576.0 - 50.0;
+            assert!((t.as_celsius() - celsius).abs() < 1e-9);
+
+            let h = measurements::Humidity::from(Humidity::from_raw(raw));
+            let percent = 100.0 * raw as f64 / 1048576.0;
+            assert!((h.as_percent() - percent).abs() < 1e-9);
+        }
+
+        let t = measurements::Temperature::from(Temperature::from_raw(0x40000));
+        assert!((t.as_kelvin() - 273.15).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,439 @@
+//! Measurements through the blocking driver, on an `embedded-hal` 0.2 mock bus.
+
+mod common;
+
+use {
+    aht20::{Aht20, Error},
+    common::{
+        fetch, frame, init, status, trigger, Recorder, Wait, BUSY, HUMIDITY, IDLE, TEMPERATURE,
+    },
+    embedded_hal_mock::eh0::{
+        i2c::{Mock, Transaction},
+        MockError,
+    },
+};
+
+/// Transcript of a measurement answering `polls` busy polls before the sensor is ready.
+fn measurement(polls: usize) -> Vec<Transaction> {
+    let mut transcript = vec![trigger()];
+    transcript.extend((0..polls).map(|_| status(BUSY)));
+    transcript.push(status(IDLE));
+    transcript.push(fetch(frame()));
+    transcript
+}
+
+/// Runs a single read against `transcript`, following the initialization, and returns its
+/// result along with the waits of the read.
+fn read(transcript: &[Transaction]) -> (Result<u32, Error<MockError>>, Vec<Wait>) {
+    read_with(transcript, |_| {})
+}
+
+/// Like [`read`], with the driver set up by `setup` after initialization.
+fn read_with(
+    transcript: &[Transaction],
+    setup: impl FnOnce(&mut Aht20<Mock>),
+) -> (Result<u32, Error<MockError>>, Vec<Wait>) {
+    let mut expectations = init();
+    expectations.extend_from_slice(transcript);
+    let mut i2c = Mock::new(&expectations);
+    let mut delay = Recorder::default();
+
+    let mut dev = Aht20::new(i2c.clone(), &mut delay).unwrap();
+    setup(&mut dev);
+    delay.clear();
+    let result = dev.read(&mut delay).map(|(h, _)| h.raw());
+    i2c.done();
+    (result, delay.waits)
+}
+
+#[test]
+fn ready_immediately() {
+    let (result, waits) = read(&measurement(0));
+    assert_eq!(result, Ok(HUMIDITY));
+    assert_eq!(waits, [Wait::Ms(80)]);
+}
+
+#[test]
+fn ready_on_last_allowed_poll() {
+    let (result, waits) = read(&measurement(5));
+    assert_eq!(result, Ok(HUMIDITY));
+    assert_eq!(waits.len(), 6);
+    assert_eq!(waits[1..], [Wait::Us(10_000); 5]);
+}
+
+#[test]
+fn never_ready() {
+    let mut transcript = vec![trigger()];
+    transcript.extend((0..6).map(|_| status(BUSY)));
+    let (result, waits) = read(&transcript);
+    assert_eq!(result, Err(Error::MaxTriesExceeded));
+    assert_eq!(waits.len(), 6);
+}
+
+#[test]
+fn polls_in_microsecond_steps() {
+    // Ready on the third poll: one trigger, three status reads and the fetch
+    let (result, waits) = read_with(&measurement(2), |dev| dev.set_poll_interval_us(500));
+    assert_eq!(result, Ok(HUMIDITY));
+    assert_eq!(waits, [Wait::Ms(80), Wait::Us(500), Wait::Us(500)]);
+}
+
+#[test]
+fn single_values() {
+    // Each call triggers exactly one measurement
+    let mut expectations = init();
+    expectations.extend(measurement(0));
+    expectations.extend(measurement(0));
+    let mut i2c = Mock::new(&expectations);
+    let mut delay = Recorder::default();
+
+    let mut dev = Aht20::new(i2c.clone(), &mut delay).unwrap();
+    assert_eq!(dev.read_humidity(&mut delay).unwrap().raw(), HUMIDITY);
+    assert_eq!(dev.read_temperature(&mut delay).unwrap().raw(), TEMPERATURE);
+    i2c.done();
+}
+
+#[test]
+fn retriggers_on_checksum_mismatch() {
+    use aht20::Config;
+
+    let mut corrupted = frame();
+    corrupted[6] ^= 0xFF;
+    let config = Config {
+        checksum_retries: 1,
+        ..Config::default()
+    };
+
+    // One corrupted frame costs a single extra trigger
+    let mut expectations = init();
+    expectations.extend([trigger(), status(IDLE), fetch(corrupted.clone())]);
+    expectations.extend(measurement(0));
+    // Retries exhausted: the mismatch is returned
+    expectations.extend([trigger(), status(IDLE), fetch(corrupted.clone())]);
+    expectations.extend([trigger(), status(IDLE), fetch(corrupted)]);
+    let mut i2c = Mock::new(&expectations);
+    let mut delay = Recorder::default();
+
+    let mut dev = Aht20::new_with_config(i2c.clone(), config, &mut delay).unwrap();
+    assert_eq!(dev.read(&mut delay).map(|(h, _)| h.raw()), Ok(HUMIDITY));
+    let result = dev.read(&mut delay).map(|_| ());
+    assert!(matches!(result, Err(Error::Checksum { .. })));
+    i2c.done();
+}
+
+#[test]
+fn checksum_mismatch_carries_the_frame() {
+    use {aht20::Config, common::crc8, core::convert::TryInto};
+
+    // A bit flipped in transit, in the temperature bytes
+    let mut corrupted = frame();
+    corrupted[4] ^= 0x04;
+    let config = Config {
+        checksum_retries: 0,
+        ..Config::default()
+    };
+
+    let mut expectations = init();
+    expectations.extend([trigger(), status(IDLE), fetch(corrupted.clone())]);
+    let mut i2c = Mock::new(&expectations);
+    let mut delay = Recorder::default();
+
+    let mut dev = Aht20::new_with_config(i2c.clone(), config, &mut delay).unwrap();
+    let error = match dev.read(&mut delay) {
+        Ok(_) => panic!("read a corrupted frame"),
+        Err(e) => e,
+    };
+    assert_eq!(
+        error,
+        Error::Checksum {
+            expected: frame()[6],
+            computed: crc8(&corrupted[..6]),
+            frame: corrupted.as_slice().try_into().unwrap(),
+        }
+    );
+    assert!(error
+        .to_string()
+        .ends_with("frame [1c, 80, 00, 06, 04, 00, 4e])"));
+    i2c.done();
+}
+
+#[test]
+fn split_measurement() {
+    use common::frame_with_status;
+
+    let mut expectations = init();
+    expectations.extend([
+        trigger(),
+        status(BUSY),
+        fetch(frame_with_status(BUSY, 0, 0)),
+        status(IDLE),
+        fetch(frame()),
+    ]);
+    let mut i2c = Mock::new(&expectations);
+
+    let mut dev = Aht20::new(i2c.clone(), &mut Recorder::default()).unwrap();
+    dev.trigger_measurement().unwrap();
+    assert_eq!(dev.is_ready(), Ok(false));
+    assert_eq!(dev.read_measurement().map(|_| ()), Err(Error::Busy));
+    assert_eq!(dev.is_ready(), Ok(true));
+    assert_eq!(dev.read_measurement().map(|(h, _)| h.raw()), Ok(HUMIDITY));
+    i2c.done();
+}
+
+#[test]
+fn fixed_delay() {
+    use common::frame_with_status;
+
+    // Two transactions per sample, with a single wait in between
+    let mut expectations = init();
+    expectations.extend([trigger(), fetch(frame())]);
+    expectations.extend([trigger(), fetch(frame_with_status(BUSY, 0, 0))]);
+    let mut i2c = Mock::new(&expectations);
+    let mut delay = Recorder::default();
+
+    let mut dev = Aht20::new(i2c.clone(), &mut delay).unwrap();
+    delay.clear();
+    let result = dev.read_fixed_delay(&mut delay).map(|(h, _)| h.raw());
+    assert_eq!(result, Ok(HUMIDITY));
+    assert_eq!(delay.waits, [Wait::Ms(80)]);
+
+    // Still busy after the wait: no polling
+    let result = dev.read_fixed_delay(&mut delay).map(|_| ());
+    assert_eq!(result, Err(Error::Busy));
+    i2c.done();
+}
+
+#[test]
+fn waits_before_the_first_poll() {
+    use aht20::Config;
+
+    // Short polls only follow the initial wait while the sensor is still busy
+    let config = Config {
+        initial_wait_ms: 60,
+        ..Config::default()
+    };
+    let mut expectations = init();
+    expectations.extend(measurement(2));
+    let mut i2c = Mock::new(&expectations);
+    let mut delay = Recorder::default();
+
+    let mut dev = Aht20::new_with_config(i2c.clone(), config, &mut delay).unwrap();
+    delay.clear();
+    dev.read(&mut delay).unwrap();
+    assert_eq!(
+        delay.waits,
+        [Wait::Ms(60), Wait::Us(10_000), Wait::Us(10_000)]
+    );
+    i2c.done();
+}
+
+#[test]
+fn read_with_timeout() {
+    use common::frame_with_status;
+
+    let busy = || fetch(frame_with_status(BUSY, 0, 0));
+    let mut expectations = init();
+    expectations.extend([trigger(), busy(), fetch(frame())]);
+    expectations.push(trigger());
+    expectations.extend((0..5).map(|_| busy()));
+    let mut i2c = Mock::new(&expectations);
+    let mut delay = Recorder::default();
+
+    let mut dev = Aht20::new(i2c.clone(), &mut delay).unwrap();
+    let result = dev.read_with_timeout(&mut delay, 250).map(|(h, _)| h.raw());
+    assert_eq!(result, Ok(HUMIDITY));
+
+    // Four polls fit in a small budget, then the time waited is reported
+    delay.clear();
+    let result = dev.read_with_timeout(&mut delay, 20).map(|_| ());
+    assert_eq!(result, Err(Error::Timeout { waited_ms: 20 }));
+    assert_eq!(delay.waits, [Wait::Us(5000); 4]);
+    i2c.done();
+}
+
+#[test]
+fn read_with_timeout_on_a_millisecond_delay() {
+    use {aht20::hal::Delay, common::frame_with_status};
+
+    /// Delay with millisecond resolution only, recording each wait.
+    #[derive(Default)]
+    struct Millis(Vec<u16>);
+
+    impl Delay for Millis {
+        fn delay_ms(&mut self, ms: u16) {
+            self.0.push(ms);
+        }
+    }
+
+    // Polls 250µs apart are rounded up to the milliseconds really waited, so the budget holds
+    let mut expectations = init();
+    expectations.push(trigger());
+    expectations.extend((0..21).map(|_| fetch(frame_with_status(BUSY, 0, 0))));
+    let mut i2c = Mock::new(&expectations);
+    let mut delay = Millis::default();
+
+    let mut dev = Aht20::new(i2c.clone(), &mut delay).unwrap();
+    dev.set_poll_interval_us(250);
+    delay.0.clear();
+    let result = dev.read_with_timeout(&mut delay, 20).map(|_| ());
+    assert_eq!(result, Err(Error::Timeout { waited_ms: 20 }));
+    assert_eq!(delay.0, [1; 20]);
+    i2c.done();
+}
+
+#[test]
+fn raised_poll_limit() {
+    use aht20::Config;
+
+    // Ready on the eighth poll, while by default the driver gives up after the sixth
+    let (result, _) = read(&measurement(7)[..7]);
+    assert_eq!(result, Err(Error::MaxTriesExceeded));
+
+    let config = Config {
+        measurement_max_polls: 8,
+        ..Config::default()
+    };
+    let mut expectations = init();
+    expectations.extend(measurement(7));
+    let mut i2c = Mock::new(&expectations);
+    let mut delay = Recorder::default();
+    let mut dev = Aht20::new_with_config(i2c.clone(), config, &mut delay).unwrap();
+    assert_eq!(dev.read(&mut delay).map(|(h, _)| h.raw()), Ok(HUMIDITY));
+
+    // A shorter interval keeps the time the raised limit allows
+    dev.set_poll_interval_us(5000);
+    assert_eq!(dev.config().measurement_max_polls, 16);
+    i2c.done();
+}
+
+#[test]
+fn rejects_zero_polls() {
+    use aht20::{Aht20Builder, Config, ConfigError};
+
+    let config = Config {
+        calibration_max_polls: 0,
+        ..Config::default()
+    };
+    let mut i2c = Mock::new(&[]);
+    let result = Aht20::new_with_config(i2c.clone(), config, &mut Recorder::default());
+    assert!(matches!(
+        result.map_err(|e| e.error),
+        Err(Error::InvalidConfig(ConfigError::ZeroRetries))
+    ));
+    assert_eq!(
+        Aht20Builder::new().max_retries(0).config(),
+        Err(ConfigError::ZeroRetries)
+    );
+    i2c.done();
+}
+
+#[test]
+fn address_nack_while_measuring() {
+    use aht20::hal::NackAware;
+
+    /// Error of a bus on which no device acknowledged its address.
+    const NACK: MockError = MockError::Io(std::io::ErrorKind::NotFound);
+
+    let mut expectations = init();
+    expectations.extend([
+        trigger(),
+        status(IDLE).with_error(NACK),
+        status(IDLE).with_error(NACK),
+        status(IDLE),
+        fetch(frame()),
+    ]);
+    let mut i2c = Mock::new(&expectations);
+    let mut delay = Recorder::default();
+
+    let bus = NackAware::new(i2c.clone(), |e| *e == NACK);
+    let mut dev = Aht20::new(bus, &mut delay).unwrap();
+    delay.clear();
+    assert_eq!(dev.read(&mut delay).map(|(h, _)| h.raw()), Ok(HUMIDITY));
+    assert_eq!(
+        delay.waits,
+        [Wait::Ms(80), Wait::Us(10_000), Wait::Us(10_000)]
+    );
+    i2c.done();
+}
+
+#[test]
+fn rejects_busy_frames() {
+    use common::frame_with_status;
+
+    let stale = frame_with_status(BUSY, HUMIDITY, TEMPERATURE);
+    let (result, _) = read(&[trigger(), status(IDLE), fetch(stale.clone())]);
+    assert_eq!(result, Err(Error::Busy));
+
+    // Non-blocking reads keep waiting instead
+    let mut expectations = init();
+    expectations.extend([trigger(), fetch(stale), fetch(frame())]);
+    let mut i2c = Mock::new(&expectations);
+    let mut dev = Aht20::new(i2c.clone(), &mut Recorder::default()).unwrap();
+    dev.start_measurement().unwrap();
+    assert!(matches!(dev.try_read(), Err(nb::Error::WouldBlock)));
+    assert_eq!(dev.try_read().map(|(h, _)| h.raw()), Ok(HUMIDITY));
+    i2c.done();
+}
+
+#[test]
+fn polls_in_frame() {
+    use {
+        aht20::{Aht20Builder, PollStrategy},
+        common::frame_with_status,
+    };
+
+    // Three polls take three fetches, where polling the status takes three reads and a fetch
+    let busy = || fetch(frame_with_status(BUSY, 0, 0));
+    let mut expectations = init();
+    expectations.extend([trigger(), busy(), busy(), fetch(frame())]);
+    let mut i2c = Mock::new(&expectations);
+    let mut delay = Recorder::default();
+
+    let mut dev = Aht20Builder::new()
+        .poll_strategy(PollStrategy::InFrame)
+        .build(i2c.clone(), &mut delay)
+        .unwrap();
+    delay.clear();
+    assert_eq!(dev.read(&mut delay).map(|(h, _)| h.raw()), Ok(HUMIDITY));
+    assert_eq!(
+        delay.waits,
+        [Wait::Ms(80), Wait::Us(10_000), Wait::Us(10_000)]
+    );
+    i2c.done();
+}
+
+#[test]
+fn readings() {
+    let mut corrupted = frame();
+    corrupted[6] ^= 0xFF;
+    let mut expectations = init();
+    expectations.extend(measurement(0));
+    expectations.extend([trigger(), status(IDLE), fetch(corrupted)]);
+    expectations.extend(measurement(0));
+    let mut i2c = Mock::new(&expectations);
+    let mut delay = Recorder::default();
+
+    let mut dev = Aht20::new(i2c.clone(), &mut delay).unwrap();
+    delay.clear();
+
+    // Errors are yielded without ending the iteration
+    let results: Vec<_> = dev
+        .readings(&mut delay, 1000)
+        .take(3)
+        .map(|result| result.map(|(h, _)| h.raw()))
+        .collect();
+    assert_eq!(results[0], Ok(HUMIDITY));
+    assert!(matches!(results[1], Err(Error::Checksum { .. })));
+    assert_eq!(results[2], Ok(HUMIDITY));
+    assert_eq!(
+        delay.waits,
+        [
+            Wait::Ms(80),
+            Wait::Ms(1000),
+            Wait::Ms(80),
+            Wait::Ms(1000),
+            Wait::Ms(80)
+        ]
+    );
+    i2c.done();
+}
@@ -0,0 +1,167 @@
+//! Readings used as plain values by callers: trait impls, formatting, packing and averaging.
+
+use {
+    aht20::{Humidity, Reading, Temperature},
+    std::collections::{HashMap, HashSet},
+};
+
+#[test]
+fn ordered_by_raw_value() {
+    let mut temperatures = [
+        Temperature::from_celsius(25.0),
+        Temperature::from_celsius(-10.0),
+        Temperature::from_celsius(85.0),
+        Temperature::from_celsius(0.0),
+    ];
+    temperatures.sort();
+    let celsius: Vec<f32> = temperatures.iter().map(|t| t.celsius().round()).collect();
+    assert_eq!(celsius, [-10.0, 0.0, 25.0, 85.0]);
+
+    assert!(Humidity::from_rh(40.0) < Humidity::from_rh(60.0));
+    assert_eq!(
+        [Humidity::from_raw(3), Humidity::from_raw(1)].iter().max(),
+        Some(&Humidity::from_raw(3))
+    );
+}
+
+#[test]
+fn copied_compared_and_hashed() {
+    let t = Temperature::from_raw(0x60000);
+    let copy = t;
+    assert_eq!(t, copy);
+    assert_ne!(t, Temperature::from_raw(0x60001));
+    assert_eq!(format!("{:?}", Humidity::from_raw(7)), "Humidity { h: 7 }");
+
+    // Readings as map keys, e.g. counting repeated values
+    let mut counts = HashMap::new();
+    for raw in [1, 2, 1, 1].iter().copied() {
+        *counts.entry(Humidity::from_raw(raw)).or_insert(0) += 1;
+    }
+    assert_eq!(counts[&Humidity::from_raw(1)], 3);
+    assert_eq!(counts[&Humidity::from_raw(2)], 1);
+
+    let set: HashSet<_> = [t, copy].iter().copied().collect();
+    assert_eq!(set.len(), 1);
+}
+
+/// Collects what `format` writes into a string.
+fn fixed(format: impl Fn(&mut String) -> core::fmt::Result) -> String {
+    let mut s = String::new();
+    format(&mut s).unwrap();
+    s
+}
+
+#[test]
+fn display() {
+    assert_eq!(Temperature::from_raw(385_087).to_string(), "23.5 °C");
+    assert_eq!(format!("{:.2}", Temperature::from_raw(385_087)), "23.45 °C");
+    assert_eq!(format!("{:.0}", Temperature::from_raw(0)), "-50 °C");
+    assert_eq!(Humidity::from_raw(474_476).to_string(), "45.3 %");
+    assert_eq!(format!("{:.3}", Humidity::from_raw(474_476)), "45.250 %");
+
+    // Precision beyond the integer conversions is capped at three decimals
+    assert_eq!(format!("{:.5}", Humidity::from_raw(0)), "0.000 %");
+}
+
+#[test]
+fn format_fixed() {
+    // 23.450°C and -3.050°C: halves round away from zero
+    let warm = Temperature::from_raw(385_087);
+    let cold = Temperature::from_raw(246_151);
+    assert_eq!(fixed(|s| warm.format_fixed(s, 1)), "23.5");
+    assert_eq!(fixed(|s| cold.format_fixed(s, 1)), "-3.1");
+    assert_eq!(fixed(|s| cold.format_fixed(s, 2)), "-3.05");
+    assert_eq!(fixed(|s| cold.format_fixed(s, 0)), "-3");
+
+    // Just below zero, -0.040°C and -0.050°C
+    assert_eq!(
+        fixed(|s| Temperature::from_raw(261_932).format_fixed(s, 1)),
+        "0.0"
+    );
+    assert_eq!(
+        fixed(|s| Temperature::from_raw(261_880).format_fixed(s, 1)),
+        "-0.1"
+    );
+    assert_eq!(
+        fixed(|s| Temperature::from_raw(261_880).format_fixed(s, 2)),
+        "-0.05"
+    );
+
+    // 99.995% carries into the integer part
+    let h = Humidity::from_raw(1_048_519);
+    assert_eq!(fixed(|s| h.format_fixed(s, 2)), "100.00");
+    assert_eq!(
+        fixed(|s| Humidity::from_raw(473_427).format_fixed(s, 1)),
+        "45.2"
+    );
+}
+
+#[test]
+fn pack_and_unpack() {
+    let corners = [0x00000, 0xFFFFF, 0xAAAAA, 0x55555, 0x12345, 0x80001];
+    for &h in &corners {
+        for &t in &corners {
+            let reading = Reading::from_raw(h, t);
+            assert_eq!(Reading::unpack(reading.pack()), reading);
+        }
+    }
+
+    // Humidity in the high 20 bits, temperature in the low 20, as in a measurement frame
+    assert_eq!(
+        Reading::from_raw(0x12345, 0x6789A).pack(),
+        [0x12, 0x34, 0x56, 0x78, 0x9A]
+    );
+    assert_eq!(
+        Reading::from_raw(0xFFFFF, 0).pack(),
+        [0xFF, 0xFF, 0xF0, 0x00, 0x00]
+    );
+    assert_eq!(
+        Reading::from_raw(0, 0xFFFFF).pack(),
+        [0x00, 0x00, 0x0F, 0xFF, 0xFF]
+    );
+    assert_eq!(
+        Reading::unpack([0xAA, 0xAA, 0xA5, 0x55, 0x55]),
+        Reading::from_raw(0xAAAAA, 0x55555)
+    );
+}
+
+#[test]
+fn average() {
+    assert_eq!(Reading::average(&[]), None);
+    assert_eq!(Temperature::average(&[]), None);
+    assert_eq!(Humidity::average(&[]), None);
+
+    let single = Reading::from_raw(0x12345, 0x6789A);
+    assert_eq!(Reading::average(&[single]), Some(single));
+
+    // Halves round up
+    let t = |raw| Temperature::from_raw(raw);
+    assert_eq!(Temperature::average(&[t(1), t(2)]), Some(t(2)));
+    assert_eq!(Temperature::average(&[t(1), t(2), t(2)]), Some(t(2)));
+    assert_eq!(Temperature::average(&[t(1), t(1), t(2)]), Some(t(1)));
+    let h = |raw| Humidity::from_raw(raw);
+    assert_eq!(Humidity::average(&[h(10), h(13)]), Some(h(12)));
+
+    // Sums far past u32, as full-scale readings overflow it after 4097 of them
+    let burst = vec![Reading::from_raw(0xFFFFF, 0xFFFFF); 1 << 16];
+    assert_eq!(Reading::average(&burst), Some(burst[0]));
+}
+
+#[test]
+fn average_crc_verified() {
+    let verified = Reading {
+        crc_verified: true,
+        ..Reading::from_raw(1, 1)
+    };
+    let unverified = Reading::from_raw(3, 3);
+    assert!(
+        Reading::average(&[verified, verified])
+            .unwrap()
+            .crc_verified
+    );
+    assert!(
+        !Reading::average(&[verified, unverified])
+            .unwrap()
+            .crc_verified
+    );
+}
@@ -0,0 +1,291 @@
+//! Recovery of a misbehaving sensor, by the driver itself or on request.
+
+mod common;
+
+use {
+    aht20::{Aht20, Aht20Builder, Error, RecoveryError, RecoveryReport, RecoveryStep},
+    common::{
+        fetch, frame, frame_with_status, init, initialize, soft_reset, status, trigger, Recorder,
+        Wait, BUSY, HUMIDITY, IDLE, TEMPERATURE, UNCALIBRATED,
+    },
+    embedded_hal_mock::eh0::{
+        i2c::{Mock, Transaction},
+        MockError,
+    },
+};
+
+/// Transcript of a measurement staying busy past the default poll limit.
+fn stuck_busy() -> Vec<Transaction> {
+    let mut transcript = vec![trigger()];
+    transcript.extend((0..6).map(|_| status(BUSY)));
+    transcript
+}
+
+/// Transcript of a soft reset and full calibration of a sensor idle on the first poll.
+fn reset_and_recalibrate() -> [Transaction; 4] {
+    [soft_reset(), initialize(), status(IDLE), status(IDLE)]
+}
+
+#[test]
+fn stuck_busy_recovers() {
+    let mut expectations = init();
+    expectations.extend(stuck_busy());
+    expectations.extend(reset_and_recalibrate());
+    expectations.extend([trigger(), status(IDLE), fetch(frame())]);
+    let mut i2c = Mock::new(&expectations);
+    let mut delay = Recorder::default();
+
+    let mut dev = Aht20Builder::new()
+        .recover_stuck_busy(true)
+        .build(i2c.clone(), &mut delay)
+        .unwrap();
+    assert_eq!(dev.read(&mut delay).map(|(h, _)| h.raw()), Ok(HUMIDITY));
+    i2c.done();
+}
+
+#[test]
+fn stuck_busy_after_recovery() {
+    let mut expectations = init();
+    expectations.extend(stuck_busy());
+    expectations.extend(reset_and_recalibrate());
+    expectations.extend(stuck_busy());
+    let mut i2c = Mock::new(&expectations);
+    let mut delay = Recorder::default();
+
+    let mut dev = Aht20Builder::new()
+        .recover_stuck_busy(true)
+        .build(i2c.clone(), &mut delay)
+        .unwrap();
+    assert_eq!(dev.read(&mut delay).map(|_| ()), Err(Error::StuckBusy));
+    assert!(dev.needs_recovery());
+    i2c.done();
+}
+
+/// Transcript of a measurement by a sensor that lost its calibration in a brown-out.
+fn browned_out() -> [Transaction; 3] {
+    let frame = frame_with_status(UNCALIBRATED, HUMIDITY, TEMPERATURE);
+    [trigger(), status(IDLE), fetch(frame)]
+}
+
+#[test]
+fn recalibrates_after_a_brown_out() {
+    let mut expectations = init();
+    expectations.extend(browned_out());
+    expectations.extend(reset_and_recalibrate());
+    expectations.extend([trigger(), status(IDLE), fetch(frame())]);
+    let mut i2c = Mock::new(&expectations);
+    let mut delay = Recorder::default();
+
+    let mut dev = Aht20Builder::new()
+        .recover_uncalibrated(true)
+        .build(i2c.clone(), &mut delay)
+        .unwrap();
+    delay.clear();
+    assert_eq!(dev.recalibrations(), 0);
+    assert_eq!(dev.read(&mut delay).map(|(h, _)| h.raw()), Ok(HUMIDITY));
+    assert_eq!(dev.recalibrations(), 1);
+    // The soft reset's wait, then the calibration settling before its poll
+    assert_eq!(delay.waits[1..3], [Wait::Ms(20), Wait::Ms(10)]);
+    i2c.done();
+}
+
+#[test]
+fn recalibrates_once_per_read() {
+    let mut expectations = init();
+    expectations.extend(browned_out());
+    expectations.extend(reset_and_recalibrate());
+    expectations.extend(browned_out());
+    let mut i2c = Mock::new(&expectations);
+    let mut delay = Recorder::default();
+
+    let mut dev = Aht20Builder::new()
+        .recover_uncalibrated(true)
+        .build(i2c.clone(), &mut delay)
+        .unwrap();
+    assert_eq!(dev.read(&mut delay).map(|_| ()), Err(Error::Uncalibrated));
+    assert_eq!(dev.recalibrations(), 1);
+    i2c.done();
+}
+
+#[test]
+fn brown_out_without_recovery() {
+    let mut expectations = init();
+    expectations.extend(browned_out());
+    let mut i2c = Mock::new(&expectations);
+    let mut delay = Recorder::default();
+
+    let mut dev = Aht20Builder::new().build(i2c.clone(), &mut delay).unwrap();
+    assert_eq!(dev.read(&mut delay).map(|_| ()), Err(Error::Uncalibrated));
+    assert_eq!(dev.recalibrations(), 0);
+    i2c.done();
+}
+
+/// Transcript of a recovery of a sensor still calibrated: soft reset, status and verification.
+fn recovery() -> [Transaction; 3] {
+    [soft_reset(), status(IDLE), status(IDLE)]
+}
+
+/// Driver initialized, then poisoned by a measurement staying busy, before `transcript`.
+fn poisoned(transcript: &[Transaction]) -> (Mock, Aht20<Mock>) {
+    let mut expectations = init();
+    expectations.extend(stuck_busy());
+    expectations.extend_from_slice(transcript);
+    let i2c = Mock::new(&expectations);
+    let mut delay = Recorder::default();
+
+    let mut dev = Aht20::new(i2c.clone(), &mut delay).unwrap();
+    assert_eq!(
+        dev.read(&mut delay).map(|_| ()),
+        Err(Error::MaxTriesExceeded)
+    );
+    assert!(dev.needs_recovery());
+    (i2c, dev)
+}
+
+#[test]
+fn poisoned_until_recovered() {
+    let mut transcript = recovery().to_vec();
+    transcript.extend([trigger(), status(IDLE), fetch(frame())]);
+    let (mut i2c, mut dev) = poisoned(&transcript);
+    let mut delay = Recorder::default();
+
+    // Refused without any bus traffic
+    assert_eq!(dev.read(&mut delay).map(|_| ()), Err(Error::NeedsRecovery));
+    assert_eq!(dev.read(&mut delay).map(|_| ()), Err(Error::NeedsRecovery));
+
+    assert!(dev.recover(&mut delay).is_ok());
+    assert!(!dev.needs_recovery());
+    assert_eq!(dev.read(&mut delay).map(|(h, _)| h.raw()), Ok(HUMIDITY));
+    i2c.done();
+}
+
+#[test]
+fn poisoned_after_a_failed_recovery() {
+    let (mut i2c, mut dev) = poisoned(&[soft_reset(), status(IDLE), status(BUSY)]);
+    let mut delay = Recorder::default();
+
+    let error = dev.recover(&mut delay).map(|_| ()).unwrap_err();
+    assert_eq!(error.error, Error::StuckBusy);
+    assert!(dev.needs_recovery());
+    assert_eq!(dev.read(&mut delay).map(|_| ()), Err(Error::NeedsRecovery));
+    i2c.done();
+}
+
+#[test]
+fn checksum_mismatch_does_not_poison() {
+    let mut corrupted = frame();
+    corrupted[6] ^= 0xFF;
+    let mut expectations = init();
+    expectations.extend([trigger(), status(IDLE), fetch(corrupted)]);
+    expectations.extend([trigger(), status(IDLE), fetch(frame())]);
+    let mut i2c = Mock::new(&expectations);
+    let mut delay = Recorder::default();
+
+    let mut dev = Aht20Builder::new()
+        .checksum_retries(0)
+        .build(i2c.clone(), &mut delay)
+        .unwrap();
+    assert!(matches!(dev.read(&mut delay), Err(Error::Checksum { .. })));
+    assert!(!dev.needs_recovery());
+    assert_eq!(dev.read(&mut delay).map(|(h, _)| h.raw()), Ok(HUMIDITY));
+    i2c.done();
+}
+
+/// Initializes the driver with `flush` as the flush option, then recovers it against
+/// `transcript`, returning the result and whether the driver was left poisoned.
+fn recover(
+    transcript: &[Transaction],
+    flush: bool,
+) -> (Result<RecoveryReport, RecoveryError<MockError>>, bool) {
+    let mut expectations = init();
+    expectations.extend_from_slice(transcript);
+    let mut i2c = Mock::new(&expectations);
+    let mut delay = Recorder::default();
+
+    let mut dev = Aht20Builder::new()
+        .flush_on_recover(flush)
+        .build(i2c.clone(), &mut delay)
+        .unwrap();
+    let result = dev.recover(&mut delay);
+    i2c.done();
+    (result, dev.needs_recovery())
+}
+
+#[test]
+fn recover_a_healthy_sensor() {
+    let (result, poisoned) = recover(&recovery(), false);
+    let report = result.unwrap();
+    assert!(!report.reinitialized && !report.flushed);
+    // The soft reset's 20ms only
+    assert_eq!(report.delay_ms, 20);
+    assert!(!poisoned);
+}
+
+#[test]
+fn recover_an_uncalibrated_sensor() {
+    let transcript = [
+        soft_reset(),
+        status(UNCALIBRATED),
+        initialize(),
+        status(IDLE),
+        status(IDLE),
+        status(IDLE),
+    ];
+    let (result, poisoned) = recover(&transcript, false);
+    let report = result.unwrap();
+    assert!(report.reinitialized);
+    // 20ms reset, then 10ms settling before the calibration poll
+    assert_eq!(report.delay_ms, 30);
+    assert!(!poisoned);
+}
+
+#[test]
+fn recover_and_flush() {
+    let mut transcript = recovery().to_vec();
+    transcript.extend([trigger(), status(IDLE), fetch(frame())]);
+    let (result, poisoned) = recover(&transcript, true);
+    let report = result.unwrap();
+    assert!(report.flushed && !report.reinitialized);
+    assert_eq!(report.delay_ms, 100);
+    assert!(!poisoned);
+}
+
+#[test]
+fn recover_fails_at_each_step() {
+    const FAULT: MockError = MockError::Io(std::io::ErrorKind::Other);
+
+    let failure = |transcript: &[Transaction], flush| {
+        let (result, poisoned) = recover(transcript, flush);
+        let error = result.map(|_| ()).unwrap_err();
+        (error.step, error.error, poisoned)
+    };
+
+    assert_eq!(
+        failure(&[soft_reset().with_error(FAULT)], false),
+        (RecoveryStep::Reset, Error::Bus(FAULT), true)
+    );
+    assert_eq!(
+        failure(&[soft_reset(), status(IDLE).with_error(FAULT)], false),
+        (RecoveryStep::Initialize, Error::Bus(FAULT), true)
+    );
+    assert_eq!(
+        failure(&[soft_reset(), status(IDLE), status(UNCALIBRATED)], false),
+        (RecoveryStep::Verify, Error::Uncalibrated, true)
+    );
+
+    // The flush runs once the sensor is confirmed, so only a fatal error poisons it again
+    let mut transcript = recovery().to_vec();
+    transcript.extend(stuck_busy());
+    assert_eq!(
+        failure(&transcript, true),
+        (RecoveryStep::Flush, Error::MaxTriesExceeded, true)
+    );
+    let mut corrupted = frame();
+    corrupted[6] ^= 0xFF;
+    let mut transcript = recovery().to_vec();
+    transcript.extend([trigger(), status(IDLE), fetch(corrupted)]);
+    let (step, error, poisoned) = failure(&transcript, true);
+    assert_eq!(step, RecoveryStep::Flush);
+    assert!(matches!(error, Error::Checksum { .. }));
+    assert!(!poisoned);
+}
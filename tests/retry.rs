@@ -0,0 +1,141 @@
+//! Retries of failed bus transactions, at each transaction site of the driver.
+
+mod common;
+
+use {
+    aht20::{hal::RetryIf, Aht20, Error},
+    common::{
+        fetch, frame, initialize, soft_reset, status, trigger, Recorder, Wait, HUMIDITY, IDLE,
+    },
+    embedded_hal_mock::eh0::{
+        i2c::{Mock, Transaction},
+        MockError,
+    },
+};
+
+/// Error of a transaction losing arbitration to another controller, worth retrying.
+const ARBITRATION: MockError = MockError::Io(std::io::ErrorKind::Interrupted);
+/// Error of a transaction no device acknowledged.
+const NACK: MockError = MockError::Io(std::io::ErrorKind::NotFound);
+
+/// A measurement ready at once.
+fn measurement() -> Vec<Transaction> {
+    vec![trigger(), status(IDLE), fetch(frame())]
+}
+
+/// `transcript` with its transaction at `index` failing `times` times with `error` first.
+fn failing(
+    transcript: &[Transaction],
+    index: usize,
+    times: usize,
+    error: MockError,
+) -> Vec<Transaction> {
+    let mut failing = transcript[..index].to_vec();
+    failing.extend((0..times).map(|_| transcript[index].clone().with_error(error.clone())));
+    failing.extend_from_slice(&transcript[index..]);
+    failing
+}
+
+/// `transcript` up to its transaction at `index`, which fails `times` times, exhausting the
+/// retries.
+fn failing_persistently(
+    transcript: &[Transaction],
+    index: usize,
+    times: usize,
+) -> Vec<Transaction> {
+    let mut failing = transcript[..index].to_vec();
+    failing.extend((0..times).map(|_| transcript[index].clone().with_error(ARBITRATION)));
+    failing
+}
+
+/// Driver attached to a bus following `expectations`, retrying each transaction once after 50µs.
+fn attach(expectations: &[Transaction]) -> (Mock, Aht20<Mock>) {
+    let i2c = Mock::new(expectations);
+    let mut dev = Aht20::attach(i2c.clone());
+    dev.set_bus_retries(1);
+    dev.set_bus_retry_delay_us(50);
+    (i2c, dev)
+}
+
+#[test]
+fn read_fails_once() {
+    for index in 0..3 {
+        let (mut i2c, mut dev) = attach(&failing(&measurement(), index, 1, ARBITRATION));
+        let mut delay = Recorder::default();
+        assert_eq!(dev.read(&mut delay).map(|(h, _)| h.raw()), Ok(HUMIDITY));
+        assert!(delay.waits.contains(&Wait::Us(50)), "transaction {}", index);
+        i2c.done();
+    }
+}
+
+#[test]
+fn read_fails_persistently() {
+    for index in 0..3 {
+        let (mut i2c, mut dev) = attach(&failing_persistently(&measurement(), index, 2));
+        let mut delay = Recorder::default();
+        assert_eq!(
+            dev.read(&mut delay).map(|_| ()),
+            Err(Error::Bus(ARBITRATION)),
+            "transaction {}",
+            index
+        );
+        i2c.done();
+    }
+}
+
+#[test]
+fn calibrate() {
+    let calibration = [initialize(), status(IDLE), status(IDLE)];
+    for index in 0..3 {
+        let (mut i2c, mut dev) = attach(&failing(&calibration, index, 1, ARBITRATION));
+        assert_eq!(dev.calibrate(&mut Recorder::default()), Ok(()));
+        i2c.done();
+
+        let (mut i2c, mut dev) = attach(&failing_persistently(&calibration, index, 2));
+        assert_eq!(
+            dev.calibrate(&mut Recorder::default()),
+            Err(Error::Bus(ARBITRATION))
+        );
+        i2c.done();
+    }
+}
+
+#[test]
+fn reset() {
+    let (mut i2c, mut dev) = attach(&failing(&[soft_reset()], 0, 1, ARBITRATION));
+    let mut delay = Recorder::default();
+    assert_eq!(dev.reset(&mut delay), Ok(()));
+    assert_eq!(delay.waits, [Wait::Us(50), Wait::Ms(20)]);
+    i2c.done();
+
+    let (mut i2c, mut dev) = attach(&failing_persistently(&[soft_reset()], 0, 2));
+    assert_eq!(dev.reset(&mut Recorder::default()), Err(ARBITRATION));
+    i2c.done();
+}
+
+#[test]
+fn predicate() {
+    // Only arbitration losses are retried, a NACK fails at once
+    let expectations = [
+        trigger().with_error(ARBITRATION),
+        trigger(),
+        status(IDLE).with_error(NACK),
+    ];
+    let mut i2c = Mock::new(&expectations);
+    let bus = RetryIf::new(i2c.clone(), |e| *e == ARBITRATION);
+    let mut dev = Aht20::attach(bus);
+    dev.set_bus_retries(3);
+
+    let result = dev.read(&mut Recorder::default()).map(|_| ());
+    assert_eq!(result, Err(Error::Bus(NACK)));
+    i2c.done();
+}
+
+#[test]
+fn no_retries_by_default() {
+    let mut i2c = Mock::new(&failing_persistently(&measurement(), 0, 1));
+    let mut dev = Aht20::attach(i2c.clone());
+    let result = dev.read(&mut Recorder::default()).map(|_| ());
+    assert_eq!(result, Err(Error::Bus(ARBITRATION)));
+    i2c.done();
+}
@@ -0,0 +1,89 @@
+//! Application code generic over the sensor traits, run against a fake and the AHT20.
+
+mod common;
+
+use {
+    aht20::{hal::Delay, Aht20, HumiditySensor, Reading, Temperature, TemperatureSensor},
+    common::{fetch, frame, init, status, trigger, Recorder, HUMIDITY, IDLE, TEMPERATURE},
+    embedded_hal_mock::eh0::i2c::Mock,
+};
+
+/// Application code: whether the air is within `band` of `target`, taking a single reading.
+fn comfortable<S: HumiditySensor>(
+    sensor: &mut S,
+    delay: &mut impl Delay,
+    target: Temperature,
+    band: f32,
+) -> Result<bool, S::Error> {
+    let reading = sensor.reading(delay)?;
+    Ok((reading.temperature - target).kelvin().abs() <= band && reading.humidity.rh() < 60.0)
+}
+
+/// Sensor returning a fixed reading, counting its measurements.
+struct Fake {
+    reading: Reading,
+    measurements: usize,
+}
+
+impl TemperatureSensor for Fake {
+    type Error = ();
+
+    fn temperature(&mut self, _delay: &mut impl Delay) -> Result<Temperature, ()> {
+        self.measurements += 1;
+        Ok(self.reading.temperature)
+    }
+}
+
+impl HumiditySensor for Fake {
+    fn humidity(&mut self, _delay: &mut impl Delay) -> Result<aht20::Humidity, ()> {
+        self.measurements += 1;
+        Ok(self.reading.humidity)
+    }
+}
+
+#[test]
+fn fake_sensor() {
+    let mut fake = Fake {
+        reading: Reading::from_raw(HUMIDITY, TEMPERATURE),
+        measurements: 0,
+    };
+    let mut delay = Recorder::default();
+
+    let target = Temperature::from_celsius(24.0);
+    assert_eq!(comfortable(&mut fake, &mut delay, target, 2.0), Ok(true));
+    assert_eq!(comfortable(&mut fake, &mut delay, target, 0.5), Ok(false));
+
+    // The default reading measures each quantity separately
+    assert_eq!(fake.measurements, 4);
+}
+
+#[test]
+fn aht20_takes_a_single_measurement() {
+    let mut expectations = init();
+    expectations.extend_from_slice(&[trigger(), status(IDLE), fetch(frame())]);
+    let mut i2c = Mock::new(&expectations);
+    let mut delay = Recorder::default();
+
+    let mut dev = Aht20::new(i2c.clone(), &mut delay).unwrap();
+    let target = Temperature::from_celsius(24.0);
+    assert_eq!(comfortable(&mut dev, &mut delay, target, 2.0), Ok(true));
+    i2c.done();
+}
+
+#[test]
+fn aht20_single_quantities() {
+    let mut expectations = init();
+    for _ in 0..2 {
+        expectations.extend_from_slice(&[trigger(), status(IDLE), fetch(frame())]);
+    }
+    let mut i2c = Mock::new(&expectations);
+    let mut delay = Recorder::default();
+
+    let mut dev = Aht20::new(i2c.clone(), &mut delay).unwrap();
+    assert_eq!(
+        dev.temperature(&mut delay),
+        Ok(Temperature::from_raw(TEMPERATURE))
+    );
+    assert_eq!(dev.humidity(&mut delay).map(|h| h.raw()), Ok(HUMIDITY));
+    i2c.done();
+}
@@ -0,0 +1,63 @@
+//! `serde` support, round-tripping readings through a binary and a text format.
+
+#![cfg(feature = "serde")]
+
+use aht20::{HumanReadable, Humidity, Reading, Temperature};
+
+fn reading() -> Reading {
+    Reading {
+        humidity: Humidity::from_raw(0x80000),
+        temperature: Temperature::from_raw(0x60000),
+        crc_verified: false,
+    }
+}
+
+#[test]
+fn postcard_round_trip() {
+    let mut buf = [0u8; 16];
+    let bytes = postcard::to_slice(&reading(), &mut buf).unwrap();
+    // Two varints of three bytes each
+    assert_eq!(bytes.len(), 6);
+    assert_eq!(postcard::from_bytes::<Reading>(bytes), Ok(reading()));
+
+    // Lossless at the ends of the raw range
+    for raw in [0, 1, 0xFFFFF].iter().copied() {
+        let bytes = postcard::to_slice(&Temperature::from_raw(raw), &mut buf).unwrap();
+        assert_eq!(
+            postcard::from_bytes::<Temperature>(bytes),
+            Ok(Temperature::from_raw(raw))
+        );
+    }
+}
+
+#[test]
+fn json_round_trip() {
+    let json = serde_json::to_string(&reading()).unwrap();
+    assert_eq!(json, r#"{"humidity":524288,"temperature":393216}"#);
+    assert_eq!(serde_json::from_str::<Reading>(&json).unwrap(), reading());
+
+    // The CRC flag isn't serialized, so it reads back as unchecked
+    let checked = Reading {
+        crc_verified: true,
+        ..reading()
+    };
+    let json = serde_json::to_string(&checked).unwrap();
+    assert!(!serde_json::from_str::<Reading>(&json).unwrap().crc_verified);
+}
+
+#[test]
+fn rejects_values_beyond_20_bits() {
+    assert!(serde_json::from_str::<Humidity>("1048575").is_ok());
+    assert!(serde_json::from_str::<Humidity>("1048576").is_err());
+    assert!(serde_json::from_str::<Reading>(r#"{"humidity":0,"temperature":2000000}"#).is_err());
+}
+
+#[test]
+fn human_readable() {
+    let json = serde_json::to_string(&HumanReadable(reading())).unwrap();
+    assert_eq!(json, r#"{"humidity_rh":50.0,"temperature_celsius":25.0}"#);
+    assert_eq!(
+        serde_json::to_string(&HumanReadable(Humidity::from_raw(0x40000))).unwrap(),
+        "25.0"
+    );
+}
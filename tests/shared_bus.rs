@@ -0,0 +1,94 @@
+//! The driver sharing its bus with other devices.
+
+mod common;
+
+use {
+    aht20::Aht20,
+    common::{fetch, frame, init, status, trigger, HUMIDITY, IDLE},
+    embedded_hal_mock::eh0::{
+        delay::NoopDelay,
+        i2c::{Mock, Transaction},
+        MockError,
+    },
+};
+
+/// Address of another device on the bus.
+const OTHER: u8 = 0x50;
+
+#[cfg(feature = "eh1")]
+#[test]
+fn embedded_hal_bus_devices() {
+    use {
+        aht20::eh1::Eh1,
+        common::eh1,
+        core::cell::RefCell,
+        embedded_hal_1::i2c::I2c,
+        embedded_hal_bus::i2c::RefCellDevice,
+        embedded_hal_mock::eh1::{
+            delay::NoopDelay,
+            i2c::{Mock, Transaction},
+        },
+    };
+
+    let expectations = [
+        eh1::write(&[0xBA]),
+        eh1::status(IDLE),
+        eh1::write(&[0xAC, 0x33, 0x00]),
+        vec![Transaction::write_read(OTHER, vec![0x00], vec![0x42])],
+        eh1::write_read(&[0x71], frame()),
+    ]
+    .concat();
+    let mut mock = Mock::new(&expectations);
+    let bus = RefCell::new(mock.clone());
+    let mut delay = NoopDelay::new();
+
+    let mut dev = Aht20::new(Eh1(RefCellDevice::new(&bus)), &mut Eh1(&mut delay)).unwrap();
+    let mut other = RefCellDevice::new(&bus);
+
+    // Another device uses the bus while the sensor measures
+    dev.trigger_measurement().unwrap();
+    let buf = &mut [0u8; 1];
+    other.write_read(OTHER, &[0x00], buf).unwrap();
+    let (humidity, _) = dev.read_measurement().unwrap();
+
+    assert_eq!((buf[0], humidity.raw()), (0x42, HUMIDITY));
+    mock.done();
+}
+
+#[test]
+fn drivers_are_send_and_sync() {
+    fn assert_send_sync<T: Send + Sync>() {}
+
+    assert_send_sync::<Aht20<Mock>>();
+    #[cfg(feature = "eh1")]
+    assert_send_sync::<Aht20<aht20::eh1::Eh1<embedded_hal_mock::eh1::i2c::Mock>>>();
+}
+
+#[test]
+fn shared_bus_proxies() {
+    use embedded_hal::blocking::i2c::Write;
+
+    let mut expectations = init();
+    expectations.extend([
+        trigger().with_error(MockError::Io(std::io::ErrorKind::Other)),
+        trigger(),
+        Transaction::write(OTHER, vec![0x00]),
+        status(IDLE),
+        fetch(frame()),
+    ]);
+    let mut mock = Mock::new(&expectations);
+    let bus = shared_bus::BusManagerSimple::new(mock.clone());
+    let mut delay = NoopDelay::new();
+
+    let mut dev = Aht20::new(bus.acquire_i2c(), &mut delay).unwrap();
+    dev.set_bus_retries(1);
+    let mut other = bus.acquire_i2c();
+
+    dev.trigger_measurement().unwrap();
+    other.write(OTHER, &[0x00]).unwrap();
+    assert!(dev.is_ready().unwrap());
+    let (humidity, _) = dev.read_measurement().unwrap();
+
+    assert_eq!(humidity.raw(), HUMIDITY);
+    mock.done();
+}
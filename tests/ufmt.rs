@@ -0,0 +1,57 @@
+//! `ufmt` support, writing into a fixed-capacity buffer as on a target without an allocator.
+
+#![cfg(feature = "ufmt")]
+
+use {
+    aht20::{Error, Humidity, Temperature},
+    heapless::String,
+    ufmt::uwrite,
+};
+
+/// Formats `value` with `uDisplay` into a fixed-capacity string.
+fn display(value: &impl ufmt::uDisplay) -> String<96> {
+    let mut s = String::new();
+    uwrite!(s, "{}", value).unwrap();
+    s
+}
+
+#[test]
+fn readings() {
+    assert_eq!(display(&Temperature::from_raw(0x60000)), "25.0 °C");
+    assert_eq!(display(&Humidity::from_raw(0x80000)), "50.0 %");
+
+    // -3.050°C and -0.040°C, rounded like `Display`
+    assert_eq!(display(&Temperature::from_raw(246_151)), "-3.1 °C");
+    assert_eq!(display(&Temperature::from_raw(261_932)), "0.0 °C");
+    assert_eq!(display(&Humidity::from_raw(0)), "0.0 %");
+    assert_eq!(display(&Humidity::from_raw(0xFFFFF)), "100.0 %");
+
+    // Each matches the `core::fmt` output
+    for raw in (0..=0xFFFFF).step_by(1009) {
+        let t = Temperature::from_raw(raw);
+        assert_eq!(display(&t).as_str(), t.to_string());
+    }
+}
+
+#[test]
+fn errors() {
+    let e: Error<u8> = Error::Bus(4);
+    assert_eq!(display(&e), "bus error: 4");
+    let e: Error<u8> = Error::Timeout { waited_ms: 50 };
+    assert_eq!(display(&e), "measurement timed out after 50 ms");
+    let e: Error<u8> = Error::OutOfRange {
+        humidity: 1,
+        temperature: 2,
+    };
+    assert_eq!(
+        display(&e),
+        "reading out of range (raw humidity 1, raw temperature 2)"
+    );
+}
+
+#[test]
+fn debug() {
+    let mut s: String<32> = String::new();
+    uwrite!(s, "{:?}", Humidity::from_raw(7)).unwrap();
+    assert_eq!(s, "Humidity { h: 7 }");
+}
@@ -0,0 +1,224 @@
+//! Sensor models sharing the driver: their commands, initialization and CRC defaults.
+
+mod common;
+
+use {
+    aht20::{commands::Command, consts::ADDRESS, Aht20, CrcMode, Variant},
+    common::{frame, Recorder, Wait, HUMIDITY, IDLE, TEMPERATURE},
+    embedded_hal_mock::eh0::i2c::{Mock, Transaction},
+};
+
+mod aht10 {
+    use super::*;
+
+    /// Status read answered with `status`, through the AHT10's `0x00`.
+    fn status(status: u8) -> Transaction {
+        Transaction::write_read(ADDRESS, vec![0x00], vec![status])
+    }
+
+    #[test]
+    fn commands() {
+        assert_eq!(
+            Command::Initialize.bytes_for(Variant::Aht10),
+            [0xE1, 0x08, 0x00]
+        );
+        assert_eq!(Command::ReadStatus.bytes_for(Variant::Aht10), [0x00]);
+
+        // Shared with the AHT20
+        assert_eq!(
+            Command::TriggerMeasurement.bytes_for(Variant::Aht10),
+            [0xAC, 0x33, 0x00]
+        );
+        assert_eq!(Command::SoftReset.bytes_for(Variant::Aht10), [0xBA]);
+    }
+
+    #[test]
+    fn measures_through_its_status_command() {
+        let expectations = [
+            Transaction::write(ADDRESS, vec![0xBA]),
+            status(IDLE),
+            Transaction::write(ADDRESS, vec![0xAC, 0x33, 0x00]),
+            status(IDLE),
+            Transaction::write_read(ADDRESS, vec![0x00], frame()),
+        ];
+        let mut i2c = Mock::new(&expectations);
+        let mut delay = Recorder::default();
+
+        let mut dev = Aht20::new_aht10(i2c.clone(), &mut delay).unwrap();
+        assert_eq!(dev.config().variant, Variant::Aht10);
+        let (humidity, temperature) = dev.read(&mut delay).unwrap();
+        assert_eq!(humidity.raw(), HUMIDITY);
+        assert_eq!(temperature.raw(), TEMPERATURE);
+        assert_eq!(delay.waits, [Wait::Ms(20), Wait::Ms(80)]);
+        i2c.done();
+    }
+
+    #[test]
+    fn accepts_frames_without_crc() {
+        let mut no_crc = frame();
+        no_crc[6] = 0xFF;
+        let expectations = [
+            Transaction::write(ADDRESS, vec![0xBA]),
+            status(IDLE),
+            Transaction::write(ADDRESS, vec![0xAC, 0x33, 0x00]),
+            status(IDLE),
+            Transaction::write_read(ADDRESS, vec![0x00], no_crc),
+        ];
+        let mut i2c = Mock::new(&expectations);
+        let mut delay = Recorder::default();
+
+        let mut dev = Aht20::new_aht10(i2c.clone(), &mut delay).unwrap();
+        assert_eq!(dev.config().crc, CrcMode::Optional);
+        let reading = dev.read_reading(&mut delay).unwrap();
+        assert_eq!(reading.humidity.raw(), HUMIDITY);
+        assert!(!reading.crc_verified);
+        i2c.done();
+    }
+}
+
+/// Initializes a driver for `variant` against `expectations`.
+fn new(variant: Variant, expectations: &[Transaction]) -> (Aht20<Mock>, Mock, Recorder) {
+    let i2c = Mock::new(expectations);
+    let mut delay = Recorder::default();
+    match Aht20::new_with_variant(i2c.clone(), variant, &mut delay) {
+        Ok(dev) => (dev, i2c, delay),
+        Err(e) => panic!("{:?} failed to initialize: {:?}", variant, e.error),
+    }
+}
+
+mod aht21 {
+    use {
+        super::*,
+        common::{initialize, soft_reset, status, UNCALIBRATED},
+    };
+
+    #[test]
+    fn initialized_like_the_aht20() {
+        let expectations = [
+            soft_reset(),
+            status(UNCALIBRATED),
+            initialize(),
+            status(IDLE),
+            status(IDLE),
+        ];
+        let (dev, mut i2c, _) = new(Variant::Aht21, &expectations);
+        assert_eq!(dev.config().crc, CrcMode::Required);
+        i2c.done();
+    }
+}
+
+mod aht25 {
+    use {
+        super::*,
+        common::{soft_reset, status, UNCALIBRATED},
+    };
+
+    #[test]
+    fn never_sent_the_initialization_command() {
+        // Any write of 0xBE would fail the mock, as the sensor NACKs it
+        let expectations = [soft_reset(), status(UNCALIBRATED)];
+        let mut i2c = Mock::new(&expectations);
+        let mut delay = Recorder::default();
+        let mut dev = match Aht20::new_aht25(i2c.clone(), &mut delay) {
+            Ok(dev) => dev,
+            Err(e) => panic!("failed to initialize: {:?}", e.error),
+        };
+        assert_eq!(dev.config().variant, Variant::Aht25);
+        i2c.done();
+
+        // Calibration only polls it
+        let expectations = [status(IDLE), status(IDLE)];
+        i2c.update_expectations(&expectations);
+        dev.calibrate(&mut delay).unwrap();
+        i2c.done();
+    }
+
+    #[test]
+    fn requires_crc() {
+        let (dev, mut i2c, _) = new(Variant::Aht25, &common::init());
+        assert_eq!(dev.config().crc, CrcMode::Required);
+        i2c.done();
+    }
+}
+
+mod aht30 {
+    use {
+        super::*,
+        common::{fetch, soft_reset, status, trigger, UNCALIBRATED},
+    };
+
+    #[test]
+    fn never_sent_the_initialization_command() {
+        let expectations = [soft_reset(), status(UNCALIBRATED)];
+        let (_, mut i2c, _) = new(Variant::Aht30, &expectations);
+        i2c.done();
+    }
+
+    #[test]
+    fn tolerates_a_missing_crc() {
+        let mut no_crc = frame();
+        no_crc[6] = 0xFF;
+        let mut expectations = common::init();
+        expectations.extend([trigger(), status(IDLE), fetch(no_crc)]);
+        let (mut dev, mut i2c, mut delay) = new(Variant::Aht30, &expectations);
+
+        assert_eq!(dev.config().crc, CrcMode::Optional);
+        let reading = dev.read_reading(&mut delay).unwrap();
+        assert_eq!(reading.temperature.raw(), TEMPERATURE);
+        assert!(!reading.crc_verified);
+        i2c.done();
+    }
+}
+
+mod dht20 {
+    use {
+        super::*,
+        aht20::consts::DHT20_SETTLE_MS,
+        common::{initialize, soft_reset, status, UNCALIBRATED},
+    };
+
+    #[test]
+    fn skips_the_initialization_command_when_calibrated() {
+        let expectations = [soft_reset(), status(IDLE)];
+        let mut i2c = Mock::new(&expectations);
+        let mut delay = Recorder::default();
+        let mut dev = match Aht20::new_dht20(i2c.clone(), &mut delay) {
+            Ok(dev) => dev,
+            Err(e) => panic!("failed to initialize: {:?}", e.error),
+        };
+        assert_eq!(dev.config().variant, Variant::Dht20);
+
+        // The reset, then the pause before the first trigger
+        assert_eq!(delay.waits, [Wait::Ms(20), Wait::Ms(DHT20_SETTLE_MS)]);
+        i2c.done();
+
+        // An explicit calibration checks the status first as well
+        let expectations = [status(IDLE), status(IDLE), status(IDLE)];
+        i2c.update_expectations(&expectations);
+        dev.calibrate(&mut delay).unwrap();
+        i2c.done();
+    }
+
+    #[test]
+    fn initializes_an_uncalibrated_sensor() {
+        let expectations = [
+            soft_reset(),
+            status(UNCALIBRATED),
+            status(UNCALIBRATED),
+            initialize(),
+            status(IDLE),
+            status(IDLE),
+        ];
+        let (_, mut i2c, delay) = new(Variant::Dht20, &expectations);
+        assert_eq!(
+            delay.waits,
+            [Wait::Ms(20), Wait::Ms(10), Wait::Ms(DHT20_SETTLE_MS)]
+        );
+        i2c.done();
+    }
+
+    #[test]
+    fn settle_time() {
+        assert_eq!(DHT20_SETTLE_MS, 10);
+    }
+}